@@ -0,0 +1,488 @@
+//! Off-chain helpers for building escrow instructions and reading escrow
+//! accounts. Enabled with the `client` feature (and always in tests).
+//!
+//! Builders take every account explicitly, in program order; the `derive`
+//! constructors fill in PDAs and ATAs for the common case. Transfer-hook
+//! accounts are not part of any builder: push them onto `Instruction::accounts`
+//! after building.
+//!
+//! `EscrowEvent` decodes the lifecycle events the program logs.
+//!
+//! The same interface is described for other languages in `idl/escrow.json`.
+
+use solana_instruction::{AccountMeta, Instruction};
+use solana_pubkey::Pubkey;
+
+use crate::events::{EscrowMade, EscrowRefunded, EscrowTaken, EscrowUpdated};
+use crate::instructions::EscrowInstrctions;
+use crate::state::Escrow;
+
+pub const TOKEN_PROGRAM_ID: Pubkey =
+    Pubkey::from_str_const("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+pub const TOKEN_2022_PROGRAM_ID: Pubkey =
+    Pubkey::from_str_const("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    Pubkey::from_str_const("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+pub const SYSTEM_PROGRAM_ID: Pubkey = Pubkey::from_str_const("11111111111111111111111111111111");
+
+pub fn program_id() -> Pubkey {
+    Pubkey::from(crate::ID)
+}
+
+/// Escrow PDA: `["escrow", maker, seed.to_le_bytes()]`.
+pub fn find_escrow_address(maker: &Pubkey, seed: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"escrow", maker.as_ref(), &seed.to_le_bytes()],
+        &program_id(),
+    )
+}
+
+pub fn associated_token_address(wallet: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[wallet.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+/// The escrow's vault: its ATA for mint_a.
+pub fn vault_address(escrow: &Pubkey, mint_a: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    associated_token_address(escrow, mint_a, token_program)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MakeAccounts {
+    pub maker: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub escrow: Pubkey,
+    pub maker_ata: Pubkey,
+    pub vault: Pubkey,
+    pub token_program: Pubkey,
+}
+
+impl MakeAccounts {
+    /// Derives the escrow, maker ATA and vault. Returns the escrow bump too,
+    /// since Make takes it as an argument.
+    pub fn derive(
+        maker: Pubkey,
+        mint_a: Pubkey,
+        mint_b: Pubkey,
+        seed: u64,
+        token_program: Pubkey,
+    ) -> (Self, u8) {
+        let (escrow, bump) = find_escrow_address(&maker, seed);
+        let accounts = Self {
+            maker,
+            mint_a,
+            mint_b,
+            escrow,
+            maker_ata: associated_token_address(&maker, &mint_a, &token_program),
+            vault: vault_address(&escrow, &mint_a, &token_program),
+            token_program,
+        };
+        (accounts, bump)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MakeArgs {
+    pub bump: u8,
+    pub seed: u64,
+    pub amount_to_receive: u64,
+    pub amount_to_give: u64,
+    /// Unix timestamp after which the offer can't be taken; `0` = never.
+    pub expires_at: i64,
+    /// The only wallet allowed to take the offer; `None` = anyone.
+    pub allowed_taker: Option<Pubkey>,
+}
+
+impl MakeArgs {
+    pub fn to_bytes(&self) -> [u8; 65] {
+        let mut data = [0u8; 65];
+        data[0] = self.bump;
+        data[1..9].copy_from_slice(&self.seed.to_le_bytes());
+        data[9..17].copy_from_slice(&self.amount_to_receive.to_le_bytes());
+        data[17..25].copy_from_slice(&self.amount_to_give.to_le_bytes());
+        data[25..33].copy_from_slice(&self.expires_at.to_le_bytes());
+        if let Some(taker) = self.allowed_taker {
+            data[33..65].copy_from_slice(taker.as_ref());
+        }
+        data
+    }
+}
+
+pub fn make(accounts: &MakeAccounts, args: &MakeArgs) -> Instruction {
+    make_with(EscrowInstrctions::Make, accounts, args)
+}
+
+pub fn make_v2(accounts: &MakeAccounts, args: &MakeArgs) -> Instruction {
+    make_with(EscrowInstrctions::MakeV2, accounts, args)
+}
+
+fn make_with(ix: EscrowInstrctions, accounts: &MakeAccounts, args: &MakeArgs) -> Instruction {
+    Instruction {
+        program_id: program_id(),
+        accounts: vec![
+            AccountMeta::new(accounts.maker, true),
+            AccountMeta::new(accounts.mint_a, false),
+            AccountMeta::new(accounts.mint_b, false),
+            AccountMeta::new(accounts.escrow, false),
+            AccountMeta::new(accounts.maker_ata, false),
+            AccountMeta::new(accounts.vault, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            AccountMeta::new_readonly(accounts.token_program, false),
+            AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        ],
+        data: [&[ix as u8][..], &args.to_bytes()].concat(),
+    }
+}
+
+/// Accounts for Take and TakePartial.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TakeAccounts {
+    pub taker: Pubkey,
+    pub maker: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub escrow: Pubkey,
+    pub taker_ata_a: Pubkey,
+    pub taker_ata_b: Pubkey,
+    pub maker_ata_b: Pubkey,
+    pub maker_ata_a: Pubkey,
+    pub vault: Pubkey,
+    pub token_program_a: Pubkey,
+    pub token_program_b: Pubkey,
+}
+
+impl TakeAccounts {
+    pub fn derive(
+        taker: Pubkey,
+        maker: Pubkey,
+        mint_a: Pubkey,
+        mint_b: Pubkey,
+        seed: u64,
+        token_program_a: Pubkey,
+        token_program_b: Pubkey,
+    ) -> Self {
+        let (escrow, _) = find_escrow_address(&maker, seed);
+        Self {
+            taker,
+            maker,
+            mint_a,
+            mint_b,
+            escrow,
+            taker_ata_a: associated_token_address(&taker, &mint_a, &token_program_a),
+            taker_ata_b: associated_token_address(&taker, &mint_b, &token_program_b),
+            maker_ata_b: associated_token_address(&maker, &mint_b, &token_program_b),
+            maker_ata_a: associated_token_address(&maker, &mint_a, &token_program_a),
+            vault: vault_address(&escrow, &mint_a, &token_program_a),
+            token_program_a,
+            token_program_b,
+        }
+    }
+
+    fn metas(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(self.taker, true),
+            AccountMeta::new(self.maker, false),
+            // Writable so the vault's withheld transfer fees can be harvested
+            AccountMeta::new(self.mint_a, false),
+            AccountMeta::new_readonly(self.mint_b, false),
+            AccountMeta::new(self.escrow, false),
+            AccountMeta::new(self.taker_ata_a, false),
+            AccountMeta::new(self.taker_ata_b, false),
+            AccountMeta::new(self.maker_ata_b, false),
+            AccountMeta::new(self.maker_ata_a, false),
+            AccountMeta::new(self.vault, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            AccountMeta::new_readonly(self.token_program_a, false),
+            AccountMeta::new_readonly(self.token_program_b, false),
+        ]
+    }
+}
+
+/// Take at the terms the taker quoted: fails if the maker has since raised
+/// `amount_to_receive` above `expected_amount_to_receive` or cut
+/// `amount_to_give` below `expected_amount_to_give`.
+pub fn take(
+    accounts: &TakeAccounts,
+    expected_amount_to_receive: u64,
+    expected_amount_to_give: u64,
+) -> Instruction {
+    Instruction {
+        program_id: program_id(),
+        accounts: accounts.metas(),
+        data: [
+            &[EscrowInstrctions::Take as u8][..],
+            &expected_amount_to_receive.to_le_bytes(),
+            &expected_amount_to_give.to_le_bytes(),
+        ]
+        .concat(),
+    }
+}
+
+/// Pays `fill` of mint_b for a pro-rata share of mint_a; fails if that share
+/// is less than `min_payout`.
+pub fn take_partial(accounts: &TakeAccounts, fill: u64, min_payout: u64) -> Instruction {
+    Instruction {
+        program_id: program_id(),
+        accounts: accounts.metas(),
+        data: [
+            &[EscrowInstrctions::TakePartial as u8][..],
+            &fill.to_le_bytes(),
+            &min_payout.to_le_bytes(),
+        ]
+        .concat(),
+    }
+}
+
+/// Accounts for Cancel, Expire and Update.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RefundAccounts {
+    pub maker: Pubkey,
+    pub mint_a: Pubkey,
+    pub escrow: Pubkey,
+    pub maker_ata: Pubkey,
+    pub vault: Pubkey,
+    pub token_program: Pubkey,
+}
+
+impl RefundAccounts {
+    pub fn derive(maker: Pubkey, mint_a: Pubkey, seed: u64, token_program: Pubkey) -> Self {
+        let (escrow, _) = find_escrow_address(&maker, seed);
+        Self {
+            maker,
+            mint_a,
+            escrow,
+            maker_ata: associated_token_address(&maker, &mint_a, &token_program),
+            vault: vault_address(&escrow, &mint_a, &token_program),
+            token_program,
+        }
+    }
+}
+
+pub fn cancel(accounts: &RefundAccounts) -> Instruction {
+    Instruction {
+        program_id: program_id(),
+        accounts: vec![
+            AccountMeta::new(accounts.maker, true),
+            AccountMeta::new(accounts.mint_a, false),
+            AccountMeta::new(accounts.escrow, false),
+            AccountMeta::new(accounts.maker_ata, false),
+            AccountMeta::new(accounts.vault, false),
+            AccountMeta::new_readonly(accounts.token_program, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        ],
+        data: vec![EscrowInstrctions::Cancel as u8],
+    }
+}
+
+/// Permissionless: the maker need not sign, any fee payer can send it.
+pub fn expire(accounts: &RefundAccounts) -> Instruction {
+    Instruction {
+        program_id: program_id(),
+        accounts: vec![
+            AccountMeta::new(accounts.maker, false),
+            AccountMeta::new(accounts.mint_a, false),
+            AccountMeta::new(accounts.escrow, false),
+            AccountMeta::new(accounts.maker_ata, false),
+            AccountMeta::new(accounts.vault, false),
+            AccountMeta::new_readonly(accounts.token_program, false),
+        ],
+        data: vec![EscrowInstrctions::Expire as u8],
+    }
+}
+
+/// Sets new terms on an open escrow; the vault is topped up from or drained
+/// to `maker_ata` until it holds `amount_to_give`.
+pub fn update(accounts: &RefundAccounts, amount_to_receive: u64, amount_to_give: u64) -> Instruction {
+    Instruction {
+        program_id: program_id(),
+        accounts: vec![
+            AccountMeta::new(accounts.maker, true),
+            AccountMeta::new_readonly(accounts.mint_a, false),
+            AccountMeta::new(accounts.escrow, false),
+            AccountMeta::new(accounts.maker_ata, false),
+            AccountMeta::new(accounts.vault, false),
+            AccountMeta::new_readonly(accounts.token_program, false),
+        ],
+        data: [
+            &[EscrowInstrctions::Update as u8][..],
+            &amount_to_receive.to_le_bytes(),
+            &amount_to_give.to_le_bytes(),
+        ]
+        .concat(),
+    }
+}
+
+/// Decoded escrow account.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EscrowAccount {
+    pub maker: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    /// Remaining mint_b the maker wants (decreases with partial fills).
+    pub amount_to_receive: u64,
+    /// Remaining mint_a in the vault (decreases with partial fills).
+    pub amount_to_give: u64,
+    pub expires_at: i64,
+    pub seed: u64,
+    pub bump: u8,
+    pub allowed_taker: Option<Pubkey>,
+}
+
+impl EscrowAccount {
+    /// Decodes raw account data; `None` unless it is exactly `Escrow::LEN` bytes.
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        if data.len() != Escrow::LEN {
+            return None;
+        }
+        let pubkey = |at: usize| Pubkey::new_from_array(data[at..at + 32].try_into().unwrap());
+        let word = |at: usize| -> [u8; 8] { data[at..at + 8].try_into().unwrap() };
+        Some(Self {
+            maker: pubkey(0),
+            mint_a: pubkey(32),
+            mint_b: pubkey(64),
+            amount_to_receive: u64::from_le_bytes(word(96)),
+            amount_to_give: u64::from_le_bytes(word(104)),
+            expires_at: i64::from_le_bytes(word(112)),
+            seed: u64::from_le_bytes(word(120)),
+            bump: data[128],
+            allowed_taker: Some(pubkey(129)).filter(|taker| *taker != Pubkey::default()),
+        })
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at != 0 && now >= self.expires_at
+    }
+}
+
+/// Lifecycle event logged by the program; see `events.rs` for the encoding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EscrowEvent {
+    /// Make/MakeV2 opened and funded an escrow.
+    Made {
+        escrow: Pubkey,
+        maker: Pubkey,
+        mint_a: Pubkey,
+        mint_b: Pubkey,
+        seed: u64,
+        amount_to_receive: u64,
+        amount_to_give: u64,
+        expires_at: i64,
+        allowed_taker: Option<Pubkey>,
+    },
+    /// Take/TakePartial: `fill` mint_b paid for `payout` mint_a; the escrow
+    /// closed once `remaining` is 0.
+    Taken {
+        escrow: Pubkey,
+        maker: Pubkey,
+        taker: Pubkey,
+        fill: u64,
+        payout: u64,
+        remaining: u64,
+    },
+    /// Cancel (`expired == false`) or Expire returned `amount` mint_a.
+    Refunded {
+        escrow: Pubkey,
+        maker: Pubkey,
+        amount: u64,
+        expired: bool,
+    },
+    /// Update set new terms.
+    Updated {
+        escrow: Pubkey,
+        amount_to_receive: u64,
+        amount_to_give: u64,
+    },
+}
+
+impl EscrowEvent {
+    /// Decodes one `sol_log_data` payload; `None` if it isn't an escrow event.
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let (discriminator, rest) = data.split_first_chunk::<8>()?;
+        let mut fields = EventFields(rest);
+        let event = match *discriminator {
+            EscrowMade::DISCRIMINATOR => Self::Made {
+                escrow: fields.pubkey()?,
+                maker: fields.pubkey()?,
+                mint_a: fields.pubkey()?,
+                mint_b: fields.pubkey()?,
+                seed: fields.u64()?,
+                amount_to_receive: fields.u64()?,
+                amount_to_give: fields.u64()?,
+                expires_at: fields.u64()? as i64,
+                allowed_taker: fields.option_pubkey()?,
+            },
+            EscrowTaken::DISCRIMINATOR => Self::Taken {
+                escrow: fields.pubkey()?,
+                maker: fields.pubkey()?,
+                taker: fields.pubkey()?,
+                fill: fields.u64()?,
+                payout: fields.u64()?,
+                remaining: fields.u64()?,
+            },
+            EscrowRefunded::DISCRIMINATOR => Self::Refunded {
+                escrow: fields.pubkey()?,
+                maker: fields.pubkey()?,
+                amount: fields.u64()?,
+                expired: fields.bool()?,
+            },
+            EscrowUpdated::DISCRIMINATOR => Self::Updated {
+                escrow: fields.pubkey()?,
+                amount_to_receive: fields.u64()?,
+                amount_to_give: fields.u64()?,
+            },
+            _ => return None,
+        };
+        fields.0.is_empty().then_some(event)
+    }
+
+    /// Decodes the escrow events in a transaction's log messages, in order.
+    ///
+    /// Only `Program data:` lines logged while the escrow program itself is
+    /// executing are read, so another program in the transaction cannot forge
+    /// escrow events by logging the same bytes.
+    pub fn from_logs<S: AsRef<str>>(logs: &[S]) -> Vec<Self> {
+        program_logs::program_data(logs, &program_id().to_string())
+            .iter()
+            .filter_map(|data| Self::decode(data))
+            .collect()
+    }
+}
+
+/// Borsh field reader over an event payload.
+struct EventFields<'a>(&'a [u8]);
+
+impl EventFields<'_> {
+    fn take<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let (head, rest) = self.0.split_first_chunk::<N>()?;
+        self.0 = rest;
+        Some(*head)
+    }
+
+    fn pubkey(&mut self) -> Option<Pubkey> {
+        self.take::<32>().map(Pubkey::new_from_array)
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.take::<8>().map(u64::from_le_bytes)
+    }
+
+    fn bool(&mut self) -> Option<bool> {
+        match self.take::<1>()? {
+            [0] => Some(false),
+            [1] => Some(true),
+            _ => None,
+        }
+    }
+
+    fn option_pubkey(&mut self) -> Option<Option<Pubkey>> {
+        if self.bool()? {
+            self.pubkey().map(Some)
+        } else {
+            Some(None)
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use pinocchio::error::ProgramError;
+
+/// Escrow failures, surfaced to clients as `ProgramError::Custom(code)`.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EscrowError {
+    /// Escrow address does not match `["escrow", maker, seed, bump]`
+    InvalidEscrowPda = 0,
+    /// Make targeted an escrow account that already exists
+    EscrowAlreadyExists = 1,
+    /// Escrow account is not owned by this program
+    InvalidEscrowOwner = 2,
+    /// Escrow account has the wrong size or alignment
+    InvalidEscrowData = 3,
+    /// Maker account does not match the escrow's stored maker
+    MakerMismatch = 4,
+    /// mint_a does not match the escrow's stored mint_a
+    MintAMismatch = 5,
+    /// mint_b does not match the escrow's stored mint_b
+    MintBMismatch = 6,
+    /// Token account is not owned by the expected wallet
+    TokenAccountOwnerMismatch = 7,
+    /// Token account holds a different mint than expected
+    TokenAccountMintMismatch = 8,
+    /// Account is not a valid token account
+    InvalidTokenAccount = 9,
+    /// Account is not a valid mint
+    InvalidMint = 10,
+    /// Passed program is neither SPL Token nor Token-2022
+    InvalidTokenProgram = 11,
+    /// Passed token program does not own the mint
+    TokenProgramMismatch = 12,
+    /// Make was given an `expires_at` that is not in the future
+    ExpiryInPast = 13,
+    /// Take after the escrow's deadline
+    EscrowExpired = 14,
+    /// Expire before the deadline, or on an escrow without one
+    EscrowNotExpired = 15,
+    /// Fill is zero or exceeds the remaining `amount_to_receive`
+    InvalidFillAmount = 16,
+    /// Fill is too small to pay out any mint_a
+    FillTooSmall = 17,
+    /// The vault received nothing from the deposit
+    EmptyDeposit = 18,
+    /// Vault is not the escrow PDA's associated token account for mint_a
+    InvalidVault = 19,
+    /// Make or Update was given a zero amount
+    InvalidAmount = 20,
+    /// The escrow's terms moved past the taker's expected amounts
+    SlippageExceeded = 21,
+    /// Taker is not the wallet the escrow is reserved for
+    TakerNotAllowed = 22,
+}
+
+impl From<EscrowError> for ProgramError {
+    fn from(e: EscrowError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
@@ -0,0 +1,143 @@
+//! Structured lifecycle events.
+//!
+//! Events are logged with `sol_log_data` in Anchor's event layout: an 8-byte
+//! discriminator (`sha256("event:<Name>")[..8]`) followed by the Borsh-encoded
+//! fields. They show up as `Program data: <base64>` log lines, so indexers that
+//! already parse Anchor events can read them, and `client::EscrowEvent` decodes
+//! them in Rust. Pubkeys are 32 raw bytes, integers little-endian, `Option` a
+//! 0/1 tag followed by the value when present.
+
+use pinocchio::Address;
+
+use crate::state::Escrow;
+
+/// An escrow was opened and funded by Make/MakeV2.
+pub struct EscrowMade<'a> {
+    pub escrow: &'a Address,
+    pub state: &'a Escrow,
+}
+
+impl EscrowMade<'_> {
+    pub const DISCRIMINATOR: [u8; 8] = [45, 225, 74, 129, 146, 57, 61, 98];
+
+    pub fn emit(&self) {
+        let mut event = EventData::<{ 8 + 4 * 32 + 4 * 8 + 33 }>::new(Self::DISCRIMINATOR);
+        event.put(self.escrow.as_ref());
+        event.put(self.state.maker().as_ref());
+        event.put(self.state.mint_a().as_ref());
+        event.put(self.state.mint_b().as_ref());
+        event.put(&self.state.seed().to_le_bytes());
+        event.put(&self.state.amount_to_receive().to_le_bytes());
+        event.put(&self.state.amount_to_give().to_le_bytes());
+        event.put(&self.state.expires_at().to_le_bytes());
+        event.put_option(self.state.allowed_taker().as_ref());
+        event.log();
+    }
+}
+
+/// Take or TakePartial filled (part of) an escrow. `remaining` is the mint_b
+/// still wanted afterwards; 0 means the escrow was closed.
+pub struct EscrowTaken<'a> {
+    pub escrow: &'a Address,
+    pub maker: &'a Address,
+    pub taker: &'a Address,
+    /// mint_b paid to the maker.
+    pub fill: u64,
+    /// mint_a paid to the taker.
+    pub payout: u64,
+    pub remaining: u64,
+}
+
+impl EscrowTaken<'_> {
+    pub const DISCRIMINATOR: [u8; 8] = [164, 79, 50, 26, 174, 149, 92, 158];
+
+    pub fn emit(&self) {
+        let mut event = EventData::<{ 8 + 3 * 32 + 3 * 8 }>::new(Self::DISCRIMINATOR);
+        event.put(self.escrow.as_ref());
+        event.put(self.maker.as_ref());
+        event.put(self.taker.as_ref());
+        event.put(&self.fill.to_le_bytes());
+        event.put(&self.payout.to_le_bytes());
+        event.put(&self.remaining.to_le_bytes());
+        event.log();
+    }
+}
+
+/// Cancel or Expire returned the vault to the maker and closed the escrow.
+pub struct EscrowRefunded<'a> {
+    pub escrow: &'a Address,
+    pub maker: &'a Address,
+    /// mint_a returned to the maker.
+    pub amount: u64,
+    /// `true` for Expire, `false` for Cancel.
+    pub expired: bool,
+}
+
+impl EscrowRefunded<'_> {
+    pub const DISCRIMINATOR: [u8; 8] = [132, 209, 49, 109, 135, 138, 28, 81];
+
+    pub fn emit(&self) {
+        let mut event = EventData::<{ 8 + 2 * 32 + 8 + 1 }>::new(Self::DISCRIMINATOR);
+        event.put(self.escrow.as_ref());
+        event.put(self.maker.as_ref());
+        event.put(&self.amount.to_le_bytes());
+        event.put(&[self.expired as u8]);
+        event.log();
+    }
+}
+
+/// Update changed an open escrow's terms.
+pub struct EscrowUpdated<'a> {
+    pub escrow: &'a Address,
+    pub amount_to_receive: u64,
+    pub amount_to_give: u64,
+}
+
+impl EscrowUpdated<'_> {
+    pub const DISCRIMINATOR: [u8; 8] = [206, 217, 203, 80, 31, 243, 215, 165];
+
+    pub fn emit(&self) {
+        let mut event = EventData::<{ 8 + 32 + 2 * 8 }>::new(Self::DISCRIMINATOR);
+        event.put(self.escrow.as_ref());
+        event.put(&self.amount_to_receive.to_le_bytes());
+        event.put(&self.amount_to_give.to_le_bytes());
+        event.log();
+    }
+}
+
+/// Stack buffer sized for the largest encoding of one event.
+struct EventData<const N: usize> {
+    data: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> EventData<N> {
+    fn new(discriminator: [u8; 8]) -> Self {
+        let mut event = Self { data: [0u8; N], len: 0 };
+        event.put(&discriminator);
+        event
+    }
+
+    fn put(&mut self, bytes: &[u8]) {
+        self.data[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
+    }
+
+    fn put_option(&mut self, address: Option<&Address>) {
+        match address {
+            Some(address) => {
+                self.put(&[1]);
+                self.put(address.as_ref());
+            }
+            None => self.put(&[0]),
+        }
+    }
+
+    fn log(&self) {
+        #[cfg(target_os = "solana")]
+        unsafe {
+            let fields: [&[u8]; 1] = [&self.data[..self.len]];
+            pinocchio::syscalls::sol_log_data(fields.as_ptr() as *const u8, fields.len() as u64);
+        }
+    }
+}
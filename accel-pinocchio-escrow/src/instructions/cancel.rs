@@ -4,7 +4,9 @@ use pinocchio::{
     error::ProgramError,
 };
 
-use crate::state::Escrow;
+use crate::events::EscrowRefunded;
+use crate::token_interface::{self, CloseAccount, TransferChecked};
+use crate::validation;
 
 pub fn process_cancel_instruction(
     accounts: &[AccountView],
@@ -16,32 +18,26 @@ pub fn process_cancel_instruction(
         escrow_account,
         maker_ata,
         vault,
-        _token_program,
-        _system_program @ ..
+        token_program,
+        _system_program,
+        hook_accounts @ ..
     ] = accounts
     else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
-    // Verify maker is signer
-    if !maker.is_signer() {
-        return Err(ProgramError::MissingRequiredSignature);
-    }
+    validation::check_signer(maker)?;
 
-    // Load escrow state
-    let escrow_state = Escrow::from_account_info(escrow_account)?;
+    token_interface::check_mint_program(mint_a, token_program)?;
+    let decimals = token_interface::mint_decimals(mint_a)?;
 
-    // Verify maker matches signer
-    if escrow_state.maker() != *maker.address() {
-        return Err(ProgramError::InvalidAccountData);
-    }
+    // Load escrow state; checks owner, PDA, maker and mint_a
+    let escrow_state = validation::load_escrow(escrow_account, maker, mint_a)?;
 
-    // Verify mint_a matches
-    if escrow_state.mint_a() != *mint_a.address() {
-        return Err(ProgramError::InvalidAccountData);
-    }
+    validation::check_vault(vault, escrow_account, mint_a, token_program)?;
+    validation::check_token_account(maker_ata, maker.address(), mint_a.address())?;
 
-    let amount_to_give = escrow_state.amount_to_give();
+    let seed_bytes = escrow_state.seed().to_le_bytes();
     let bump = escrow_state.bump;
 
     // Build escrow PDA signer seeds
@@ -49,24 +45,34 @@ pub fn process_cancel_instruction(
     let seed = [
         Seed::from(b"escrow"),
         Seed::from(maker.address().as_array()),
+        Seed::from(&seed_bytes),
         Seed::from(&bump_bytes),
     ];
     let signer = Signer::from(&seed);
 
-    // 1) Transfer mint_a: vault -> maker_ata (escrow PDA signs)
-    pinocchio_token::instructions::Transfer {
+    // 1) Transfer the whole vault, including any tokens donated to it, so it
+    // can close: vault -> maker_ata (escrow PDA signs)
+    let amount = token_interface::load_token_account(vault)?.amount;
+    TransferChecked {
         from: vault,
+        mint: mint_a,
         to: maker_ata,
         authority: escrow_account,
-        amount: amount_to_give,
+        amount,
+        decimals,
+        token_program,
+        extra_accounts: hook_accounts,
     }
     .invoke_signed(&[signer.clone()])?;
 
-    // 2) Close vault ATA -> maker (escrow PDA signs)
-    pinocchio_token::instructions::CloseAccount {
+    // 2) Close vault ATA -> maker (escrow PDA signs); Token-2022 first needs
+    // any withheld transfer fees harvested to the mint
+    token_interface::harvest_withheld_fees(vault, mint_a, token_program)?;
+    CloseAccount {
         account: vault,
         destination: maker,
         authority: escrow_account,
+        token_program,
     }
     .invoke_signed(&[signer])?;
 
@@ -83,5 +89,13 @@ pub fn process_cancel_instruction(
         }
     }
 
+    EscrowRefunded {
+        escrow: escrow_account.address(),
+        maker: maker.address(),
+        amount,
+        expired: false,
+    }
+    .emit();
+
     Ok(())
 }
@@ -0,0 +1,109 @@
+use pinocchio::{
+    AccountView, ProgramResult,
+    cpi::{Seed, Signer},
+    error::ProgramError,
+    sysvars::{Sysvar, clock::Clock},
+};
+
+use crate::errors::EscrowError;
+use crate::events::EscrowRefunded;
+use crate::token_interface::{self, CloseAccount, TransferChecked};
+use crate::validation;
+
+/// Permissionless: once the deadline passes, anyone can return the vault to the
+/// maker and close the escrow.
+pub fn process_expire_instruction(
+    accounts: &[AccountView],
+    _data: &[u8],
+) -> ProgramResult {
+    let [
+        maker,
+        mint_a,
+        escrow_account,
+        maker_ata,
+        vault,
+        token_program,
+        hook_accounts @ ..
+    ] = accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    token_interface::check_mint_program(mint_a, token_program)?;
+    let decimals = token_interface::mint_decimals(mint_a)?;
+
+    // Load escrow state; checks owner, PDA, maker and mint_a
+    let escrow_state = validation::load_escrow(escrow_account, maker, mint_a)?;
+
+    // Refund destination must be the maker's own account for mint_a, since the
+    // caller chooses the accounts
+    validation::check_vault(vault, escrow_account, mint_a, token_program)?;
+    validation::check_token_account(maker_ata, maker.address(), mint_a.address())?;
+
+    // Only escrows with a deadline that has passed can be expired
+    if !escrow_state.is_expired(Clock::get()?.unix_timestamp) {
+        return Err(EscrowError::EscrowNotExpired.into());
+    }
+
+    let seed_bytes = escrow_state.seed().to_le_bytes();
+    let bump = escrow_state.bump;
+
+    // Build escrow PDA signer seeds
+    let bump_bytes = [bump];
+    let seed = [
+        Seed::from(b"escrow"),
+        Seed::from(maker.address().as_array()),
+        Seed::from(&seed_bytes),
+        Seed::from(&bump_bytes),
+    ];
+    let signer = Signer::from(&seed);
+
+    // 1) Transfer the whole vault, including any tokens donated to it, so it
+    // can close: vault -> maker_ata (escrow PDA signs)
+    let amount = token_interface::load_token_account(vault)?.amount;
+    TransferChecked {
+        from: vault,
+        mint: mint_a,
+        to: maker_ata,
+        authority: escrow_account,
+        amount,
+        decimals,
+        token_program,
+        extra_accounts: hook_accounts,
+    }
+    .invoke_signed(&[signer.clone()])?;
+
+    // 2) Close vault ATA -> maker (escrow PDA signs); Token-2022 first needs
+    // any withheld transfer fees harvested to the mint
+    token_interface::harvest_withheld_fees(vault, mint_a, token_program)?;
+    CloseAccount {
+        account: vault,
+        destination: maker,
+        authority: escrow_account,
+        token_program,
+    }
+    .invoke_signed(&[signer])?;
+
+    // 3) Close escrow account -> return lamports to maker
+    {
+        let escrow_lamports = escrow_account.lamports();
+        maker.set_lamports(maker.lamports() + escrow_lamports);
+        escrow_account.set_lamports(0);
+
+        // Zero out escrow data
+        unsafe {
+            let mut data = escrow_account.try_borrow_mut()?;
+            core::ptr::write_bytes(data.as_mut_ptr(), 0, data.len());
+        }
+    }
+
+    EscrowRefunded {
+        escrow: escrow_account.address(),
+        maker: maker.address(),
+        amount,
+        expired: true,
+    }
+    .emit();
+
+    Ok(())
+}
@@ -1,10 +1,41 @@
 use pinocchio::{
-    AccountView, ProgramResult, cpi::{Seed, Signer}, error::ProgramError, sysvars::{Sysvar, rent::Rent}
+    AccountView, ProgramResult, cpi::{Seed, Signer}, error::ProgramError, sysvars::{Sysvar, clock::Clock, rent::Rent}
 };
-use pinocchio_pubkey::derive_address;
+use pinocchio_codec::{FromBytes, Reader};
 use pinocchio_system::instructions::CreateAccount;
 
+use crate::errors::EscrowError;
+use crate::events::EscrowMade;
 use crate::state::Escrow;
+use crate::token_interface::{self, TransferChecked};
+use crate::validation;
+
+/// Make/MakeV2 payload:
+/// [bump: u8, seed: u64, amount_to_receive: u64, amount_to_give: u64, expires_at: i64,
+///  allowed_taker: [u8; 32] (all zeroes = anyone may take)]
+pub struct MakeData {
+    pub bump: u8,
+    pub seed: u64,
+    pub amount_to_receive: u64,
+    pub amount_to_give: u64,
+    pub expires_at: i64,
+    pub allowed_taker: [u8; 32],
+}
+
+impl FromBytes for MakeData {
+    const LEN: usize = 65;
+
+    fn read(reader: &mut Reader) -> Result<Self, ProgramError> {
+        Ok(Self {
+            bump: reader.read()?,
+            seed: reader.read()?,
+            amount_to_receive: reader.read()?,
+            amount_to_give: reader.read()?,
+            expires_at: reader.read()?,
+            allowed_taker: reader.read()?,
+        })
+    }
+}
 
 pub fn process_make_instruction(
     accounts: &[AccountView],
@@ -20,33 +51,37 @@ pub fn process_make_instruction(
         escrow_ata,
         system_program,
         token_program,
-        _associated_token_program@ ..
+        _associated_token_program,
+        hook_accounts @ ..
     ] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
-    {
-        let maker_ata_state = pinocchio_token::state::TokenAccount::from_account_view(&maker_ata)?;
-        if maker_ata_state.owner() != maker.address() {
-            return Err(ProgramError::IllegalOwner);
-        }
-        if maker_ata_state.mint() != mint_a.address() {
-            return Err(ProgramError::InvalidAccountData);
-        }
-    } // drop Ref to release borrow before CPIs
+    validation::check_signer(maker)?;
+
+    let MakeData { bump, seed: escrow_seed, amount_to_receive, amount_to_give, expires_at, allowed_taker } =
+        MakeData::from_bytes(data)?;
+    // An escrow asking nothing in return would hand the deposit to any taker
+    if amount_to_receive == 0 {
+        return Err(EscrowError::InvalidAmount.into());
+    }
 
-    let bump = data[0];
-    let seed = [b"escrow".as_ref(), maker.address().as_ref(), &[bump]];
-    let seeds = &seed[..];
+    // mint_a may belong to SPL Token or Token-2022; the passed program must own it
+    token_interface::check_mint_program(mint_a, token_program)?;
+    let decimals = token_interface::mint_decimals(mint_a)?;
 
-    let escrow_account_pda = derive_address(&seed, None, &crate::ID.to_bytes());
-    assert_eq!(escrow_account_pda, *escrow_account.address().as_array());
+    validation::check_token_account(maker_ata, maker.address(), mint_a.address())?;
 
-    let amount_to_receive = unsafe{ *(data.as_ptr().add(1) as *const u64) };
-    let amount_to_give = unsafe{ *(data.as_ptr().add(9) as *const u64) };
+    let seed_bytes = escrow_seed.to_le_bytes();
+    validation::check_escrow_pda(escrow_account, maker.address(), &seed_bytes, bump)?;
 
-    let bump = [bump.to_le()];
-    let seed = [Seed::from(b"escrow"), Seed::from(maker.address().as_array()), Seed::from(&bump)];
+    // A deadline, if set, must be in the future
+    if expires_at != 0 && expires_at <= Clock::get()?.unix_timestamp {
+        return Err(EscrowError::ExpiryInPast.into());
+    }
+
+    let bump_bytes = [bump];
+    let seed = [Seed::from(b"escrow"), Seed::from(maker.address().as_array()), Seed::from(&seed_bytes), Seed::from(&bump_bytes)];
     let seeds = Signer::from(&seed);
 
     unsafe {
@@ -67,12 +102,15 @@ pub fn process_make_instruction(
                 escrow_state.set_mint_a(mint_a.address());
                 escrow_state.set_mint_b(mint_b.address());
                 escrow_state.set_amount_to_receive(amount_to_receive);
-                escrow_state.set_amount_to_give(amount_to_give);  
-                escrow_state.bump = data[0];
+                escrow_state.set_amount_to_give(amount_to_give);
+                escrow_state.set_expires_at(expires_at);
+                escrow_state.set_seed(escrow_seed);
+                escrow_state.bump = bump;
+                escrow_state.set_allowed_taker(&allowed_taker);
             }
         }
         else {
-            return Err(ProgramError::IllegalOwner);
+            return Err(EscrowError::EscrowAlreadyExists.into());
         }
     }
 
@@ -85,12 +123,26 @@ pub fn process_make_instruction(
         system_program: system_program,
     }.invoke()?;
 
-    pinocchio_token::instructions::Transfer {
+    TransferChecked {
         from: maker_ata,
+        mint: mint_a,
         to: escrow_ata,
         authority: maker,
         amount: amount_to_give,
+        decimals,
+        token_program,
+        extra_accounts: hook_accounts,
     }.invoke()?;
 
+    // Record what the vault actually received: transfer-fee mints withhold part of it
+    let deposited = token_interface::load_token_account(escrow_ata)?.amount;
+    if deposited == 0 {
+        return Err(EscrowError::EmptyDeposit.into());
+    }
+    let escrow_state = Escrow::from_account_info(escrow_account)?;
+    escrow_state.set_amount_to_give(deposited);
+
+    EscrowMade { escrow: escrow_account.address(), state: escrow_state }.emit();
+
     Ok(())
 }
\ No newline at end of file
@@ -2,19 +2,17 @@ use pinocchio::{
     AccountView, ProgramResult,
     cpi::{Seed, Signer},
     error::ProgramError,
-    sysvars::{Sysvar, rent::Rent},
+    sysvars::{Sysvar, clock::Clock, rent::Rent},
 };
-use pinocchio_pubkey::derive_address;
+use pinocchio_codec::FromBytes;
 use pinocchio_system::instructions::CreateAccount;
 
+use crate::errors::EscrowError;
+use crate::events::EscrowMade;
+use crate::instructions::make::MakeData;
 use crate::state::Escrow;
-
-#[derive(wincode::SchemaRead, wincode::SchemaWrite)]
-struct MakeData {
-    bump: u8,
-    amount_to_receive: u64,
-    amount_to_give: u64,
-}
+use crate::token_interface::{self, TransferChecked};
+use crate::validation;
 
 pub fn process_make_instruction_v2(
     accounts: &[AccountView],
@@ -29,41 +27,49 @@ pub fn process_make_instruction_v2(
         escrow_ata,
         system_program,
         token_program,
-        _associated_token_program @ ..
+        _associated_token_program,
+        hook_accounts @ ..
     ] = accounts
     else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
+    validation::check_signer(maker)?;
+
+    // mint_a may belong to SPL Token or Token-2022; the passed program must own it
+    token_interface::check_mint_program(mint_a, token_program)?;
+    let decimals = token_interface::mint_decimals(mint_a)?;
+
     // Validate maker ATA
-    {
-        let maker_ata_state = pinocchio_token::state::TokenAccount::from_account_view(&maker_ata)?;
-        if maker_ata_state.owner() != maker.address() {
-            return Err(ProgramError::IllegalOwner);
-        }
-        if maker_ata_state.mint() != mint_a.address() {
-            return Err(ProgramError::InvalidAccountData);
-        }
-    } // drop Ref to release borrow before CPIs
+    validation::check_token_account(maker_ata, maker.address(), mint_a.address())?;
 
-    // Deserialize instruction data using wincode
-    let make_data: MakeData =
-        wincode::deserialize(data).map_err(|_| ProgramError::InvalidInstructionData)?;
+    // Bounds-checked decode of the shared Make payload
+    let make_data = MakeData::from_bytes(data)?;
 
     let bump = make_data.bump;
+    let seed_bytes = make_data.seed.to_le_bytes();
     let amount_to_receive = make_data.amount_to_receive;
     let amount_to_give = make_data.amount_to_give;
+    let expires_at = make_data.expires_at;
+    // An escrow asking nothing in return would hand the deposit to any taker
+    if amount_to_receive == 0 {
+        return Err(EscrowError::InvalidAmount.into());
+    }
+
+    // A deadline, if set, must be in the future
+    if expires_at != 0 && expires_at <= Clock::get()?.unix_timestamp {
+        return Err(EscrowError::ExpiryInPast.into());
+    }
 
     // Verify escrow PDA
-    let seed = [b"escrow".as_ref(), maker.address().as_ref(), &[bump]];
-    let escrow_account_pda = derive_address(&seed, None, &crate::ID.to_bytes());
-    assert_eq!(escrow_account_pda, *escrow_account.address().as_array());
+    validation::check_escrow_pda(escrow_account, maker.address(), &seed_bytes, bump)?;
 
     // Build signer seeds
     let bump_bytes = [bump.to_le()];
     let seed = [
         Seed::from(b"escrow"),
         Seed::from(maker.address().as_array()),
+        Seed::from(&seed_bytes),
         Seed::from(&bump_bytes),
     ];
     let seeds = Signer::from(&seed);
@@ -87,10 +93,13 @@ pub fn process_make_instruction_v2(
                 escrow_state.set_mint_b(mint_b.address());
                 escrow_state.set_amount_to_receive(amount_to_receive);
                 escrow_state.set_amount_to_give(amount_to_give);
+                escrow_state.set_expires_at(expires_at);
+                escrow_state.set_seed(make_data.seed);
                 escrow_state.bump = bump;
+                escrow_state.set_allowed_taker(&make_data.allowed_taker);
             }
         } else {
-            return Err(ProgramError::IllegalOwner);
+            return Err(EscrowError::EscrowAlreadyExists.into());
         }
     }
 
@@ -104,13 +113,27 @@ pub fn process_make_instruction_v2(
     }
     .invoke()?;
 
-    pinocchio_token::instructions::Transfer {
+    TransferChecked {
         from: maker_ata,
+        mint: mint_a,
         to: escrow_ata,
         authority: maker,
         amount: amount_to_give,
+        decimals,
+        token_program,
+        extra_accounts: hook_accounts,
     }
     .invoke()?;
 
+    // Record what the vault actually received: transfer-fee mints withhold part of it
+    let deposited = token_interface::load_token_account(escrow_ata)?.amount;
+    if deposited == 0 {
+        return Err(EscrowError::EmptyDeposit.into());
+    }
+    let escrow_state = Escrow::from_account_info(escrow_account)?;
+    escrow_state.set_amount_to_give(deposited);
+
+    EscrowMade { escrow: escrow_account.address(), state: escrow_state }.emit();
+
     Ok(())
 }
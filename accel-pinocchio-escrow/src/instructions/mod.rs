@@ -2,11 +2,17 @@ pub mod make;
 pub mod take;
 pub mod cancel;
 pub mod make_v2;
+pub mod expire;
+pub mod take_partial;
+pub mod update;
 
 pub use make::*;
 pub use take::*;
 pub use cancel::*;
 pub use make_v2::*;
+pub use expire::*;
+pub use take_partial::*;
+pub use update::*;
 use pinocchio::error::ProgramError;
 
 pub enum EscrowInstrctions {
@@ -14,6 +20,9 @@ pub enum EscrowInstrctions {
     Take = 1,
     Cancel = 2,
     MakeV2 = 3,
+    Expire = 4,
+    TakePartial = 5,
+    Update = 6,
 }
 
 impl TryFrom<&u8> for EscrowInstrctions {
@@ -25,6 +34,9 @@ impl TryFrom<&u8> for EscrowInstrctions {
             1 => Ok(EscrowInstrctions::Take),
             2 => Ok(EscrowInstrctions::Cancel),
             3 => Ok(EscrowInstrctions::MakeV2),
+            4 => Ok(EscrowInstrctions::Expire),
+            5 => Ok(EscrowInstrctions::TakePartial),
+            6 => Ok(EscrowInstrctions::Update),
             _ => Err(ProgramError::InvalidInstructionData),
         }
     }
@@ -2,13 +2,37 @@ use pinocchio::{
     AccountView, ProgramResult,
     cpi::{Seed, Signer},
     error::ProgramError,
+    sysvars::{Sysvar, clock::Clock},
 };
+use pinocchio_codec::{FromBytes, Reader};
 
-use crate::state::Escrow;
+use crate::errors::EscrowError;
+use crate::events::EscrowTaken;
+use crate::token_interface::{self, CloseAccount, TransferChecked};
+use crate::validation;
+
+/// Take payload, the taker's slippage guard:
+/// [expected_amount_to_receive: u64, expected_amount_to_give: u64].
+/// The take fails if the maker now asks for more mint_b or offers less mint_a.
+pub struct TakeData {
+    pub expected_amount_to_receive: u64,
+    pub expected_amount_to_give: u64,
+}
+
+impl FromBytes for TakeData {
+    const LEN: usize = 16;
+
+    fn read(reader: &mut Reader) -> Result<Self, ProgramError> {
+        Ok(Self {
+            expected_amount_to_receive: reader.read()?,
+            expected_amount_to_give: reader.read()?,
+        })
+    }
+}
 
 pub fn process_take_instruction(
     accounts: &[AccountView],
-    _data: &[u8],
+    data: &[u8],
 ) -> ProgramResult {
     let [
         taker,
@@ -19,38 +43,61 @@ pub fn process_take_instruction(
         taker_ata_a,
         taker_ata_b,
         maker_ata_b,
+        maker_ata_a,
         vault,
         _system_program,
-        _token_program,
-        _associated_token_program @ ..
+        token_program_a,
+        token_program_b,
+        hook_accounts @ ..
     ] = accounts
     else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
-    // Verify taker is signer
-    if !taker.is_signer() {
-        return Err(ProgramError::MissingRequiredSignature);
-    }
+    validation::check_signer(taker)?;
 
-    // Load escrow state
-    let escrow_state = Escrow::from_account_info(escrow_account)?;
+    // Each mint may live under SPL Token or Token-2022
+    token_interface::check_mint_program(mint_a, token_program_a)?;
+    token_interface::check_mint_program(mint_b, token_program_b)?;
+    let decimals_a = token_interface::mint_decimals(mint_a)?;
+    let decimals_b = token_interface::mint_decimals(mint_b)?;
 
-    // Verify maker matches
-    if escrow_state.maker() != *maker.address() {
-        return Err(ProgramError::InvalidAccountData);
+    // Load escrow state; checks owner, PDA, maker and mint_a
+    let escrow_state = validation::load_escrow(escrow_account, maker, mint_a)?;
+    if escrow_state.mint_b() != *mint_b.address() {
+        return Err(EscrowError::MintBMismatch.into());
     }
 
-    // Verify mints match
-    if escrow_state.mint_a() != *mint_a.address() {
-        return Err(ProgramError::InvalidAccountData);
+    // Reject offers whose deadline has passed
+    if escrow_state.is_expired(Clock::get()?.unix_timestamp) {
+        return Err(EscrowError::EscrowExpired.into());
     }
-    if escrow_state.mint_b() != *mint_b.address() {
-        return Err(ProgramError::InvalidAccountData);
+
+    // Private offers can only be filled by the taker the maker named
+    if !escrow_state.can_be_taken_by(taker.address()) {
+        return Err(EscrowError::TakerNotAllowed.into());
     }
 
+    // The maker can Update an open escrow, so the taker must say which terms
+    // they agreed to
+    let expected = TakeData::from_bytes(data)?;
+    if escrow_state.amount_to_receive() > expected.expected_amount_to_receive
+        || escrow_state.amount_to_give() < expected.expected_amount_to_give
+    {
+        return Err(EscrowError::SlippageExceeded.into());
+    }
+
+    // Token accounts must be the escrow's vault and the parties' own accounts
+    // for the right mints
+    validation::check_vault(vault, escrow_account, mint_a, token_program_a)?;
+    validation::check_token_account(taker_ata_a, taker.address(), mint_a.address())?;
+    validation::check_token_account(taker_ata_b, taker.address(), mint_b.address())?;
+    validation::check_token_account(maker_ata_b, maker.address(), mint_b.address())?;
+    validation::check_token_account(maker_ata_a, maker.address(), mint_a.address())?;
+
     let amount_to_receive = escrow_state.amount_to_receive();
     let amount_to_give = escrow_state.amount_to_give();
+    let seed_bytes = escrow_state.seed().to_le_bytes();
     let bump = escrow_state.bump;
 
     // Build escrow PDA signer seeds
@@ -58,37 +105,65 @@ pub fn process_take_instruction(
     let seed = [
         Seed::from(b"escrow"),
         Seed::from(maker.address().as_array()),
+        Seed::from(&seed_bytes),
         Seed::from(&bump_bytes),
     ];
     let signer = Signer::from(&seed);
 
     // 1) Transfer mint_b: taker_ata_b -> maker_ata_b (taker signs)
-    pinocchio_token::instructions::Transfer {
+    TransferChecked {
         from: taker_ata_b,
+        mint: mint_b,
         to: maker_ata_b,
         authority: taker,
         amount: amount_to_receive,
+        decimals: decimals_b,
+        token_program: token_program_b,
+        extra_accounts: hook_accounts,
     }
     .invoke()?;
 
     // 2) Transfer mint_a: vault -> taker_ata_a (escrow PDA signs)
-    pinocchio_token::instructions::Transfer {
+    TransferChecked {
         from: vault,
+        mint: mint_a,
         to: taker_ata_a,
         authority: escrow_account,
         amount: amount_to_give,
+        decimals: decimals_a,
+        token_program: token_program_a,
+        extra_accounts: hook_accounts,
     }
     .invoke_signed(&[signer.clone()])?;
 
-    // 3) Close vault ATA -> maker (escrow PDA signs)
-    pinocchio_token::instructions::CloseAccount {
+    // 3) Return tokens donated to the vault to the maker, or the close fails
+    let surplus = token_interface::load_token_account(vault)?.amount;
+    if surplus > 0 {
+        TransferChecked {
+            from: vault,
+            mint: mint_a,
+            to: maker_ata_a,
+            authority: escrow_account,
+            amount: surplus,
+            decimals: decimals_a,
+            token_program: token_program_a,
+            extra_accounts: hook_accounts,
+        }
+        .invoke_signed(&[signer.clone()])?;
+    }
+
+    // 4) Close vault ATA -> maker (escrow PDA signs); Token-2022 first needs
+    // any withheld transfer fees harvested to the mint
+    token_interface::harvest_withheld_fees(vault, mint_a, token_program_a)?;
+    CloseAccount {
         account: vault,
         destination: maker,
         authority: escrow_account,
+        token_program: token_program_a,
     }
     .invoke_signed(&[signer])?;
 
-    // 4) Close escrow account -> return lamports to maker
+    // 5) Close escrow account -> return lamports to maker
     {
         let escrow_lamports = escrow_account.lamports();
         maker.set_lamports(maker.lamports() + escrow_lamports);
@@ -101,5 +176,15 @@ pub fn process_take_instruction(
         }
     }
 
+    EscrowTaken {
+        escrow: escrow_account.address(),
+        maker: maker.address(),
+        taker: taker.address(),
+        fill: amount_to_receive,
+        payout: amount_to_give,
+        remaining: 0,
+    }
+    .emit();
+
     Ok(())
 }
@@ -0,0 +1,197 @@
+use pinocchio::{
+    AccountView, ProgramResult,
+    cpi::{Seed, Signer},
+    error::ProgramError,
+    sysvars::{Sysvar, clock::Clock},
+};
+
+use pinocchio_codec::{FromBytes, Reader};
+
+use crate::errors::EscrowError;
+use crate::events::EscrowTaken;
+use crate::token_interface::{self, CloseAccount, TransferChecked};
+use crate::validation;
+
+/// TakePartial payload: [fill: u64, min_payout: u64].
+/// `min_payout` is the taker's slippage guard: the fill fails if it would pay
+/// out less mint_a, e.g. because the maker repriced with Update.
+pub struct TakePartialData {
+    pub fill: u64,
+    pub min_payout: u64,
+}
+
+impl FromBytes for TakePartialData {
+    const LEN: usize = 16;
+
+    fn read(reader: &mut Reader) -> Result<Self, ProgramError> {
+        Ok(Self {
+            fill: reader.read()?,
+            min_payout: reader.read()?,
+        })
+    }
+}
+
+/// Fills part of an escrow: the taker pays `fill` of mint_b and receives mint_a
+/// pro rata. Accounts are closed only once the order is fully filled.
+pub fn process_take_partial_instruction(
+    accounts: &[AccountView],
+    data: &[u8],
+) -> ProgramResult {
+    let [
+        taker,
+        maker,
+        mint_a,
+        mint_b,
+        escrow_account,
+        taker_ata_a,
+        taker_ata_b,
+        maker_ata_b,
+        maker_ata_a,
+        vault,
+        _system_program,
+        token_program_a,
+        token_program_b,
+        hook_accounts @ ..
+    ] = accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    validation::check_signer(taker)?;
+
+    let TakePartialData { fill, min_payout } = TakePartialData::from_bytes(data)?;
+
+    // Each mint may live under SPL Token or Token-2022
+    token_interface::check_mint_program(mint_a, token_program_a)?;
+    token_interface::check_mint_program(mint_b, token_program_b)?;
+    let decimals_a = token_interface::mint_decimals(mint_a)?;
+    let decimals_b = token_interface::mint_decimals(mint_b)?;
+
+    // Load escrow state; checks owner, PDA, maker and mint_a
+    let escrow_state = validation::load_escrow(escrow_account, maker, mint_a)?;
+    if escrow_state.mint_b() != *mint_b.address() {
+        return Err(EscrowError::MintBMismatch.into());
+    }
+
+    // Reject offers whose deadline has passed
+    if escrow_state.is_expired(Clock::get()?.unix_timestamp) {
+        return Err(EscrowError::EscrowExpired.into());
+    }
+
+    // Private offers can only be filled by the taker the maker named
+    if !escrow_state.can_be_taken_by(taker.address()) {
+        return Err(EscrowError::TakerNotAllowed.into());
+    }
+
+    // Token accounts must be the escrow's vault and the parties' own accounts
+    // for the right mints
+    validation::check_vault(vault, escrow_account, mint_a, token_program_a)?;
+    validation::check_token_account(taker_ata_a, taker.address(), mint_a.address())?;
+    validation::check_token_account(taker_ata_b, taker.address(), mint_b.address())?;
+    validation::check_token_account(maker_ata_b, maker.address(), mint_b.address())?;
+    validation::check_token_account(maker_ata_a, maker.address(), mint_a.address())?;
+
+    // Update remaining amounts and compute the mint_a payout
+    let payout = escrow_state.apply_fill(fill)?;
+    if payout < min_payout {
+        return Err(EscrowError::SlippageExceeded.into());
+    }
+    let remaining = escrow_state.amount_to_receive();
+    let filled = escrow_state.is_filled();
+
+    let seed_bytes = escrow_state.seed().to_le_bytes();
+    let bump = escrow_state.bump;
+
+    // Build escrow PDA signer seeds
+    let bump_bytes = [bump];
+    let seed = [
+        Seed::from(b"escrow"),
+        Seed::from(maker.address().as_array()),
+        Seed::from(&seed_bytes),
+        Seed::from(&bump_bytes),
+    ];
+    let signer = Signer::from(&seed);
+
+    // 1) Transfer mint_b: taker_ata_b -> maker_ata_b (taker signs)
+    TransferChecked {
+        from: taker_ata_b,
+        mint: mint_b,
+        to: maker_ata_b,
+        authority: taker,
+        amount: fill,
+        decimals: decimals_b,
+        token_program: token_program_b,
+        extra_accounts: hook_accounts,
+    }
+    .invoke()?;
+
+    // 2) Transfer mint_a: vault -> taker_ata_a (escrow PDA signs)
+    TransferChecked {
+        from: vault,
+        mint: mint_a,
+        to: taker_ata_a,
+        authority: escrow_account,
+        amount: payout,
+        decimals: decimals_a,
+        token_program: token_program_a,
+        extra_accounts: hook_accounts,
+    }
+    .invoke_signed(&[signer.clone()])?;
+
+    EscrowTaken {
+        escrow: escrow_account.address(),
+        maker: maker.address(),
+        taker: taker.address(),
+        fill,
+        payout,
+        remaining,
+    }
+    .emit();
+
+    if !filled {
+        return Ok(());
+    }
+
+    // 3) Fully filled: return tokens donated to the vault to the maker, or the
+    // close fails
+    let surplus = token_interface::load_token_account(vault)?.amount;
+    if surplus > 0 {
+        TransferChecked {
+            from: vault,
+            mint: mint_a,
+            to: maker_ata_a,
+            authority: escrow_account,
+            amount: surplus,
+            decimals: decimals_a,
+            token_program: token_program_a,
+            extra_accounts: hook_accounts,
+        }
+        .invoke_signed(&[signer.clone()])?;
+    }
+
+    // 4) Close vault ATA -> maker (escrow PDA signs); Token-2022 first needs
+    // any withheld transfer fees harvested to the mint
+    token_interface::harvest_withheld_fees(vault, mint_a, token_program_a)?;
+    CloseAccount {
+        account: vault,
+        destination: maker,
+        authority: escrow_account,
+        token_program: token_program_a,
+    }
+    .invoke_signed(&[signer])?;
+
+    // 5) Close escrow account -> return lamports to maker
+    {
+        let escrow_lamports = escrow_account.lamports();
+        maker.set_lamports(maker.lamports() + escrow_lamports);
+        escrow_account.set_lamports(0);
+
+        // Zero out escrow data
+        unsafe {
+            let mut data = escrow_account.try_borrow_mut()?;
+            core::ptr::write_bytes(data.as_mut_ptr(), 0, data.len());
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use pinocchio::{
+    AccountView, ProgramResult,
+    cpi::{Seed, Signer},
+    error::ProgramError,
+    sysvars::{Sysvar, clock::Clock},
+};
+use pinocchio_codec::{FromBytes, Reader};
+
+use crate::errors::EscrowError;
+use crate::events::EscrowUpdated;
+use crate::state::Escrow;
+use crate::token_interface::{self, TransferChecked};
+use crate::validation;
+
+/// Update payload: the new terms, [amount_to_receive: u64, amount_to_give: u64]
+pub struct UpdateData {
+    pub amount_to_receive: u64,
+    pub amount_to_give: u64,
+}
+
+impl FromBytes for UpdateData {
+    const LEN: usize = 16;
+
+    fn read(reader: &mut Reader) -> Result<Self, ProgramError> {
+        Ok(Self {
+            amount_to_receive: reader.read()?,
+            amount_to_give: reader.read()?,
+        })
+    }
+}
+
+/// Lets the maker reprice an open escrow and top up or withdraw from the vault
+/// without closing it. `amount_to_give` is the new vault size: the difference
+/// moves between maker_ata and the vault.
+pub fn process_update_instruction(
+    accounts: &[AccountView],
+    data: &[u8],
+) -> ProgramResult {
+    let [
+        maker,
+        mint_a,
+        escrow_account,
+        maker_ata,
+        vault,
+        token_program,
+        hook_accounts @ ..
+    ] = accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    validation::check_signer(maker)?;
+
+    let UpdateData { amount_to_receive, amount_to_give } = UpdateData::from_bytes(data)?;
+    if amount_to_receive == 0 || amount_to_give == 0 {
+        return Err(EscrowError::InvalidAmount.into());
+    }
+
+    token_interface::check_mint_program(mint_a, token_program)?;
+    let decimals = token_interface::mint_decimals(mint_a)?;
+
+    // Load escrow state; checks owner, PDA, maker and mint_a
+    let escrow_state = validation::load_escrow(escrow_account, maker, mint_a)?;
+
+    // Only open offers can be changed
+    if escrow_state.is_expired(Clock::get()?.unix_timestamp) {
+        return Err(EscrowError::EscrowExpired.into());
+    }
+
+    let held = validation::check_vault(vault, escrow_account, mint_a, token_program)?.amount;
+    validation::check_token_account(maker_ata, maker.address(), mint_a.address())?;
+
+    escrow_state.set_amount_to_receive(amount_to_receive);
+    let seed_bytes = escrow_state.seed().to_le_bytes();
+    let bump = escrow_state.bump;
+
+    if amount_to_give > held {
+        // Top up: maker_ata -> vault (maker signs)
+        TransferChecked {
+            from: maker_ata,
+            mint: mint_a,
+            to: vault,
+            authority: maker,
+            amount: amount_to_give - held,
+            decimals,
+            token_program,
+            extra_accounts: hook_accounts,
+        }
+        .invoke()?;
+    } else if amount_to_give < held {
+        // Withdraw: vault -> maker_ata (escrow PDA signs)
+        let bump_bytes = [bump];
+        let seed = [
+            Seed::from(b"escrow"),
+            Seed::from(maker.address().as_array()),
+            Seed::from(&seed_bytes),
+            Seed::from(&bump_bytes),
+        ];
+        let signer = Signer::from(&seed);
+
+        TransferChecked {
+            from: vault,
+            mint: mint_a,
+            to: maker_ata,
+            authority: escrow_account,
+            amount: held - amount_to_give,
+            decimals,
+            token_program,
+            extra_accounts: hook_accounts,
+        }
+        .invoke_signed(&[signer])?;
+    }
+
+    // Record what the vault actually holds: transfer-fee mints withhold part of a top-up
+    let held = token_interface::load_token_account(vault)?.amount;
+    Escrow::from_account_info(escrow_account)?.set_amount_to_give(held);
+
+    EscrowUpdated {
+        escrow: escrow_account.address(),
+        amount_to_receive,
+        amount_to_give: held,
+    }
+    .emit();
+
+    Ok(())
+}
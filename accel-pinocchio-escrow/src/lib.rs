@@ -4,8 +4,15 @@ use pinocchio::{AccountView, entrypoint, Address, ProgramResult, address::declar
 use crate::instructions::EscrowInstrctions;
 
 mod tests;
+mod errors;
+mod events;
 mod state;
 mod instructions;
+mod token_interface;
+mod validation;
+
+#[cfg(any(test, feature = "client"))]
+pub mod client;
 
 entrypoint!(process_instruction);
 
@@ -16,8 +23,9 @@ pub fn process_instruction(
     accounts: &[AccountView],
     instruction_data: &[u8],
 ) -> ProgramResult {
-
-    assert_eq!(program_id, &ID);
+    if program_id != &ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
 
     let (discriminator, data) = instruction_data.split_first()
         .ok_or(ProgramError::InvalidInstructionData)?;
@@ -27,6 +35,9 @@ pub fn process_instruction(
         EscrowInstrctions::Take => instructions::process_take_instruction(accounts, data)?,
         EscrowInstrctions::Cancel => instructions::process_cancel_instruction(accounts, data)?,
         EscrowInstrctions::MakeV2 => instructions::process_make_instruction_v2(accounts, data)?,
+        EscrowInstrctions::Expire => instructions::process_expire_instruction(accounts, data)?,
+        EscrowInstrctions::TakePartial => instructions::process_take_partial_instruction(accounts, data)?,
+        EscrowInstrctions::Update => instructions::process_update_instruction(accounts, data)?,
     }
     Ok(())
 }
\ No newline at end of file
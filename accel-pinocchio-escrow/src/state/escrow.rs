@@ -1,5 +1,7 @@
 use pinocchio::{AccountView, error::ProgramError};
 
+use crate::errors::EscrowError;
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Escrow {
@@ -8,20 +10,23 @@ pub struct Escrow {
     mint_b: [u8; 32],
     amount_to_receive: [u8; 8],
     amount_to_give: [u8; 8],
+    expires_at: [u8; 8],
+    seed: [u8; 8],
     pub bump: u8,
+    allowed_taker: [u8; 32],
 }
 
 impl Escrow {
-    pub const LEN: usize = 32 + 32 + 32 + 8 + 8 + 1;
+    pub const LEN: usize = 32 + 32 + 32 + 8 + 8 + 8 + 8 + 1 + 32;
 
     pub fn from_account_info(account_info: &AccountView) -> Result<&mut Self, ProgramError> {
         let mut data = account_info.try_borrow_mut()?;
         if data.len() != Escrow::LEN {
-            return Err(ProgramError::InvalidAccountData);
+            return Err(EscrowError::InvalidEscrowData.into());
         }
 
         if (data.as_ptr() as usize) % core::mem::align_of::<Self>() != 0 {
-            return Err(ProgramError::InvalidAccountData);
+            return Err(EscrowError::InvalidEscrowData.into());
         }
 
         Ok(unsafe { &mut *(data.as_mut_ptr() as *mut Self) })
@@ -66,4 +71,71 @@ impl Escrow {
     pub fn set_amount_to_give(&mut self, amount: u64) {
         self.amount_to_give = amount.to_le_bytes();
     }
+
+    /// Unix timestamp after which the offer can no longer be taken; 0 means it never expires.
+    pub fn expires_at(&self) -> i64 {
+        i64::from_le_bytes(self.expires_at)
+    }
+
+    pub fn set_expires_at(&mut self, expires_at: i64) {
+        self.expires_at = expires_at.to_le_bytes();
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at() != 0 && now >= self.expires_at()
+    }
+
+    /// Maker-chosen nonce in the PDA seeds, so one maker can hold many escrows.
+    pub fn seed(&self) -> u64 {
+        u64::from_le_bytes(self.seed)
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed.to_le_bytes();
+    }
+
+    /// The only wallet allowed to fill the offer; `None` (all zeroes) means anyone.
+    pub fn allowed_taker(&self) -> Option<pinocchio::Address> {
+        (self.allowed_taker != [0u8; 32]).then(|| pinocchio::Address::from(self.allowed_taker))
+    }
+
+    pub fn set_allowed_taker(&mut self, allowed_taker: &[u8; 32]) {
+        self.allowed_taker = *allowed_taker;
+    }
+
+    pub fn can_be_taken_by(&self, taker: &pinocchio::Address) -> bool {
+        self.allowed_taker().is_none_or(|allowed| &allowed == taker)
+    }
+
+    /// Records a fill of `fill` mint_b and returns the mint_a owed to the taker.
+    ///
+    /// The payout is `fill * amount_to_give / amount_to_receive` rounded down, so
+    /// rounding dust stays in the vault; the fill that completes the order takes
+    /// whatever remains. Fills that are zero, exceed the remaining amount, or
+    /// would pay out nothing are rejected.
+    pub fn apply_fill(&mut self, fill: u64) -> Result<u64, ProgramError> {
+        let remaining_receive = self.amount_to_receive();
+        let remaining_give = self.amount_to_give();
+
+        if fill == 0 || fill > remaining_receive {
+            return Err(EscrowError::InvalidFillAmount.into());
+        }
+
+        let payout = if fill == remaining_receive {
+            remaining_give
+        } else {
+            (fill as u128 * remaining_give as u128 / remaining_receive as u128) as u64
+        };
+        if payout == 0 {
+            return Err(EscrowError::FillTooSmall.into());
+        }
+
+        self.set_amount_to_receive(remaining_receive - fill);
+        self.set_amount_to_give(remaining_give - payout);
+        Ok(payout)
+    }
+
+    pub fn is_filled(&self) -> bool {
+        self.amount_to_receive() == 0
+    }
 }
\ No newline at end of file
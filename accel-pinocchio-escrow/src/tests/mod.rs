@@ -3,19 +3,27 @@ mod tests {
 
     use std::path::PathBuf;
 
-    use litesvm::LiteSVM;
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    use litesvm::{types::TransactionResult, LiteSVM};
     use litesvm_token::{spl_token::{self}, CreateAssociatedTokenAccount, CreateMint, MintTo};
 
-    use solana_instruction::{AccountMeta, Instruction};
+    use solana_clock::Clock;
+    use solana_instruction::{error::InstructionError, AccountMeta, Instruction};
     use solana_keypair::Keypair;
     use solana_message::Message;
     use solana_native_token::LAMPORTS_PER_SOL;
     use solana_pubkey::Pubkey;
     use solana_signer::Signer;
     use solana_transaction::Transaction;
+    use solana_transaction_error::TransactionError;
+
+    use crate::client;
+    use crate::errors::EscrowError;
 
     const PROGRAM_ID: &str = "4ibrEMW5F6hKnkW4jVedswYv6H6VtwPN6ar6dvXDN1nT";
     const TOKEN_PROGRAM_ID: Pubkey = spl_token::ID;
+    const TOKEN_2022_PROGRAM_ID: Pubkey = spl_token_2022::ID;
     const ASSOCIATED_TOKEN_PROGRAM_ID: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
 
     fn program_id() -> Pubkey {
@@ -51,8 +59,16 @@ mod tests {
         svm: &mut LiteSVM,
         payer: &Keypair,
     ) -> (Pubkey, Pubkey, Pubkey, Pubkey, u8, Pubkey) {
-        let program_id = program_id();
+        do_make_with(svm, payer, 0, 0)
+    }
 
+    /// Helper: runs Make with an escrow seed and a deadline (`0` = never expires).
+    fn do_make_with(
+        svm: &mut LiteSVM,
+        payer: &Keypair,
+        seed: u64,
+        expires_at: i64,
+    ) -> (Pubkey, Pubkey, Pubkey, Pubkey, u8, Pubkey) {
         let mint_a = CreateMint::new(svm, payer)
             .decimals(6)
             .authority(&payer.pubkey())
@@ -65,55 +81,59 @@ mod tests {
             .send()
             .unwrap();
 
-        let maker_ata_a = CreateAssociatedTokenAccount::new(svm, payer, &mint_a)
+        let (escrow, maker_ata_a, bump, vault) =
+            make_escrow(svm, payer, &mint_a, &mint_b, seed, expires_at, &TOKEN_PROGRAM_ID);
+
+        // Return: (mint_a, mint_b, escrow_pda, maker_ata_a, bump, vault)
+        (mint_a, mint_b, escrow, maker_ata_a, bump, vault)
+    }
+
+    /// Helper: funds the maker with mint_a under `token_program` and runs Make
+    /// for existing mints. Returns (escrow_pda, maker_ata_a, bump, vault).
+    fn make_escrow(
+        svm: &mut LiteSVM,
+        payer: &Keypair,
+        mint_a: &Pubkey,
+        mint_b: &Pubkey,
+        seed: u64,
+        expires_at: i64,
+        token_program: &Pubkey,
+    ) -> (Pubkey, Pubkey, u8, Pubkey) {
+        let maker_ata_a = CreateAssociatedTokenAccount::new(svm, payer, mint_a)
             .owner(&payer.pubkey())
+            .token_program_id(token_program)
             .send()
             .unwrap();
 
-        let escrow = Pubkey::find_program_address(
-            &[b"escrow".as_ref(), payer.pubkey().as_ref()],
-            &program_id,
-        );
-
-        let vault = spl_associated_token_account::get_associated_token_address(
-            &escrow.0,
-            &mint_a,
-        );
-
-        let associated_token_program = ASSOCIATED_TOKEN_PROGRAM_ID.parse::<Pubkey>().unwrap();
-        let token_program = TOKEN_PROGRAM_ID;
-        let system_program = solana_sdk_ids::system_program::ID;
+        let escrow = client::find_escrow_address(&payer.pubkey(), seed);
+        let vault = client::vault_address(&escrow.0, mint_a, token_program);
 
         // Mint 1000 tokens of mint_a to maker
-        MintTo::new(svm, payer, &mint_a, &maker_ata_a, 1_000_000_000)
+        MintTo::new(svm, payer, mint_a, &maker_ata_a, 1_000_000_000)
+            .token_program_id(token_program)
             .send()
             .unwrap();
 
         let bump: u8 = escrow.1;
 
-        let make_data = [
-            vec![0u8], // Discriminator: Make
-            bump.to_le_bytes().to_vec(),
-            AMOUNT_TO_RECEIVE.to_le_bytes().to_vec(),
-            AMOUNT_TO_GIVE.to_le_bytes().to_vec(),
-        ]
-        .concat();
-
-        let make_ix = Instruction {
-            program_id,
-            accounts: vec![
-                AccountMeta::new(payer.pubkey(), true),
-                AccountMeta::new(mint_a, false),
-                AccountMeta::new(mint_b, false),
-                AccountMeta::new(escrow.0, false),
-                AccountMeta::new(maker_ata_a, false),
-                AccountMeta::new(vault, false),
-                AccountMeta::new(system_program, false),
-                AccountMeta::new(token_program, false),
-                AccountMeta::new(associated_token_program, false),
-            ],
-            data: make_data,
+        let accounts = client::MakeAccounts {
+            maker: payer.pubkey(),
+            mint_a: *mint_a,
+            mint_b: *mint_b,
+            escrow: escrow.0,
+            maker_ata: maker_ata_a,
+            vault,
+            token_program: *token_program,
+        };
+        let args = client::MakeArgs {
+            bump,
+            seed,
+            amount_to_receive: AMOUNT_TO_RECEIVE,
+            amount_to_give: AMOUNT_TO_GIVE,
+            expires_at,
+            allowed_taker: None,
         };
+        let make_ix = client::make(&accounts, &args);
 
         let message = Message::new(&[make_ix], Some(&payer.pubkey()));
         let recent_blockhash = svm.latest_blockhash();
@@ -121,8 +141,33 @@ mod tests {
         let tx = svm.send_transaction(transaction).unwrap();
         println!("Make tx CUs: {}", tx.compute_units_consumed);
 
-        // Return: (mint_a, mint_b, escrow_pda, maker_ata_a, bump, vault)
-        (mint_a, mint_b, escrow.0, maker_ata_a, bump, vault)
+        (escrow.0, maker_ata_a, bump, vault)
+    }
+
+    /// Helper: builds a Make instruction (discriminator = 0) with raw `data`.
+    #[allow(clippy::too_many_arguments)]
+    fn make_ix(
+        maker: &Pubkey,
+        mint_a: &Pubkey,
+        mint_b: &Pubkey,
+        escrow: &Pubkey,
+        maker_ata_a: &Pubkey,
+        vault: &Pubkey,
+        token_program: &Pubkey,
+        data: Vec<u8>,
+    ) -> Instruction {
+        let accounts = client::MakeAccounts {
+            maker: *maker,
+            mint_a: *mint_a,
+            mint_b: *mint_b,
+            escrow: *escrow,
+            maker_ata: *maker_ata_a,
+            vault: *vault,
+            token_program: *token_program,
+        };
+        let mut ix = client::make(&accounts, &client::MakeArgs::default());
+        ix.data = data;
+        ix
     }
 
     #[test]
@@ -153,8 +198,9 @@ mod tests {
         println!("Maker ATA A: {}\n", maker_ata_a);
 
         // Derive the PDA for the escrow account using the maker's public key and a seed value
+        let seed: u64 = 42;
         let escrow = Pubkey::find_program_address(
-            &[b"escrow".as_ref(), payer.pubkey().as_ref()],
+            &[b"escrow".as_ref(), payer.pubkey().as_ref(), &seed.to_le_bytes()],
             &PROGRAM_ID.parse().unwrap(),
         );
         println!("Escrow PDA: {}\n", escrow.0);
@@ -186,8 +232,11 @@ mod tests {
         let make_data = [
             vec![0u8],              // Discriminator for "Make" instruction
             bump.to_le_bytes().to_vec(),
+            seed.to_le_bytes().to_vec(),
             amount_to_receive.to_le_bytes().to_vec(),
             amount_to_give.to_le_bytes().to_vec(),
+            0i64.to_le_bytes().to_vec(), // expires_at: never
+            [0u8; 32].to_vec(),      // allowed_taker: anyone
         ].concat();
         let make_ix = Instruction {
             program_id: program_id,
@@ -222,13 +271,12 @@ mod tests {
     #[test]
     pub fn test_take_instruction() {
         let (mut svm, maker) = setup();
-        let (mint_a, mint_b, escrow_pda, _maker_ata_a, _bump, vault) =
+        let (mint_a, mint_b, escrow_pda, maker_ata_a, _bump, vault) =
             do_make(&mut svm, &maker);
 
         let program_id = program_id();
         let token_program = TOKEN_PROGRAM_ID;
         let system_program = solana_sdk_ids::system_program::ID;
-        let associated_token_program = ASSOCIATED_TOKEN_PROGRAM_ID.parse::<Pubkey>().unwrap();
 
         // Create taker
         let taker = Keypair::new();
@@ -257,8 +305,10 @@ mod tests {
             .send()
             .unwrap();
 
-        // Build Take instruction (discriminator = 1)
-        let take_data = vec![1u8]; // Discriminator: Take
+        // Build Take instruction (discriminator = 1), quoting the escrow's terms
+        let mut take_data = vec![1u8]; // Discriminator: Take
+        take_data.extend_from_slice(&AMOUNT_TO_RECEIVE.to_le_bytes());
+        take_data.extend_from_slice(&AMOUNT_TO_GIVE.to_le_bytes());
         let take_ix = Instruction {
             program_id,
             accounts: vec![
@@ -270,10 +320,11 @@ mod tests {
                 AccountMeta::new(taker_ata_a, false),      // taker_ata_a
                 AccountMeta::new(taker_ata_b, false),      // taker_ata_b
                 AccountMeta::new(maker_ata_b, false),      // maker_ata_b
+                AccountMeta::new(maker_ata_a, false),      // maker_ata_a
                 AccountMeta::new(vault, false),            // vault
                 AccountMeta::new(system_program, false),   // system_program
-                AccountMeta::new(token_program, false),    // token_program
-                AccountMeta::new(associated_token_program, false), // associated_token_program
+                AccountMeta::new(token_program, false),    // token_program_a
+                AccountMeta::new(token_program, false),    // token_program_b
             ],
             data: take_data,
         };
@@ -344,8 +395,9 @@ mod tests {
             .send()
             .unwrap();
 
+        let seed: u64 = 7;
         let escrow = Pubkey::find_program_address(
-            &[b"escrow".as_ref(), payer.pubkey().as_ref()],
+            &[b"escrow".as_ref(), payer.pubkey().as_ref(), &seed.to_le_bytes()],
             &program_id,
         );
 
@@ -368,8 +420,11 @@ mod tests {
         let make_v2_data = [
             vec![3u8], // Discriminator: MakeV2
             bump.to_le_bytes().to_vec(),
+            seed.to_le_bytes().to_vec(),
             AMOUNT_TO_RECEIVE.to_le_bytes().to_vec(),
             AMOUNT_TO_GIVE.to_le_bytes().to_vec(),
+            0i64.to_le_bytes().to_vec(), // expires_at: never
+            [0u8; 32].to_vec(),      // allowed_taker: anyone
         ]
         .concat();
 
@@ -397,4 +452,1415 @@ mod tests {
         println!("\n\nMakeV2 transaction successful");
         println!("CUs Consumed: {}", tx.compute_units_consumed);
     }
+
+    /// Helper: funds a taker with mint_b and creates the ATAs Take needs.
+    /// Returns (taker, taker_ata_a, taker_ata_b, maker_ata_b).
+    fn setup_taker(
+        svm: &mut LiteSVM,
+        maker: &Keypair,
+        mint_a: &Pubkey,
+        mint_b: &Pubkey,
+    ) -> (Keypair, Pubkey, Pubkey, Pubkey) {
+        setup_taker_with(svm, maker, mint_a, mint_b, &TOKEN_PROGRAM_ID, &TOKEN_PROGRAM_ID)
+    }
+
+    /// Helper: `setup_taker` for mints owned by the given token programs.
+    fn setup_taker_with(
+        svm: &mut LiteSVM,
+        maker: &Keypair,
+        mint_a: &Pubkey,
+        mint_b: &Pubkey,
+        token_program_a: &Pubkey,
+        token_program_b: &Pubkey,
+    ) -> (Keypair, Pubkey, Pubkey, Pubkey) {
+        let taker = Keypair::new();
+        svm.airdrop(&taker.pubkey(), 5 * LAMPORTS_PER_SOL).unwrap();
+
+        let taker_ata_a = CreateAssociatedTokenAccount::new(svm, &taker, mint_a)
+            .owner(&taker.pubkey())
+            .token_program_id(token_program_a)
+            .send()
+            .unwrap();
+        let taker_ata_b = CreateAssociatedTokenAccount::new(svm, &taker, mint_b)
+            .owner(&taker.pubkey())
+            .token_program_id(token_program_b)
+            .send()
+            .unwrap();
+        MintTo::new(svm, maker, mint_b, &taker_ata_b, AMOUNT_TO_RECEIVE)
+            .token_program_id(token_program_b)
+            .send()
+            .unwrap();
+        let maker_ata_b = CreateAssociatedTokenAccount::new(svm, maker, mint_b)
+            .owner(&maker.pubkey())
+            .token_program_id(token_program_b)
+            .send()
+            .unwrap();
+
+        (taker, taker_ata_a, taker_ata_b, maker_ata_b)
+    }
+
+    /// Helper: builds the Take instruction (discriminator = 1).
+    #[allow(clippy::too_many_arguments)]
+    fn take_ix(
+        taker: &Pubkey,
+        maker: &Pubkey,
+        mint_a: &Pubkey,
+        mint_b: &Pubkey,
+        escrow: &Pubkey,
+        taker_ata_a: &Pubkey,
+        taker_ata_b: &Pubkey,
+        maker_ata_b: &Pubkey,
+        vault: &Pubkey,
+    ) -> Instruction {
+        take_ix_with(
+            taker, maker, mint_a, mint_b, escrow, taker_ata_a, taker_ata_b, maker_ata_b, vault,
+            &TOKEN_PROGRAM_ID, &TOKEN_PROGRAM_ID,
+        )
+    }
+
+    /// Helper: builds the Expire instruction (discriminator = 4).
+    fn expire_ix(
+        maker: &Pubkey,
+        mint_a: &Pubkey,
+        escrow: &Pubkey,
+        maker_ata: &Pubkey,
+        vault: &Pubkey,
+    ) -> Instruction {
+        client::expire(&client::RefundAccounts {
+            maker: *maker,
+            mint_a: *mint_a,
+            escrow: *escrow,
+            maker_ata: *maker_ata,
+            vault: *vault,
+            token_program: TOKEN_PROGRAM_ID,
+        })
+    }
+
+    /// Helper: asserts the transaction failed with the given escrow error.
+    fn assert_escrow_error(result: TransactionResult, expected: EscrowError) {
+        assert_instruction_error(result, InstructionError::Custom(expected as u32));
+    }
+
+    fn assert_instruction_error(result: TransactionResult, expected: InstructionError) {
+        let failed = result.expect_err("transaction should fail");
+        assert_eq!(failed.err, TransactionError::InstructionError(0, expected));
+    }
+
+    /// Helper: moves the clock forward by `seconds`.
+    fn warp_clock(svm: &mut LiteSVM, seconds: i64) {
+        let mut clock: Clock = svm.get_sysvar();
+        clock.unix_timestamp += seconds;
+        svm.set_sysvar(&clock);
+    }
+
+    fn token_balance(svm: &LiteSVM, ata: &Pubkey) -> u64 {
+        let account = svm.get_account(ata).unwrap();
+        u64::from_le_bytes(account.data[64..72].try_into().unwrap())
+    }
+
+    #[test]
+    pub fn test_take_after_expiry_fails() {
+        let (mut svm, maker) = setup();
+        let now = svm.get_sysvar::<Clock>().unix_timestamp;
+        let (mint_a, mint_b, escrow_pda, _maker_ata_a, _bump, vault) =
+            do_make_with(&mut svm, &maker, 0, now + 3600);
+        let (taker, taker_ata_a, taker_ata_b, maker_ata_b) =
+            setup_taker(&mut svm, &maker, &mint_a, &mint_b);
+
+        warp_clock(&mut svm, 3600);
+
+        let ix = take_ix(
+            &taker.pubkey(), &maker.pubkey(), &mint_a, &mint_b, &escrow_pda,
+            &taker_ata_a, &taker_ata_b, &maker_ata_b, &vault,
+        );
+        let message = Message::new(&[ix], Some(&taker.pubkey()));
+        let transaction = Transaction::new(&[&taker], message, svm.latest_blockhash());
+        assert_escrow_error(svm.send_transaction(transaction), EscrowError::EscrowExpired);
+    }
+
+    #[test]
+    pub fn test_take_before_expiry_succeeds() {
+        let (mut svm, maker) = setup();
+        let now = svm.get_sysvar::<Clock>().unix_timestamp;
+        let (mint_a, mint_b, escrow_pda, _maker_ata_a, _bump, vault) =
+            do_make_with(&mut svm, &maker, 0, now + 3600);
+        let (taker, taker_ata_a, taker_ata_b, maker_ata_b) =
+            setup_taker(&mut svm, &maker, &mint_a, &mint_b);
+
+        warp_clock(&mut svm, 3599);
+
+        let ix = take_ix(
+            &taker.pubkey(), &maker.pubkey(), &mint_a, &mint_b, &escrow_pda,
+            &taker_ata_a, &taker_ata_b, &maker_ata_b, &vault,
+        );
+        let message = Message::new(&[ix], Some(&taker.pubkey()));
+        let transaction = Transaction::new(&[&taker], message, svm.latest_blockhash());
+        svm.send_transaction(transaction).unwrap();
+
+        assert_eq!(token_balance(&svm, &taker_ata_a), AMOUNT_TO_GIVE);
+    }
+
+    #[test]
+    pub fn test_expire_instruction() {
+        let (mut svm, maker) = setup();
+        let now = svm.get_sysvar::<Clock>().unix_timestamp;
+        let (mint_a, _mint_b, escrow_pda, maker_ata_a, _bump, vault) =
+            do_make_with(&mut svm, &maker, 0, now + 3600);
+        assert_eq!(token_balance(&svm, &maker_ata_a), 1_000_000_000 - AMOUNT_TO_GIVE);
+
+        // Anyone may crank expiry once the deadline passes
+        let cranker = Keypair::new();
+        svm.airdrop(&cranker.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        warp_clock(&mut svm, 3600);
+
+        let ix = expire_ix(&maker.pubkey(), &mint_a, &escrow_pda, &maker_ata_a, &vault);
+        let message = Message::new(&[ix], Some(&cranker.pubkey()));
+        let transaction = Transaction::new(&[&cranker], message, svm.latest_blockhash());
+        let tx = svm.send_transaction(transaction).unwrap();
+
+        println!("\n\nExpire transaction successful");
+        println!("CUs Consumed: {}", tx.compute_units_consumed);
+
+        assert_eq!(token_balance(&svm, &maker_ata_a), 1_000_000_000);
+        assert!(svm.get_account(&vault).map_or(true, |a| a.lamports == 0));
+        assert!(svm.get_account(&escrow_pda).map_or(true, |a| a.lamports == 0));
+    }
+
+    #[test]
+    pub fn test_expire_before_deadline_fails() {
+        let (mut svm, maker) = setup();
+        let now = svm.get_sysvar::<Clock>().unix_timestamp;
+        let (mint_a, _mint_b, escrow_pda, maker_ata_a, _bump, vault) =
+            do_make_with(&mut svm, &maker, 0, now + 3600);
+
+        let ix = expire_ix(&maker.pubkey(), &mint_a, &escrow_pda, &maker_ata_a, &vault);
+        let message = Message::new(&[ix], Some(&maker.pubkey()));
+        let transaction = Transaction::new(&[&maker], message, svm.latest_blockhash());
+        assert_escrow_error(svm.send_transaction(transaction), EscrowError::EscrowNotExpired);
+    }
+
+    #[test]
+    pub fn test_expire_without_deadline_fails() {
+        let (mut svm, maker) = setup();
+        let (mint_a, _mint_b, escrow_pda, maker_ata_a, _bump, vault) = do_make(&mut svm, &maker);
+
+        warp_clock(&mut svm, 365 * 86400);
+
+        let ix = expire_ix(&maker.pubkey(), &mint_a, &escrow_pda, &maker_ata_a, &vault);
+        let message = Message::new(&[ix], Some(&maker.pubkey()));
+        let transaction = Transaction::new(&[&maker], message, svm.latest_blockhash());
+        assert_escrow_error(svm.send_transaction(transaction), EscrowError::EscrowNotExpired);
+    }
+
+    #[test]
+    pub fn test_multiple_escrows_per_maker() {
+        let (mut svm, maker) = setup();
+
+        // Two open offers from the same maker, distinguished by seed
+        let (mint_a1, mint_b1, escrow_1, _maker_ata_a1, _bump_1, vault_1) =
+            do_make_with(&mut svm, &maker, 1, 0);
+        let (mint_a2, _mint_b2, escrow_2, maker_ata_a2, _bump_2, vault_2) =
+            do_make_with(&mut svm, &maker, 2, 0);
+        assert_ne!(escrow_1, escrow_2);
+
+        let escrow_1_account = svm.get_account(&escrow_1).unwrap();
+        let stored_seed = u64::from_le_bytes(escrow_1_account.data[120..128].try_into().unwrap());
+        assert_eq!(stored_seed, 1);
+
+        // Take the first, leaving the second open
+        let (taker, taker_ata_a, taker_ata_b, maker_ata_b) =
+            setup_taker(&mut svm, &maker, &mint_a1, &mint_b1);
+        let ix = take_ix(
+            &taker.pubkey(), &maker.pubkey(), &mint_a1, &mint_b1, &escrow_1,
+            &taker_ata_a, &taker_ata_b, &maker_ata_b, &vault_1,
+        );
+        let message = Message::new(&[ix], Some(&taker.pubkey()));
+        let transaction = Transaction::new(&[&taker], message, svm.latest_blockhash());
+        svm.send_transaction(transaction).unwrap();
+
+        assert_eq!(token_balance(&svm, &taker_ata_a), AMOUNT_TO_GIVE);
+        assert_eq!(token_balance(&svm, &vault_2), AMOUNT_TO_GIVE);
+
+        // Cancel the second
+        let cancel_ix = Instruction {
+            program_id: program_id(),
+            accounts: vec![
+                AccountMeta::new(maker.pubkey(), true),
+                AccountMeta::new(mint_a2, false),
+                AccountMeta::new(escrow_2, false),
+                AccountMeta::new(maker_ata_a2, false),
+                AccountMeta::new(vault_2, false),
+                AccountMeta::new(TOKEN_PROGRAM_ID, false),
+                AccountMeta::new(solana_sdk_ids::system_program::ID, false),
+            ],
+            data: vec![2u8],
+        };
+        let message = Message::new(&[cancel_ix], Some(&maker.pubkey()));
+        let transaction = Transaction::new(&[&maker], message, svm.latest_blockhash());
+        svm.send_transaction(transaction).unwrap();
+
+        assert_eq!(token_balance(&svm, &maker_ata_a2), 1_000_000_000);
+    }
+
+    /// Helper: builds TakePartial (discriminator = 5); same accounts as Take.
+    #[allow(clippy::too_many_arguments)]
+    fn take_partial_ix(
+        taker: &Pubkey,
+        maker: &Pubkey,
+        mint_a: &Pubkey,
+        mint_b: &Pubkey,
+        escrow: &Pubkey,
+        taker_ata_a: &Pubkey,
+        taker_ata_b: &Pubkey,
+        maker_ata_b: &Pubkey,
+        vault: &Pubkey,
+        fill: u64,
+        min_payout: u64,
+    ) -> Instruction {
+        let accounts = take_accounts(
+            taker, maker, mint_a, mint_b, escrow, taker_ata_a, taker_ata_b, maker_ata_b, vault,
+            &TOKEN_PROGRAM_ID, &TOKEN_PROGRAM_ID,
+        );
+        client::take_partial(&accounts, fill, min_payout)
+    }
+
+    #[test]
+    pub fn test_take_partial_instruction() {
+        let (mut svm, maker) = setup();
+        let (mint_a, mint_b, escrow_pda, _maker_ata_a, _bump, vault) =
+            do_make(&mut svm, &maker);
+        let (taker, taker_ata_a, taker_ata_b, maker_ata_b) =
+            setup_taker(&mut svm, &maker, &mint_a, &mint_b);
+
+        // First fill: 1/3 of the order, payout rounds down (5 * 33_333_333)
+        let first_fill = 33_333_333;
+        let ix = take_partial_ix(
+            &taker.pubkey(), &maker.pubkey(), &mint_a, &mint_b, &escrow_pda,
+            &taker_ata_a, &taker_ata_b, &maker_ata_b, &vault, first_fill, 166_666_665,
+        );
+        let message = Message::new(&[ix], Some(&taker.pubkey()));
+        let transaction = Transaction::new(&[&taker], message, svm.latest_blockhash());
+        let tx = svm.send_transaction(transaction).unwrap();
+        println!("\n\nTakePartial transaction successful");
+        println!("CUs Consumed: {}", tx.compute_units_consumed);
+
+        assert_eq!(token_balance(&svm, &taker_ata_a), 166_666_665);
+        assert_eq!(token_balance(&svm, &maker_ata_b), first_fill);
+        assert_eq!(token_balance(&svm, &vault), AMOUNT_TO_GIVE - 166_666_665);
+
+        // Remaining amounts are tracked in escrow state
+        let escrow = svm.get_account(&escrow_pda).unwrap();
+        let remaining_receive = u64::from_le_bytes(escrow.data[96..104].try_into().unwrap());
+        let remaining_give = u64::from_le_bytes(escrow.data[104..112].try_into().unwrap());
+        assert_eq!(remaining_receive, AMOUNT_TO_RECEIVE - first_fill);
+        assert_eq!(remaining_give, AMOUNT_TO_GIVE - 166_666_665);
+
+        // Final fill takes the remainder, including rounding dust, and closes accounts
+        svm.expire_blockhash();
+        let ix = take_partial_ix(
+            &taker.pubkey(), &maker.pubkey(), &mint_a, &mint_b, &escrow_pda,
+            &taker_ata_a, &taker_ata_b, &maker_ata_b, &vault, remaining_receive, remaining_give,
+        );
+        let message = Message::new(&[ix], Some(&taker.pubkey()));
+        let transaction = Transaction::new(&[&taker], message, svm.latest_blockhash());
+        svm.send_transaction(transaction).unwrap();
+
+        assert_eq!(token_balance(&svm, &taker_ata_a), AMOUNT_TO_GIVE);
+        assert_eq!(token_balance(&svm, &maker_ata_b), AMOUNT_TO_RECEIVE);
+        assert!(svm.get_account(&vault).map_or(true, |a| a.lamports == 0));
+        assert!(svm.get_account(&escrow_pda).map_or(true, |a| a.lamports == 0));
+    }
+
+    #[test]
+    pub fn test_take_partial_rejects_invalid_fills() {
+        let (mut svm, maker) = setup();
+        let (mint_a, mint_b, escrow_pda, _maker_ata_a, _bump, vault) =
+            do_make(&mut svm, &maker);
+        let (taker, taker_ata_a, taker_ata_b, maker_ata_b) =
+            setup_taker(&mut svm, &maker, &mint_a, &mint_b);
+
+        for fill in [0, AMOUNT_TO_RECEIVE + 1] {
+            let ix = take_partial_ix(
+                &taker.pubkey(), &maker.pubkey(), &mint_a, &mint_b, &escrow_pda,
+                &taker_ata_a, &taker_ata_b, &maker_ata_b, &vault, fill, 0,
+            );
+            let message = Message::new(&[ix], Some(&taker.pubkey()));
+            let transaction = Transaction::new(&[&taker], message, svm.latest_blockhash());
+            assert_escrow_error(svm.send_transaction(transaction), EscrowError::InvalidFillAmount);
+        }
+    }
+
+    /// Helper: creates a plain Token-2022 mint with 6 decimals.
+    fn create_mint_2022(svm: &mut LiteSVM, payer: &Keypair) -> Pubkey {
+        CreateMint::new(svm, payer)
+            .decimals(6)
+            .authority(&payer.pubkey())
+            .token_program_id(&TOKEN_2022_PROGRAM_ID)
+            .send()
+            .unwrap()
+    }
+
+    /// Helper: creates a Token-2022 mint with the TransferFeeConfig extension.
+    fn create_transfer_fee_mint(
+        svm: &mut LiteSVM,
+        payer: &Keypair,
+        fee_basis_points: u16,
+        maximum_fee: u64,
+    ) -> Pubkey {
+        use spl_token_2022::extension::{transfer_fee, ExtensionType};
+
+        let mint = Keypair::new();
+        let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[
+            ExtensionType::TransferFeeConfig,
+        ])
+        .unwrap();
+        let lamports = svm.minimum_balance_for_rent_exemption(space);
+
+        // System program CreateAccount: [0u32, lamports, space, owner]
+        let create_account_ix = Instruction {
+            program_id: solana_sdk_ids::system_program::ID,
+            accounts: vec![
+                AccountMeta::new(payer.pubkey(), true),
+                AccountMeta::new(mint.pubkey(), true),
+            ],
+            data: [
+                0u32.to_le_bytes().to_vec(),
+                lamports.to_le_bytes().to_vec(),
+                (space as u64).to_le_bytes().to_vec(),
+                TOKEN_2022_PROGRAM_ID.to_bytes().to_vec(),
+            ]
+            .concat(),
+        };
+        let init_fee_ix = transfer_fee::instruction::initialize_transfer_fee_config(
+            &TOKEN_2022_PROGRAM_ID,
+            &mint.pubkey(),
+            Some(&payer.pubkey()),
+            Some(&payer.pubkey()),
+            fee_basis_points,
+            maximum_fee,
+        )
+        .unwrap();
+        let init_mint_ix = spl_token_2022::instruction::initialize_mint2(
+            &TOKEN_2022_PROGRAM_ID,
+            &mint.pubkey(),
+            &payer.pubkey(),
+            None,
+            6,
+        )
+        .unwrap();
+
+        let message = Message::new(
+            &[create_account_ix, init_fee_ix, init_mint_ix],
+            Some(&payer.pubkey()),
+        );
+        let transaction = Transaction::new(&[payer, &mint], message, svm.latest_blockhash());
+        svm.send_transaction(transaction).unwrap();
+
+        mint.pubkey()
+    }
+
+    /// Helper: Take for the given token programs (index 10 = mint_a's, 11 = mint_b's),
+    /// quoting the default terms.
+    #[allow(clippy::too_many_arguments)]
+    fn take_ix_with(
+        taker: &Pubkey,
+        maker: &Pubkey,
+        mint_a: &Pubkey,
+        mint_b: &Pubkey,
+        escrow: &Pubkey,
+        taker_ata_a: &Pubkey,
+        taker_ata_b: &Pubkey,
+        maker_ata_b: &Pubkey,
+        vault: &Pubkey,
+        token_program_a: &Pubkey,
+        token_program_b: &Pubkey,
+    ) -> Instruction {
+        let accounts = take_accounts(
+            taker, maker, mint_a, mint_b, escrow, taker_ata_a, taker_ata_b, maker_ata_b, vault,
+            token_program_a, token_program_b,
+        );
+        client::take(&accounts, AMOUNT_TO_RECEIVE, AMOUNT_TO_GIVE)
+    }
+
+    /// Helper: explicit Take/TakePartial accounts, so tests can pass wrong ones.
+    #[allow(clippy::too_many_arguments)]
+    fn take_accounts(
+        taker: &Pubkey,
+        maker: &Pubkey,
+        mint_a: &Pubkey,
+        mint_b: &Pubkey,
+        escrow: &Pubkey,
+        taker_ata_a: &Pubkey,
+        taker_ata_b: &Pubkey,
+        maker_ata_b: &Pubkey,
+        vault: &Pubkey,
+        token_program_a: &Pubkey,
+        token_program_b: &Pubkey,
+    ) -> client::TakeAccounts {
+        client::TakeAccounts {
+            taker: *taker,
+            maker: *maker,
+            mint_a: *mint_a,
+            mint_b: *mint_b,
+            escrow: *escrow,
+            taker_ata_a: *taker_ata_a,
+            taker_ata_b: *taker_ata_b,
+            maker_ata_b: *maker_ata_b,
+            maker_ata_a: client::associated_token_address(maker, mint_a, token_program_a),
+            vault: *vault,
+            token_program_a: *token_program_a,
+            token_program_b: *token_program_b,
+        }
+    }
+
+    #[test]
+    pub fn test_token_2022_make_and_take() {
+        let (mut svm, maker) = setup();
+        let mint_a = create_mint_2022(&mut svm, &maker);
+        let mint_b = create_mint_2022(&mut svm, &maker);
+
+        let (escrow_pda, _maker_ata_a, _bump, vault) =
+            make_escrow(&mut svm, &maker, &mint_a, &mint_b, 0, 0, &TOKEN_2022_PROGRAM_ID);
+        assert_eq!(svm.get_account(&vault).unwrap().owner, TOKEN_2022_PROGRAM_ID);
+        assert_eq!(token_balance(&svm, &vault), AMOUNT_TO_GIVE);
+
+        let (taker, taker_ata_a, taker_ata_b, maker_ata_b) = setup_taker_with(
+            &mut svm, &maker, &mint_a, &mint_b, &TOKEN_2022_PROGRAM_ID, &TOKEN_2022_PROGRAM_ID,
+        );
+        let ix = take_ix_with(
+            &taker.pubkey(), &maker.pubkey(), &mint_a, &mint_b, &escrow_pda,
+            &taker_ata_a, &taker_ata_b, &maker_ata_b, &vault,
+            &TOKEN_2022_PROGRAM_ID, &TOKEN_2022_PROGRAM_ID,
+        );
+        let message = Message::new(&[ix], Some(&taker.pubkey()));
+        let transaction = Transaction::new(&[&taker], message, svm.latest_blockhash());
+        let tx = svm.send_transaction(transaction).unwrap();
+        println!("\n\nToken-2022 Take transaction successful");
+        println!("CUs Consumed: {}", tx.compute_units_consumed);
+
+        assert_eq!(token_balance(&svm, &taker_ata_a), AMOUNT_TO_GIVE);
+        assert_eq!(token_balance(&svm, &maker_ata_b), AMOUNT_TO_RECEIVE);
+        assert!(svm.get_account(&escrow_pda).map_or(true, |a| a.lamports == 0));
+    }
+
+    #[test]
+    pub fn test_mixed_token_programs_take() {
+        let (mut svm, maker) = setup();
+        // mint_a under Token-2022, mint_b under legacy SPL Token
+        let mint_a = create_mint_2022(&mut svm, &maker);
+        let mint_b = CreateMint::new(&mut svm, &maker)
+            .decimals(6)
+            .authority(&maker.pubkey())
+            .send()
+            .unwrap();
+
+        let (escrow_pda, _maker_ata_a, _bump, vault) =
+            make_escrow(&mut svm, &maker, &mint_a, &mint_b, 0, 0, &TOKEN_2022_PROGRAM_ID);
+        let (taker, taker_ata_a, taker_ata_b, maker_ata_b) = setup_taker_with(
+            &mut svm, &maker, &mint_a, &mint_b, &TOKEN_2022_PROGRAM_ID, &TOKEN_PROGRAM_ID,
+        );
+
+        // Swapping the two token programs must fail the mint ownership check
+        let bad_ix = take_ix_with(
+            &taker.pubkey(), &maker.pubkey(), &mint_a, &mint_b, &escrow_pda,
+            &taker_ata_a, &taker_ata_b, &maker_ata_b, &vault,
+            &TOKEN_PROGRAM_ID, &TOKEN_2022_PROGRAM_ID,
+        );
+        let message = Message::new(&[bad_ix], Some(&taker.pubkey()));
+        let transaction = Transaction::new(&[&taker], message, svm.latest_blockhash());
+        assert_escrow_error(svm.send_transaction(transaction), EscrowError::TokenProgramMismatch);
+
+        let ix = take_ix_with(
+            &taker.pubkey(), &maker.pubkey(), &mint_a, &mint_b, &escrow_pda,
+            &taker_ata_a, &taker_ata_b, &maker_ata_b, &vault,
+            &TOKEN_2022_PROGRAM_ID, &TOKEN_PROGRAM_ID,
+        );
+        let message = Message::new(&[ix], Some(&taker.pubkey()));
+        let transaction = Transaction::new(&[&taker], message, svm.latest_blockhash());
+        svm.send_transaction(transaction).unwrap();
+
+        assert_eq!(token_balance(&svm, &taker_ata_a), AMOUNT_TO_GIVE);
+        assert_eq!(token_balance(&svm, &maker_ata_b), AMOUNT_TO_RECEIVE);
+    }
+
+    #[test]
+    pub fn test_token_2022_cancel() {
+        let (mut svm, maker) = setup();
+        let mint_a = create_mint_2022(&mut svm, &maker);
+        let mint_b = create_mint_2022(&mut svm, &maker);
+        let (escrow_pda, maker_ata_a, _bump, vault) =
+            make_escrow(&mut svm, &maker, &mint_a, &mint_b, 0, 0, &TOKEN_2022_PROGRAM_ID);
+
+        let cancel_ix = Instruction {
+            program_id: program_id(),
+            accounts: vec![
+                AccountMeta::new(maker.pubkey(), true),
+                AccountMeta::new(mint_a, false),
+                AccountMeta::new(escrow_pda, false),
+                AccountMeta::new(maker_ata_a, false),
+                AccountMeta::new(vault, false),
+                AccountMeta::new(TOKEN_2022_PROGRAM_ID, false),
+                AccountMeta::new(solana_sdk_ids::system_program::ID, false),
+            ],
+            data: vec![2u8],
+        };
+        let message = Message::new(&[cancel_ix], Some(&maker.pubkey()));
+        let transaction = Transaction::new(&[&maker], message, svm.latest_blockhash());
+        svm.send_transaction(transaction).unwrap();
+
+        assert_eq!(token_balance(&svm, &maker_ata_a), 1_000_000_000);
+        assert!(svm.get_account(&vault).map_or(true, |a| a.lamports == 0));
+    }
+
+    #[test]
+    pub fn test_transfer_fee_mint_records_net_deposit() {
+        let (mut svm, maker) = setup();
+        // 1% fee, effectively uncapped
+        let mint_a = create_transfer_fee_mint(&mut svm, &maker, 100, u64::MAX);
+        let mint_b = create_mint_2022(&mut svm, &maker);
+
+        let (escrow_pda, _maker_ata_a, _bump, vault) =
+            make_escrow(&mut svm, &maker, &mint_a, &mint_b, 0, 0, &TOKEN_2022_PROGRAM_ID);
+
+        // The vault holds the deposit net of the fee, and escrow state matches it
+        let net_deposit = AMOUNT_TO_GIVE - AMOUNT_TO_GIVE / 100;
+        assert_eq!(token_balance(&svm, &vault), net_deposit);
+        let escrow = svm.get_account(&escrow_pda).unwrap();
+        let amount_to_give = u64::from_le_bytes(escrow.data[104..112].try_into().unwrap());
+        assert_eq!(amount_to_give, net_deposit);
+
+        // Take empties the vault; the taker bears the fee on the way out
+        let (taker, taker_ata_a, taker_ata_b, maker_ata_b) = setup_taker_with(
+            &mut svm, &maker, &mint_a, &mint_b, &TOKEN_2022_PROGRAM_ID, &TOKEN_2022_PROGRAM_ID,
+        );
+        let accounts = take_accounts(
+            &taker.pubkey(), &maker.pubkey(), &mint_a, &mint_b, &escrow_pda,
+            &taker_ata_a, &taker_ata_b, &maker_ata_b, &vault,
+            &TOKEN_2022_PROGRAM_ID, &TOKEN_2022_PROGRAM_ID,
+        );
+        let ix = client::take(&accounts, AMOUNT_TO_RECEIVE, net_deposit);
+        let message = Message::new(&[ix], Some(&taker.pubkey()));
+        let transaction = Transaction::new(&[&taker], message, svm.latest_blockhash());
+        svm.send_transaction(transaction).unwrap();
+
+        assert_eq!(token_balance(&svm, &taker_ata_a), net_deposit - net_deposit / 100);
+        assert!(svm.get_account(&escrow_pda).map_or(true, |a| a.lamports == 0));
+        // The vault closes despite the fees withheld in it
+        assert!(svm.get_account(&vault).map_or(true, |a| a.lamports == 0));
+    }
+
+    #[test]
+    pub fn test_cancel_harvests_withheld_fees() {
+        let (mut svm, maker) = setup();
+        let mint_a = create_transfer_fee_mint(&mut svm, &maker, 100, u64::MAX);
+        let mint_b = create_mint_2022(&mut svm, &maker);
+
+        let (escrow_pda, maker_ata_a, _bump, vault) =
+            make_escrow(&mut svm, &maker, &mint_a, &mint_b, 0, 0, &TOKEN_2022_PROGRAM_ID);
+
+        // The deposit left its fee withheld in the vault, which Token-2022
+        // won't close over; Cancel harvests it to the mint first
+        let ix = client::cancel(&client::RefundAccounts {
+            maker: maker.pubkey(),
+            mint_a,
+            escrow: escrow_pda,
+            maker_ata: maker_ata_a,
+            vault,
+            token_program: TOKEN_2022_PROGRAM_ID,
+        });
+        send(&mut svm, ix, &maker).unwrap();
+
+        assert!(svm.get_account(&vault).map_or(true, |a| a.lamports == 0));
+        assert!(svm.get_account(&escrow_pda).map_or(true, |a| a.lamports == 0));
+    }
+
+    #[test]
+    pub fn test_cancel_returns_donated_tokens() {
+        let (mut svm, maker) = setup();
+        let (mint_a, _mint_b, escrow_pda, maker_ata_a, _bump, vault) =
+            do_make(&mut svm, &maker);
+
+        // Tokens sent straight to the vault aren't tracked by the escrow, but
+        // mustn't keep it from closing
+        MintTo::new(&mut svm, &maker, &mint_a, &vault, 7).send().unwrap();
+
+        let ix = cancel_ix(&maker.pubkey(), &mint_a, &escrow_pda, &maker_ata_a, &vault);
+        send(&mut svm, ix, &maker).unwrap();
+
+        assert_eq!(token_balance(&svm, &maker_ata_a), 1_000_000_007);
+        assert!(svm.get_account(&vault).map_or(true, |a| a.lamports == 0));
+        assert!(svm.get_account(&escrow_pda).map_or(true, |a| a.lamports == 0));
+    }
+
+    #[test]
+    pub fn test_take_returns_donated_tokens_to_maker() {
+        let (mut svm, maker) = setup();
+        let (mint_a, mint_b, escrow_pda, maker_ata_a, _bump, vault) =
+            do_make(&mut svm, &maker);
+        let (taker, taker_ata_a, taker_ata_b, maker_ata_b) =
+            setup_taker(&mut svm, &maker, &mint_a, &mint_b);
+        MintTo::new(&mut svm, &maker, &mint_a, &vault, 7).send().unwrap();
+
+        // The taker gets the agreed amount; the donation goes back to the maker
+        let ix = take_ix(
+            &taker.pubkey(), &maker.pubkey(), &mint_a, &mint_b, &escrow_pda,
+            &taker_ata_a, &taker_ata_b, &maker_ata_b, &vault,
+        );
+        send(&mut svm, ix, &taker).unwrap();
+
+        assert_eq!(token_balance(&svm, &taker_ata_a), AMOUNT_TO_GIVE);
+        assert_eq!(token_balance(&svm, &maker_ata_a), 1_000_000_000 - AMOUNT_TO_GIVE + 7);
+        assert!(svm.get_account(&vault).map_or(true, |a| a.lamports == 0));
+        assert!(svm.get_account(&escrow_pda).map_or(true, |a| a.lamports == 0));
+    }
+
+    /// Helper: builds the Cancel instruction (discriminator = 2).
+    fn cancel_ix(
+        maker: &Pubkey,
+        mint_a: &Pubkey,
+        escrow: &Pubkey,
+        maker_ata: &Pubkey,
+        vault: &Pubkey,
+    ) -> Instruction {
+        client::cancel(&client::RefundAccounts {
+            maker: *maker,
+            mint_a: *mint_a,
+            escrow: *escrow,
+            maker_ata: *maker_ata,
+            vault: *vault,
+            token_program: TOKEN_PROGRAM_ID,
+        })
+    }
+
+    /// Helper: signs `ix` with `signer` as fee payer and sends it.
+    fn send(svm: &mut LiteSVM, ix: Instruction, signer: &Keypair) -> TransactionResult {
+        let message = Message::new(&[ix], Some(&signer.pubkey()));
+        let transaction = Transaction::new(&[signer], message, svm.latest_blockhash());
+        svm.send_transaction(transaction)
+    }
+
+    /// Helper: creates mints and a funded maker ATA for Make tests that never
+    /// reach the deposit. Returns (mint_a, mint_b, maker_ata_a).
+    fn setup_make_accounts(svm: &mut LiteSVM, maker: &Keypair) -> (Pubkey, Pubkey, Pubkey) {
+        let mint_a = CreateMint::new(svm, maker).decimals(6).send().unwrap();
+        let mint_b = CreateMint::new(svm, maker).decimals(6).send().unwrap();
+        let maker_ata_a = CreateAssociatedTokenAccount::new(svm, maker, &mint_a)
+            .owner(&maker.pubkey())
+            .send()
+            .unwrap();
+        MintTo::new(svm, maker, &mint_a, &maker_ata_a, AMOUNT_TO_GIVE).send().unwrap();
+        (mint_a, mint_b, maker_ata_a)
+    }
+
+    fn make_data(bump: u8, seed: u64) -> Vec<u8> {
+        let args = client::MakeArgs {
+            bump,
+            seed,
+            amount_to_receive: AMOUNT_TO_RECEIVE,
+            amount_to_give: AMOUNT_TO_GIVE,
+            expires_at: 0,
+            allowed_taker: None,
+        };
+        [&[0u8][..], &args.to_bytes()].concat()
+    }
+
+    #[test]
+    pub fn test_make_rejects_short_data() {
+        let (mut svm, maker) = setup();
+        let (mint_a, mint_b, maker_ata_a) = setup_make_accounts(&mut svm, &maker);
+        let (escrow, bump) = Pubkey::find_program_address(
+            &[b"escrow".as_ref(), maker.pubkey().as_ref(), &0u64.to_le_bytes()],
+            &program_id(),
+        );
+        let vault = spl_associated_token_account::get_associated_token_address(&escrow, &mint_a);
+
+        // Discriminator and bump only: the rest of the payload is missing
+        let ix = make_ix(
+            &maker.pubkey(), &mint_a, &mint_b, &escrow, &maker_ata_a, &vault,
+            &TOKEN_PROGRAM_ID, vec![0u8, bump],
+        );
+        assert_instruction_error(
+            send(&mut svm, ix, &maker),
+            InstructionError::InvalidInstructionData,
+        );
+    }
+
+    #[test]
+    pub fn test_make_rejects_zero_amounts() {
+        let (mut svm, maker) = setup();
+        let (mint_a, mint_b, maker_ata_a) = setup_make_accounts(&mut svm, &maker);
+        let (escrow, bump) = Pubkey::find_program_address(
+            &[b"escrow".as_ref(), maker.pubkey().as_ref(), &0u64.to_le_bytes()],
+            &program_id(),
+        );
+        let vault = spl_associated_token_account::get_associated_token_address(&escrow, &mint_a);
+
+        let cases = [
+            (0, AMOUNT_TO_GIVE, EscrowError::InvalidAmount),
+            (AMOUNT_TO_RECEIVE, 0, EscrowError::EmptyDeposit),
+        ];
+        for (amount_to_receive, amount_to_give, expected) in cases {
+            let args = client::MakeArgs {
+                bump,
+                seed: 0,
+                amount_to_receive,
+                amount_to_give,
+                expires_at: 0,
+                allowed_taker: None,
+            };
+            for discriminator in [0u8, 3] {
+                let ix = make_ix(
+                    &maker.pubkey(), &mint_a, &mint_b, &escrow, &maker_ata_a, &vault,
+                    &TOKEN_PROGRAM_ID, [&[discriminator][..], &args.to_bytes()].concat(),
+                );
+                assert_escrow_error(send(&mut svm, ix, &maker), expected);
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_make_rejects_wrong_escrow_pda() {
+        let (mut svm, maker) = setup();
+        let (mint_a, mint_b, maker_ata_a) = setup_make_accounts(&mut svm, &maker);
+        let (escrow, bump) = Pubkey::find_program_address(
+            &[b"escrow".as_ref(), maker.pubkey().as_ref(), &0u64.to_le_bytes()],
+            &program_id(),
+        );
+        let vault = spl_associated_token_account::get_associated_token_address(&escrow, &mint_a);
+
+        // Escrow derived for seed 0, but the data claims seed 1
+        let ix = make_ix(
+            &maker.pubkey(), &mint_a, &mint_b, &escrow, &maker_ata_a, &vault,
+            &TOKEN_PROGRAM_ID, make_data(bump, 1),
+        );
+        assert_escrow_error(send(&mut svm, ix, &maker), EscrowError::InvalidEscrowPda);
+    }
+
+    #[test]
+    pub fn test_make_rejects_foreign_maker_ata() {
+        let (mut svm, maker) = setup();
+        let (mint_a, mint_b, _maker_ata_a) = setup_make_accounts(&mut svm, &maker);
+        let (escrow, bump) = Pubkey::find_program_address(
+            &[b"escrow".as_ref(), maker.pubkey().as_ref(), &0u64.to_le_bytes()],
+            &program_id(),
+        );
+        let vault = spl_associated_token_account::get_associated_token_address(&escrow, &mint_a);
+
+        // Someone else's mint_a account as the source
+        let victim = Keypair::new();
+        let victim_ata = CreateAssociatedTokenAccount::new(&mut svm, &maker, &mint_a)
+            .owner(&victim.pubkey())
+            .send()
+            .unwrap();
+
+        let ix = make_ix(
+            &maker.pubkey(), &mint_a, &mint_b, &escrow, &victim_ata, &vault,
+            &TOKEN_PROGRAM_ID, make_data(bump, 0),
+        );
+        assert_escrow_error(
+            send(&mut svm, ix, &maker),
+            EscrowError::TokenAccountOwnerMismatch,
+        );
+    }
+
+    #[test]
+    pub fn test_take_requires_taker_signature() {
+        let (mut svm, maker) = setup();
+        let (mint_a, mint_b, escrow_pda, _maker_ata_a, _bump, vault) =
+            do_make(&mut svm, &maker);
+        let (taker, taker_ata_a, taker_ata_b, maker_ata_b) =
+            setup_taker(&mut svm, &maker, &mint_a, &mint_b);
+
+        let mut ix = take_ix(
+            &taker.pubkey(), &maker.pubkey(), &mint_a, &mint_b, &escrow_pda,
+            &taker_ata_a, &taker_ata_b, &maker_ata_b, &vault,
+        );
+        ix.accounts[0].is_signer = false;
+        assert_instruction_error(
+            send(&mut svm, ix, &maker),
+            InstructionError::MissingRequiredSignature,
+        );
+    }
+
+    #[test]
+    pub fn test_take_rejects_fake_vault() {
+        let (mut svm, maker) = setup();
+        let (mint_a, mint_b, escrow_pda, _maker_ata_a, _bump, _vault) =
+            do_make(&mut svm, &maker);
+        let (taker, taker_ata_a, taker_ata_b, maker_ata_b) =
+            setup_taker(&mut svm, &maker, &mint_a, &mint_b);
+
+        // A mint_a account that isn't the escrow's ATA stands in for the vault
+        let fake_vault = CreateAssociatedTokenAccount::new(&mut svm, &taker, &mint_a)
+            .owner(&Keypair::new().pubkey())
+            .send()
+            .unwrap();
+
+        let ix = take_ix(
+            &taker.pubkey(), &maker.pubkey(), &mint_a, &mint_b, &escrow_pda,
+            &taker_ata_a, &taker_ata_b, &maker_ata_b, &fake_vault,
+        );
+        assert_escrow_error(send(&mut svm, ix, &taker), EscrowError::InvalidVault);
+    }
+
+    #[test]
+    pub fn test_take_rejects_wrong_token_accounts() {
+        let (mut svm, maker) = setup();
+        let (mint_a, mint_b, escrow_pda, _maker_ata_a, _bump, vault) =
+            do_make(&mut svm, &maker);
+        let (taker, taker_ata_a, taker_ata_b, maker_ata_b) =
+            setup_taker(&mut svm, &maker, &mint_a, &mint_b);
+
+        // mint_b account where the taker should receive mint_a
+        let ix = take_ix(
+            &taker.pubkey(), &maker.pubkey(), &mint_a, &mint_b, &escrow_pda,
+            &taker_ata_b, &taker_ata_b, &maker_ata_b, &vault,
+        );
+        assert_escrow_error(
+            send(&mut svm, ix, &taker),
+            EscrowError::TokenAccountMintMismatch,
+        );
+
+        // Payment routed back to the taker instead of the maker
+        svm.expire_blockhash();
+        let ix = take_ix(
+            &taker.pubkey(), &maker.pubkey(), &mint_a, &mint_b, &escrow_pda,
+            &taker_ata_a, &taker_ata_b, &taker_ata_b, &vault,
+        );
+        assert_escrow_error(
+            send(&mut svm, ix, &taker),
+            EscrowError::TokenAccountOwnerMismatch,
+        );
+    }
+
+    #[test]
+    pub fn test_cancel_rejects_non_maker() {
+        let (mut svm, maker) = setup();
+        let (mint_a, _mint_b, escrow_pda, _maker_ata_a, _bump, vault) =
+            do_make(&mut svm, &maker);
+
+        let attacker = Keypair::new();
+        svm.airdrop(&attacker.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        let attacker_ata = CreateAssociatedTokenAccount::new(&mut svm, &attacker, &mint_a)
+            .owner(&attacker.pubkey())
+            .send()
+            .unwrap();
+
+        let ix = cancel_ix(&attacker.pubkey(), &mint_a, &escrow_pda, &attacker_ata, &vault);
+        assert_escrow_error(send(&mut svm, ix, &attacker), EscrowError::MakerMismatch);
+    }
+
+    #[test]
+    pub fn test_cancel_rejects_foreign_escrow_account() {
+        let (mut svm, maker) = setup();
+        let (mint_a, mint_b, _escrow_pda, maker_ata_a, _bump, vault) =
+            do_make(&mut svm, &maker);
+
+        // Escrow-shaped data naming the caller as maker, owned by another program
+        let mut data = vec![0u8; 161];
+        data[0..32].copy_from_slice(maker.pubkey().as_ref());
+        data[32..64].copy_from_slice(mint_a.as_ref());
+        data[64..96].copy_from_slice(mint_b.as_ref());
+        let fake_escrow = Pubkey::new_unique();
+        svm.set_account(
+            fake_escrow,
+            solana_account::Account {
+                lamports: LAMPORTS_PER_SOL,
+                data,
+                owner: Pubkey::new_unique(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        )
+        .unwrap();
+
+        let ix = cancel_ix(&maker.pubkey(), &mint_a, &fake_escrow, &maker_ata_a, &vault);
+        assert_escrow_error(send(&mut svm, ix, &maker), EscrowError::InvalidEscrowOwner);
+    }
+
+    #[test]
+    pub fn test_cancel_rejects_redirected_refund() {
+        let (mut svm, maker) = setup();
+        let (mint_a, _mint_b, escrow_pda, _maker_ata_a, _bump, vault) =
+            do_make(&mut svm, &maker);
+
+        let other_ata = CreateAssociatedTokenAccount::new(&mut svm, &maker, &mint_a)
+            .owner(&Keypair::new().pubkey())
+            .send()
+            .unwrap();
+
+        let ix = cancel_ix(&maker.pubkey(), &mint_a, &escrow_pda, &other_ata, &vault);
+        assert_escrow_error(
+            send(&mut svm, ix, &maker),
+            EscrowError::TokenAccountOwnerMismatch,
+        );
+    }
+
+    #[test]
+    pub fn test_client_make_take_round_trip() {
+        let (mut svm, maker) = setup();
+        let mint_a = CreateMint::new(&mut svm, &maker).decimals(6).send().unwrap();
+        let mint_b = CreateMint::new(&mut svm, &maker).decimals(6).send().unwrap();
+        let seed = 7;
+
+        let (accounts, bump) =
+            client::MakeAccounts::derive(maker.pubkey(), mint_a, mint_b, seed, TOKEN_PROGRAM_ID);
+        CreateAssociatedTokenAccount::new(&mut svm, &maker, &mint_a)
+            .owner(&maker.pubkey())
+            .send()
+            .unwrap();
+        MintTo::new(&mut svm, &maker, &mint_a, &accounts.maker_ata, AMOUNT_TO_GIVE)
+            .send()
+            .unwrap();
+
+        let args = client::MakeArgs {
+            bump,
+            seed,
+            amount_to_receive: AMOUNT_TO_RECEIVE,
+            amount_to_give: AMOUNT_TO_GIVE,
+            expires_at: 0,
+            allowed_taker: None,
+        };
+        send(&mut svm, client::make_v2(&accounts, &args), &maker).unwrap();
+
+        // The decoder reads back exactly what Make stored
+        let data = svm.get_account(&accounts.escrow).unwrap().data;
+        let escrow = client::EscrowAccount::decode(&data).unwrap();
+        assert_eq!(
+            escrow,
+            client::EscrowAccount {
+                maker: maker.pubkey(),
+                mint_a,
+                mint_b,
+                amount_to_receive: AMOUNT_TO_RECEIVE,
+                amount_to_give: AMOUNT_TO_GIVE,
+                expires_at: 0,
+                seed,
+                bump,
+                allowed_taker: None,
+            }
+        );
+        assert!(client::EscrowAccount::decode(&data[1..]).is_none());
+
+        let (taker, ..) = setup_taker(&mut svm, &maker, &mint_a, &mint_b);
+        let take_accounts = client::TakeAccounts::derive(
+            taker.pubkey(), maker.pubkey(), mint_a, mint_b, seed,
+            TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID,
+        );
+        assert_eq!(take_accounts.escrow, accounts.escrow);
+        assert_eq!(take_accounts.vault, accounts.vault);
+        let take_ix = client::take(&take_accounts, escrow.amount_to_receive, escrow.amount_to_give);
+        send(&mut svm, take_ix, &taker).unwrap();
+
+        assert_eq!(token_balance(&svm, &take_accounts.taker_ata_a), AMOUNT_TO_GIVE);
+        assert_eq!(token_balance(&svm, &take_accounts.maker_ata_b), AMOUNT_TO_RECEIVE);
+    }
+
+    #[test]
+    pub fn test_idl_matches_program() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("idl/escrow.json");
+        let idl: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+
+        assert_eq!(idl["metadata"]["address"], PROGRAM_ID);
+
+        // Discriminants and account lists line up with the client builders
+        let maker = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let (make_accounts, _) =
+            client::MakeAccounts::derive(maker, mint, mint, 0, TOKEN_PROGRAM_ID);
+        let take_accounts = client::TakeAccounts::derive(
+            maker, maker, mint, mint, 0, TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID,
+        );
+        let refund_accounts = client::RefundAccounts::derive(maker, mint, 0, TOKEN_PROGRAM_ID);
+        let built = [
+            ("make", client::make(&make_accounts, &client::MakeArgs::default())),
+            ("take", client::take(&take_accounts, 1, 1)),
+            ("cancel", client::cancel(&refund_accounts)),
+            ("makeV2", client::make_v2(&make_accounts, &client::MakeArgs::default())),
+            ("expire", client::expire(&refund_accounts)),
+            ("takePartial", client::take_partial(&take_accounts, 1, 1)),
+            ("update", client::update(&refund_accounts, 1, 1)),
+        ];
+        let instructions = idl["instructions"].as_array().unwrap();
+        assert_eq!(instructions.len(), built.len());
+        for (entry, (name, ix)) in instructions.iter().zip(built) {
+            assert_eq!(entry["name"], name);
+            assert_eq!(entry["discriminant"]["value"], ix.data[0]);
+            let accounts = entry["accounts"].as_array().unwrap();
+            assert_eq!(accounts.len(), ix.accounts.len(), "{name}");
+            for (account, meta) in accounts.iter().zip(&ix.accounts) {
+                assert_eq!(account["isSigner"], meta.is_signer, "{name}.{}", account["name"]);
+            }
+        }
+
+        // Error codes match EscrowError
+        let errors = idl["errors"].as_array().unwrap();
+        assert_eq!(errors.last().unwrap()["code"], EscrowError::TakerNotAllowed as u32);
+        assert_eq!(errors[EscrowError::EscrowExpired as usize]["name"], "EscrowExpired");
+    }
+
+    /// Helper: builds the Update instruction (discriminator = 6).
+    fn update_ix(
+        maker: &Pubkey,
+        mint_a: &Pubkey,
+        escrow: &Pubkey,
+        maker_ata: &Pubkey,
+        vault: &Pubkey,
+        amount_to_receive: u64,
+        amount_to_give: u64,
+    ) -> Instruction {
+        let accounts = client::RefundAccounts {
+            maker: *maker,
+            mint_a: *mint_a,
+            escrow: *escrow,
+            maker_ata: *maker_ata,
+            vault: *vault,
+            token_program: TOKEN_PROGRAM_ID,
+        };
+        client::update(&accounts, amount_to_receive, amount_to_give)
+    }
+
+    fn escrow_terms(svm: &LiteSVM, escrow: &Pubkey) -> (u64, u64) {
+        let data = svm.get_account(escrow).unwrap().data;
+        let escrow = client::EscrowAccount::decode(&data).unwrap();
+        (escrow.amount_to_receive, escrow.amount_to_give)
+    }
+
+    #[test]
+    pub fn test_update_reprices_and_tops_up() {
+        let (mut svm, maker) = setup();
+        let (mint_a, _mint_b, escrow_pda, maker_ata_a, _bump, vault) =
+            do_make(&mut svm, &maker);
+
+        let ix = update_ix(
+            &maker.pubkey(), &mint_a, &escrow_pda, &maker_ata_a, &vault,
+            2 * AMOUNT_TO_RECEIVE, 2 * AMOUNT_TO_GIVE,
+        );
+        let tx = send(&mut svm, ix, &maker).unwrap();
+        println!("\n\nUpdate transaction successful");
+        println!("CUs Consumed: {}", tx.compute_units_consumed);
+
+        assert_eq!(escrow_terms(&svm, &escrow_pda), (2 * AMOUNT_TO_RECEIVE, 2 * AMOUNT_TO_GIVE));
+        assert_eq!(token_balance(&svm, &vault), 2 * AMOUNT_TO_GIVE);
+        assert_eq!(token_balance(&svm, &maker_ata_a), 1_000_000_000 - 2 * AMOUNT_TO_GIVE);
+    }
+
+    #[test]
+    pub fn test_update_withdraws_from_vault() {
+        let (mut svm, maker) = setup();
+        let (mint_a, _mint_b, escrow_pda, maker_ata_a, _bump, vault) =
+            do_make(&mut svm, &maker);
+
+        let ix = update_ix(
+            &maker.pubkey(), &mint_a, &escrow_pda, &maker_ata_a, &vault,
+            AMOUNT_TO_RECEIVE, AMOUNT_TO_GIVE / 5,
+        );
+        send(&mut svm, ix, &maker).unwrap();
+
+        assert_eq!(escrow_terms(&svm, &escrow_pda), (AMOUNT_TO_RECEIVE, AMOUNT_TO_GIVE / 5));
+        assert_eq!(token_balance(&svm, &vault), AMOUNT_TO_GIVE / 5);
+        assert_eq!(token_balance(&svm, &maker_ata_a), 1_000_000_000 - AMOUNT_TO_GIVE / 5);
+    }
+
+    #[test]
+    pub fn test_update_rejects_invalid_requests() {
+        let (mut svm, maker) = setup();
+        let (mint_a, mint_b, escrow_pda, maker_ata_a, _bump, vault) =
+            do_make(&mut svm, &maker);
+
+        let ix = update_ix(
+            &maker.pubkey(), &mint_a, &escrow_pda, &maker_ata_a, &vault, AMOUNT_TO_RECEIVE, 0,
+        );
+        assert_escrow_error(send(&mut svm, ix, &maker), EscrowError::InvalidAmount);
+
+        // Someone else cannot reprice the maker's escrow
+        let (taker, taker_ata_a, ..) = setup_taker(&mut svm, &maker, &mint_a, &mint_b);
+        let ix = update_ix(
+            &taker.pubkey(), &mint_a, &escrow_pda, &taker_ata_a, &vault, 1, AMOUNT_TO_GIVE,
+        );
+        assert_escrow_error(send(&mut svm, ix, &taker), EscrowError::MakerMismatch);
+    }
+
+    #[test]
+    pub fn test_take_slippage_guard() {
+        let (mut svm, maker) = setup();
+        let (mint_a, mint_b, escrow_pda, maker_ata_a, _bump, vault) =
+            do_make(&mut svm, &maker);
+        let (taker, taker_ata_a, taker_ata_b, maker_ata_b) =
+            setup_taker(&mut svm, &maker, &mint_a, &mint_b);
+        let accounts = take_accounts(
+            &taker.pubkey(), &maker.pubkey(), &mint_a, &mint_b, &escrow_pda,
+            &taker_ata_a, &taker_ata_b, &maker_ata_b, &vault,
+            &TOKEN_PROGRAM_ID, &TOKEN_PROGRAM_ID,
+        );
+
+        // Maker cuts the deposit after the taker quoted the offer
+        let ix = update_ix(
+            &maker.pubkey(), &mint_a, &escrow_pda, &maker_ata_a, &vault,
+            AMOUNT_TO_RECEIVE, AMOUNT_TO_GIVE / 2,
+        );
+        send(&mut svm, ix, &maker).unwrap();
+
+        let ix = client::take(&accounts, AMOUNT_TO_RECEIVE, AMOUNT_TO_GIVE);
+        assert_escrow_error(send(&mut svm, ix, &taker), EscrowError::SlippageExceeded);
+
+        // Leaving the guard out doesn't skip it
+        let mut ix = client::take(&accounts, AMOUNT_TO_RECEIVE, AMOUNT_TO_GIVE / 2);
+        ix.data.truncate(1);
+        assert_instruction_error(
+            send(&mut svm, ix, &taker),
+            InstructionError::InvalidInstructionData,
+        );
+
+        let ix = client::take(&accounts, AMOUNT_TO_RECEIVE, AMOUNT_TO_GIVE / 2);
+        send(&mut svm, ix, &taker).unwrap();
+        assert_eq!(token_balance(&svm, &taker_ata_a), AMOUNT_TO_GIVE / 2);
+    }
+
+    #[test]
+    pub fn test_take_partial_min_payout() {
+        let (mut svm, maker) = setup();
+        let (mint_a, mint_b, escrow_pda, _maker_ata_a, _bump, vault) =
+            do_make(&mut svm, &maker);
+        let (taker, taker_ata_a, taker_ata_b, maker_ata_b) =
+            setup_taker(&mut svm, &maker, &mint_a, &mint_b);
+        let accounts = take_accounts(
+            &taker.pubkey(), &maker.pubkey(), &mint_a, &mint_b, &escrow_pda,
+            &taker_ata_a, &taker_ata_b, &maker_ata_b, &vault,
+            &TOKEN_PROGRAM_ID, &TOKEN_PROGRAM_ID,
+        );
+
+        // A 10-token fill pays out 50 tokens at the current price
+        let fill = 10_000_000;
+        let ix = client::take_partial(&accounts, fill, 5 * fill + 1);
+        assert_escrow_error(send(&mut svm, ix, &taker), EscrowError::SlippageExceeded);
+
+        // Leaving the guard out doesn't skip it
+        let mut ix = client::take_partial(&accounts, fill, 5 * fill);
+        ix.data.truncate(9);
+        assert_instruction_error(
+            send(&mut svm, ix, &taker),
+            InstructionError::InvalidInstructionData,
+        );
+
+        let ix = client::take_partial(&accounts, fill, 5 * fill);
+        send(&mut svm, ix, &taker).unwrap();
+        assert_eq!(token_balance(&svm, &taker_ata_a), 5 * fill);
+    }
+
+    #[test]
+    pub fn test_private_escrow_only_allowed_taker() {
+        let (mut svm, maker) = setup();
+        let mint_a = CreateMint::new(&mut svm, &maker).decimals(6).send().unwrap();
+        let mint_b = CreateMint::new(&mut svm, &maker).decimals(6).send().unwrap();
+        let (taker, ..) = setup_taker(&mut svm, &maker, &mint_a, &mint_b);
+        let stranger = Keypair::new();
+        svm.airdrop(&stranger.pubkey(), 5 * LAMPORTS_PER_SOL).unwrap();
+        CreateAssociatedTokenAccount::new(&mut svm, &stranger, &mint_a)
+            .owner(&stranger.pubkey())
+            .send()
+            .unwrap();
+        let stranger_ata_b = CreateAssociatedTokenAccount::new(&mut svm, &stranger, &mint_b)
+            .owner(&stranger.pubkey())
+            .send()
+            .unwrap();
+        MintTo::new(&mut svm, &maker, &mint_b, &stranger_ata_b, AMOUNT_TO_RECEIVE)
+            .send()
+            .unwrap();
+
+        let seed = 3;
+        let (accounts, bump) =
+            client::MakeAccounts::derive(maker.pubkey(), mint_a, mint_b, seed, TOKEN_PROGRAM_ID);
+        CreateAssociatedTokenAccount::new(&mut svm, &maker, &mint_a)
+            .owner(&maker.pubkey())
+            .send()
+            .unwrap();
+        MintTo::new(&mut svm, &maker, &mint_a, &accounts.maker_ata, AMOUNT_TO_GIVE)
+            .send()
+            .unwrap();
+        let args = client::MakeArgs {
+            bump,
+            seed,
+            amount_to_receive: AMOUNT_TO_RECEIVE,
+            amount_to_give: AMOUNT_TO_GIVE,
+            expires_at: 0,
+            allowed_taker: Some(taker.pubkey()),
+        };
+        send(&mut svm, client::make(&accounts, &args), &maker).unwrap();
+
+        let data = svm.get_account(&accounts.escrow).unwrap().data;
+        let escrow = client::EscrowAccount::decode(&data).unwrap();
+        assert_eq!(escrow.allowed_taker, Some(taker.pubkey()));
+
+        // Anyone else is turned away, for full and partial fills alike
+        let stranger_accounts = client::TakeAccounts::derive(
+            stranger.pubkey(), maker.pubkey(), mint_a, mint_b, seed,
+            TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID,
+        );
+        assert_escrow_error(
+            send(
+                &mut svm,
+                client::take(&stranger_accounts, AMOUNT_TO_RECEIVE, AMOUNT_TO_GIVE),
+                &stranger,
+            ),
+            EscrowError::TakerNotAllowed,
+        );
+        assert_escrow_error(
+            send(&mut svm, client::take_partial(&stranger_accounts, 1_000_000, 5_000_000), &stranger),
+            EscrowError::TakerNotAllowed,
+        );
+
+        let taker_accounts = client::TakeAccounts::derive(
+            taker.pubkey(), maker.pubkey(), mint_a, mint_b, seed,
+            TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID,
+        );
+        let take_ix = client::take(&taker_accounts, AMOUNT_TO_RECEIVE, AMOUNT_TO_GIVE);
+        send(&mut svm, take_ix, &taker).unwrap();
+        assert_eq!(token_balance(&svm, &taker_accounts.taker_ata_a), AMOUNT_TO_GIVE);
+    }
+
+    #[test]
+    pub fn test_events_cover_escrow_lifecycle() {
+        let (mut svm, maker) = setup();
+        let mint_a = CreateMint::new(&mut svm, &maker).decimals(6).send().unwrap();
+        let mint_b = CreateMint::new(&mut svm, &maker).decimals(6).send().unwrap();
+        let (taker, ..) = setup_taker(&mut svm, &maker, &mint_a, &mint_b);
+
+        let seed = 11;
+        let (accounts, bump) =
+            client::MakeAccounts::derive(maker.pubkey(), mint_a, mint_b, seed, TOKEN_PROGRAM_ID);
+        CreateAssociatedTokenAccount::new(&mut svm, &maker, &mint_a)
+            .owner(&maker.pubkey())
+            .send()
+            .unwrap();
+        MintTo::new(&mut svm, &maker, &mint_a, &accounts.maker_ata, AMOUNT_TO_GIVE)
+            .send()
+            .unwrap();
+        let args = client::MakeArgs {
+            bump,
+            seed,
+            amount_to_receive: AMOUNT_TO_RECEIVE,
+            amount_to_give: AMOUNT_TO_GIVE,
+            expires_at: 0,
+            allowed_taker: Some(taker.pubkey()),
+        };
+        let tx = send(&mut svm, client::make(&accounts, &args), &maker).unwrap();
+        assert_eq!(
+            client::EscrowEvent::from_logs(&tx.logs),
+            [client::EscrowEvent::Made {
+                escrow: accounts.escrow,
+                maker: maker.pubkey(),
+                mint_a,
+                mint_b,
+                seed,
+                amount_to_receive: AMOUNT_TO_RECEIVE,
+                amount_to_give: AMOUNT_TO_GIVE,
+                expires_at: 0,
+                allowed_taker: Some(taker.pubkey()),
+            }]
+        );
+
+        let take_accounts = client::TakeAccounts::derive(
+            taker.pubkey(), maker.pubkey(), mint_a, mint_b, seed,
+            TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID,
+        );
+        let fill = AMOUNT_TO_RECEIVE / 4;
+        let tx = send(&mut svm, client::take_partial(&take_accounts, fill, AMOUNT_TO_GIVE / 4), &taker).unwrap();
+        assert_eq!(
+            client::EscrowEvent::from_logs(&tx.logs),
+            [client::EscrowEvent::Taken {
+                escrow: accounts.escrow,
+                maker: maker.pubkey(),
+                taker: taker.pubkey(),
+                fill,
+                payout: AMOUNT_TO_GIVE / 4,
+                remaining: AMOUNT_TO_RECEIVE - fill,
+            }]
+        );
+
+        let take_ix = client::take(
+            &take_accounts,
+            AMOUNT_TO_RECEIVE - fill,
+            AMOUNT_TO_GIVE - AMOUNT_TO_GIVE / 4,
+        );
+        let tx = send(&mut svm, take_ix, &taker).unwrap();
+        assert_eq!(
+            client::EscrowEvent::from_logs(&tx.logs),
+            [client::EscrowEvent::Taken {
+                escrow: accounts.escrow,
+                maker: maker.pubkey(),
+                taker: taker.pubkey(),
+                fill: AMOUNT_TO_RECEIVE - fill,
+                payout: AMOUNT_TO_GIVE - AMOUNT_TO_GIVE / 4,
+                remaining: 0,
+            }]
+        );
+    }
+
+    #[test]
+    pub fn test_events_for_update_and_cancel() {
+        let (mut svm, maker) = setup();
+        let (mint_a, _mint_b, escrow_pda, maker_ata_a, _bump, vault) =
+            do_make(&mut svm, &maker);
+
+        let ix = update_ix(
+            &maker.pubkey(), &mint_a, &escrow_pda, &maker_ata_a, &vault,
+            AMOUNT_TO_RECEIVE, AMOUNT_TO_GIVE / 2,
+        );
+        let tx = send(&mut svm, ix, &maker).unwrap();
+        assert_eq!(
+            client::EscrowEvent::from_logs(&tx.logs),
+            [client::EscrowEvent::Updated {
+                escrow: escrow_pda,
+                amount_to_receive: AMOUNT_TO_RECEIVE,
+                amount_to_give: AMOUNT_TO_GIVE / 2,
+            }]
+        );
+
+        let ix = cancel_ix(&maker.pubkey(), &mint_a, &escrow_pda, &maker_ata_a, &vault);
+        let tx = send(&mut svm, ix, &maker).unwrap();
+        assert_eq!(
+            client::EscrowEvent::from_logs(&tx.logs),
+            [client::EscrowEvent::Refunded {
+                escrow: escrow_pda,
+                maker: maker.pubkey(),
+                amount: AMOUNT_TO_GIVE / 2,
+                expired: false,
+            }]
+        );
+    }
+
+    #[test]
+    pub fn test_event_decoder_ignores_other_programs() {
+        let event = [
+            &[132, 209, 49, 109, 135, 138, 28, 81][..],
+            Pubkey::new_unique().as_ref(),
+            Pubkey::new_unique().as_ref(),
+            &7u64.to_le_bytes(),
+            &[1],
+        ]
+        .concat();
+        let data = format!("Program data: {}", STANDARD.encode(&event));
+        let other = Pubkey::new_unique();
+
+        let logs = [
+            format!("Program {PROGRAM_ID} invoke [1]"),
+            format!("Program {other} invoke [2]"),
+            "Program log: success".to_string(),
+            data.clone(),
+            format!("Program {other} success"),
+            data,
+            format!("Program {PROGRAM_ID} success"),
+        ];
+        let events = client::EscrowEvent::from_logs(&logs);
+        assert_eq!(events.len(), 1);
+        assert!(matches!(
+            events[0],
+            client::EscrowEvent::Refunded { amount: 7, expired: true, .. }
+        ));
+
+        // Truncated payloads are rejected rather than half-decoded
+        assert_eq!(client::EscrowEvent::decode(&event[..event.len() - 1]), None);
+    }
 }
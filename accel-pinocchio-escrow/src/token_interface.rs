@@ -0,0 +1,237 @@
+//! Token CPIs and account reads that work for both SPL Token and Token-2022.
+//!
+//! `pinocchio_token` builders are hard-wired to the legacy program and its
+//! 165-byte account layout, so the escrow builds `TransferChecked` and
+//! `CloseAccount` itself against whichever token program the caller passes.
+//! Token-2022 refuses to close an account still holding withheld transfer
+//! fees, so vaults are harvested to their mint first.
+//! Transfer-hook mints need their extra accounts (hook program, validation
+//! PDA and resolved metas) forwarded on the CPI; clients resolve them off-chain
+//! and append them as trailing accounts. Token-2022 looks up what it needs by
+//! key and ignores the rest.
+
+use pinocchio::{
+    AccountView, Address, ProgramResult,
+    cpi::{Signer, slice_invoke_signed},
+    error::ProgramError,
+    instruction::{InstructionAccount, InstructionView},
+};
+use crate::errors::EscrowError;
+
+pub const TOKEN_2022_PROGRAM_ID: [u8; 32] =
+    pinocchio_pubkey::pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: [u8; 32] =
+    pinocchio_pubkey::pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+/// Base token account size; Token-2022 accounts with extensions are longer.
+const TOKEN_ACCOUNT_LEN: usize = 165;
+/// Base mint size; the decimals byte sits at the same offset in both programs.
+const MINT_LEN: usize = 82;
+const MINT_DECIMALS_OFFSET: usize = 44;
+
+/// Token-2022 `ExtensionType::TransferFeeAmount`: fees withheld in an account.
+const TRANSFER_FEE_AMOUNT_EXTENSION: u16 = 2;
+
+const TRANSFER_CHECKED: u8 = 12;
+const CLOSE_ACCOUNT: u8 = 9;
+const TRANSFER_FEE_EXTENSION: u8 = 26;
+const HARVEST_WITHHELD_TOKENS_TO_MINT: u8 = 4;
+
+pub fn is_token_program(program: &AccountView) -> bool {
+    program.address() == &pinocchio_token::ID
+        || program.address().as_array() == &TOKEN_2022_PROGRAM_ID
+}
+
+/// Verifies `token_program` is SPL Token or Token-2022 and owns `mint`.
+pub fn check_mint_program(mint: &AccountView, token_program: &AccountView) -> ProgramResult {
+    if !is_token_program(token_program) {
+        return Err(EscrowError::InvalidTokenProgram.into());
+    }
+    if unsafe { mint.owner() } != token_program.address() {
+        return Err(EscrowError::TokenProgramMismatch.into());
+    }
+    Ok(())
+}
+
+pub fn mint_decimals(mint: &AccountView) -> Result<u8, ProgramError> {
+    let data = mint.try_borrow()?;
+    if data.len() < MINT_LEN {
+        return Err(EscrowError::InvalidMint.into());
+    }
+    Ok(data[MINT_DECIMALS_OFFSET])
+}
+
+/// Whether `account` is the associated token account of `wallet` for `mint`
+/// under `token_program`. On-chain `find_program_address` is a single syscall
+/// that derives the canonical address.
+pub fn is_associated_token_address(
+    account: &Address,
+    wallet: &Address,
+    mint: &Address,
+    token_program: &Address,
+) -> bool {
+    let seeds = [wallet.as_ref(), token_program.as_ref(), mint.as_ref()];
+    let (ata, _) =
+        Address::find_program_address(&seeds, &Address::from(ASSOCIATED_TOKEN_PROGRAM_ID));
+    ata == *account
+}
+
+/// Transfer fees Token-2022 withheld in `account`; 0 without the extension.
+pub fn withheld_fees(account: &AccountView) -> Result<u64, ProgramError> {
+    let data = account.try_borrow()?;
+    // Extensions follow the base account and its one-byte account type as
+    // [type: u16, length: u16, value] entries
+    let mut tlv = data.get(TOKEN_ACCOUNT_LEN + 1..).unwrap_or(&[]);
+    while tlv.len() >= 4 {
+        let kind = u16::from_le_bytes([tlv[0], tlv[1]]);
+        let len = u16::from_le_bytes([tlv[2], tlv[3]]) as usize;
+        let value = tlv.get(4..4 + len).ok_or(EscrowError::InvalidTokenAccount)?;
+        if kind == TRANSFER_FEE_AMOUNT_EXTENSION {
+            let withheld = value.get(..8).ok_or(EscrowError::InvalidTokenAccount)?;
+            return Ok(u64::from_le_bytes(withheld.try_into().unwrap()));
+        }
+        tlv = &tlv[4 + len..];
+    }
+    Ok(0)
+}
+
+/// Moves the transfer fees withheld in `account` to `mint` so the account can
+/// be closed; a no-op when none are withheld. Harvesting is permissionless,
+/// but `mint` must be passed writable.
+pub fn harvest_withheld_fees(
+    account: &AccountView,
+    mint: &AccountView,
+    token_program: &AccountView,
+) -> ProgramResult {
+    if withheld_fees(account)? == 0 {
+        return Ok(());
+    }
+
+    let metas = [
+        InstructionAccount::writable(mint.address()),
+        InstructionAccount::writable(account.address()),
+    ];
+
+    let instruction = InstructionView {
+        program_id: token_program.address(),
+        accounts: &metas,
+        data: &[TRANSFER_FEE_EXTENSION, HARVEST_WITHHELD_TOKENS_TO_MINT],
+    };
+
+    slice_invoke_signed(&instruction, &[mint, account], &[])
+}
+
+/// Fields copied out of a token account so no borrow outlives the read.
+pub struct TokenAccountInfo {
+    pub mint: Address,
+    pub owner: Address,
+    pub amount: u64,
+}
+
+pub fn load_token_account(account: &AccountView) -> Result<TokenAccountInfo, ProgramError> {
+    let owner_program = unsafe { account.owner() };
+    if owner_program != &pinocchio_token::ID
+        && owner_program.as_array() != &TOKEN_2022_PROGRAM_ID
+    {
+        return Err(EscrowError::InvalidTokenAccount.into());
+    }
+
+    let data = account.try_borrow()?;
+    if data.len() < TOKEN_ACCOUNT_LEN {
+        return Err(EscrowError::InvalidTokenAccount.into());
+    }
+
+    let mut mint = [0u8; 32];
+    let mut owner = [0u8; 32];
+    let mut amount = [0u8; 8];
+    mint.copy_from_slice(&data[0..32]);
+    owner.copy_from_slice(&data[32..64]);
+    amount.copy_from_slice(&data[64..72]);
+
+    Ok(TokenAccountInfo {
+        mint: Address::from(mint),
+        owner: Address::from(owner),
+        amount: u64::from_le_bytes(amount),
+    })
+}
+
+pub struct TransferChecked<'a> {
+    pub from: &'a AccountView,
+    pub mint: &'a AccountView,
+    pub to: &'a AccountView,
+    pub authority: &'a AccountView,
+    pub amount: u64,
+    pub decimals: u8,
+    pub token_program: &'a AccountView,
+    /// Transfer-hook accounts, appended after the four required accounts.
+    pub extra_accounts: &'a [AccountView],
+}
+
+impl TransferChecked<'_> {
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let mut metas = Vec::with_capacity(4 + self.extra_accounts.len());
+        metas.push(InstructionAccount::writable(self.from.address()));
+        metas.push(InstructionAccount::readonly(self.mint.address()));
+        metas.push(InstructionAccount::writable(self.to.address()));
+        metas.push(InstructionAccount::readonly_signer(self.authority.address()));
+
+        let mut views = Vec::with_capacity(4 + self.extra_accounts.len());
+        views.extend_from_slice(&[self.from, self.mint, self.to, self.authority]);
+
+        for account in self.extra_accounts {
+            metas.push(InstructionAccount::new(
+                account.address(),
+                account.is_writable(),
+                account.is_signer(),
+            ));
+            views.push(account);
+        }
+
+        // [discriminator, amount: u64 LE, decimals: u8]
+        let mut data = [0u8; 10];
+        data[0] = TRANSFER_CHECKED;
+        data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+        data[9] = self.decimals;
+
+        let instruction = InstructionView {
+            program_id: self.token_program.address(),
+            accounts: &metas,
+            data: &data,
+        };
+
+        slice_invoke_signed(&instruction, &views, signers)
+    }
+}
+
+pub struct CloseAccount<'a> {
+    pub account: &'a AccountView,
+    pub destination: &'a AccountView,
+    pub authority: &'a AccountView,
+    pub token_program: &'a AccountView,
+}
+
+impl CloseAccount<'_> {
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let metas = [
+            InstructionAccount::writable(self.account.address()),
+            InstructionAccount::writable(self.destination.address()),
+            InstructionAccount::readonly_signer(self.authority.address()),
+        ];
+
+        let instruction = InstructionView {
+            program_id: self.token_program.address(),
+            accounts: &metas,
+            data: &[CLOSE_ACCOUNT],
+        };
+
+        slice_invoke_signed(
+            &instruction,
+            &[self.account, self.destination, self.authority],
+            signers,
+        )
+    }
+}
@@ -0,0 +1,98 @@
+//! Account checks shared by the escrow instructions.
+//!
+//! Each helper fails with a typed `EscrowError` (or the runtime's own error
+//! for a missing signature) so clients can tell which account was rejected.
+
+use pinocchio::{AccountView, Address, ProgramResult, error::ProgramError};
+use pinocchio_pubkey::derive_address;
+
+use crate::errors::EscrowError;
+use crate::state::Escrow;
+use crate::token_interface::{self, TokenAccountInfo};
+
+pub fn check_signer(account: &AccountView) -> ProgramResult {
+    if !account.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    Ok(())
+}
+
+pub fn check_program_owned(account: &AccountView) -> ProgramResult {
+    if unsafe { account.owner() } != &crate::ID {
+        return Err(EscrowError::InvalidEscrowOwner.into());
+    }
+    Ok(())
+}
+
+/// Verifies `escrow` is the PDA `["escrow", maker, seed, bump]`.
+pub fn check_escrow_pda(
+    escrow: &AccountView,
+    maker: &Address,
+    seed_bytes: &[u8; 8],
+    bump: u8,
+) -> ProgramResult {
+    let seed = [b"escrow".as_ref(), maker.as_ref(), seed_bytes, &[bump]];
+    let pda = derive_address(&seed, None, &crate::ID.to_bytes());
+    if pda != *escrow.address().as_array() {
+        return Err(EscrowError::InvalidEscrowPda.into());
+    }
+    Ok(())
+}
+
+/// Loads a live escrow and checks it belongs to `maker`, holds `mint_a` and
+/// sits at the address its stored seed and bump derive.
+pub fn load_escrow<'a>(
+    escrow: &'a AccountView,
+    maker: &AccountView,
+    mint_a: &AccountView,
+) -> Result<&'a mut Escrow, ProgramError> {
+    check_program_owned(escrow)?;
+    let state = Escrow::from_account_info(escrow)?;
+    if state.maker() != *maker.address() {
+        return Err(EscrowError::MakerMismatch.into());
+    }
+    if state.mint_a() != *mint_a.address() {
+        return Err(EscrowError::MintAMismatch.into());
+    }
+    check_escrow_pda(escrow, maker.address(), &state.seed().to_le_bytes(), state.bump)?;
+    Ok(state)
+}
+
+/// Verifies `account` is a token account for `mint` owned by `owner`.
+pub fn check_token_account(
+    account: &AccountView,
+    owner: &Address,
+    mint: &Address,
+) -> Result<TokenAccountInfo, ProgramError> {
+    let state = token_interface::load_token_account(account)?;
+    if state.owner != *owner {
+        return Err(EscrowError::TokenAccountOwnerMismatch.into());
+    }
+    if state.mint != *mint {
+        return Err(EscrowError::TokenAccountMintMismatch.into());
+    }
+    Ok(state)
+}
+
+/// Verifies `vault` is the escrow PDA's associated token account for mint_a
+/// under `token_program`, as created by Make.
+pub fn check_vault(
+    vault: &AccountView,
+    escrow: &AccountView,
+    mint_a: &AccountView,
+    token_program: &AccountView,
+) -> Result<TokenAccountInfo, ProgramError> {
+    if !token_interface::is_associated_token_address(
+        vault.address(),
+        escrow.address(),
+        mint_a.address(),
+        token_program.address(),
+    ) {
+        return Err(EscrowError::InvalidVault.into());
+    }
+    let state = token_interface::load_token_account(vault)?;
+    if state.owner != *escrow.address() || state.mint != *mint_a.address() {
+        return Err(EscrowError::InvalidVault.into());
+    }
+    Ok(state)
+}
@@ -0,0 +1,56 @@
+//! Off-chain decoding of the events in `events.rs`. Enabled with the `client`
+//! feature (and always in tests).
+
+use anchor_lang::{AnchorDeserialize, Discriminator};
+
+use crate::events::{
+    BasketMade, BasketRefunded, BasketTaken, CounterAccepted, CounterMade, CounterWithdrawn, EscrowMade,
+    EscrowRefunded, EscrowTaken,
+};
+
+#[derive(Debug, PartialEq)]
+pub enum EscrowEvent {
+    Made(EscrowMade),
+    Taken(EscrowTaken),
+    Refunded(EscrowRefunded),
+    BasketMade(BasketMade),
+    BasketTaken(BasketTaken),
+    BasketRefunded(BasketRefunded),
+    CounterMade(CounterMade),
+    CounterAccepted(CounterAccepted),
+    CounterWithdrawn(CounterWithdrawn),
+}
+
+impl EscrowEvent {
+    /// Decodes one `Program data:` payload; `None` if it isn't an escrow event.
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        fn parse<T: AnchorDeserialize + Discriminator>(data: &[u8]) -> Option<T> {
+            let mut fields = data.strip_prefix(T::DISCRIMINATOR)?;
+            let event = T::deserialize(&mut fields).ok()?;
+            fields.is_empty().then_some(event)
+        }
+
+        parse(data)
+            .map(Self::Made)
+            .or_else(|| parse(data).map(Self::Taken))
+            .or_else(|| parse(data).map(Self::Refunded))
+            .or_else(|| parse(data).map(Self::BasketMade))
+            .or_else(|| parse(data).map(Self::BasketTaken))
+            .or_else(|| parse(data).map(Self::BasketRefunded))
+            .or_else(|| parse(data).map(Self::CounterMade))
+            .or_else(|| parse(data).map(Self::CounterAccepted))
+            .or_else(|| parse(data).map(Self::CounterWithdrawn))
+    }
+
+    /// Decodes the escrow events in a transaction's log messages, in order.
+    ///
+    /// Only `Program data:` lines logged while the escrow program itself is
+    /// executing are read, so another program in the transaction cannot forge
+    /// escrow events by logging the same bytes.
+    pub fn from_logs<S: AsRef<str>>(logs: &[S]) -> Vec<Self> {
+        program_logs::program_data(logs, &crate::ID.to_string())
+            .iter()
+            .filter_map(|data| Self::decode(data))
+            .collect()
+    }
+}
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum EscrowError {
+    #[msg("Escrow is still locked. Must wait until its unlock time.")]
+    EscrowStillLocked,
+    #[msg("This escrow is reserved for a different taker.")]
+    TakerNotAllowed,
+    #[msg("Expiry must be in the future and after the unlock time.")]
+    InvalidTimeWindow,
+    #[msg("Escrow has expired and can no longer be taken.")]
+    EscrowExpired,
+    #[msg("Escrow is open for takers and cannot be refunded until it expires.")]
+    EscrowActive,
+    #[msg("Fee must be at most 1000 basis points.")]
+    InvalidFeeBps,
+    #[msg("Only the config admin can do this.")]
+    Unauthorized,
+    #[msg("A basket needs 1 to 4 deposit and request legs with non-zero amounts and distinct mints.")]
+    InvalidBasket,
+    #[msg("Remaining accounts don't match the basket's legs.")]
+    BasketAccountsMismatch,
+    #[msg("Could not compute the mint's transfer fee.")]
+    InvalidTransferFee,
+    #[msg("Auction floor must not exceed the start price and its end must be in the future.")]
+    InvalidAuction,
+    #[msg("The current price is above the taker's maximum.")]
+    PriceAboveMax,
+    #[msg("Counter-offer amount must be non-zero.")]
+    InvalidCounter,
+    #[msg("The counter-offer is below the amount the maker accepted.")]
+    CounterBelowAccepted,
+}
@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{BasketLeg, DutchAuction};
+
+// Lifecycle events, logged with `emit!` as `Program data: <base64>` lines
+// (8-byte discriminator + Borsh fields). `client::EscrowEvent` decodes them.
+
+#[event]
+#[derive(Debug, PartialEq)]
+pub struct EscrowMade {
+    pub escrow: Pubkey,
+    pub maker: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub seed: u64,
+    pub deposit: u64,  // Mint A moved into the vault
+    pub receive: u64,  // Mint B the maker wants
+    pub taker: Option<Pubkey>,
+    pub unlock_at: i64,
+    pub expires_at: Option<i64>,
+    pub auction: Option<DutchAuction>,
+}
+
+#[event]
+#[derive(Debug, PartialEq)]
+pub struct EscrowTaken {
+    pub escrow: Pubkey,
+    pub maker: Pubkey,
+    pub taker: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub deposit: u64,  // Mint A paid out of the vault to the taker
+    pub receive: u64,  // Mint B paid to the maker (the auction price at take, if any)
+    pub fee: u64,  // Mint A skimmed to the treasury
+}
+
+#[event]
+#[derive(Debug, PartialEq)]
+pub struct EscrowRefunded {
+    pub escrow: Pubkey,
+    pub maker: Pubkey,
+    pub mint_a: Pubkey,
+    pub amount: u64,  // Mint A returned to the maker
+}
+
+#[event]
+#[derive(Debug, PartialEq)]
+pub struct BasketMade {
+    pub basket: Pubkey,
+    pub maker: Pubkey,
+    pub seed: u64,
+    pub deposits: Vec<BasketLeg>,  // Moved into the basket's vaults
+    pub requests: Vec<BasketLeg>,  // Wanted by the maker
+}
+
+#[event]
+#[derive(Debug, PartialEq)]
+pub struct BasketTaken {
+    pub basket: Pubkey,
+    pub maker: Pubkey,
+    pub taker: Pubkey,
+    pub fees: Vec<u64>,  // Protocol fee taken from each deposit leg's vault, in order
+}
+
+#[event]
+#[derive(Debug, PartialEq)]
+pub struct BasketRefunded {
+    pub basket: Pubkey,
+    pub maker: Pubkey,
+}
+
+#[event]
+#[derive(Debug, PartialEq)]
+pub struct CounterMade {
+    pub counter: Pubkey,
+    pub escrow: Pubkey,
+    pub taker: Pubkey,
+    pub amount: u64,  // Mint B locked in the counter's vault
+}
+
+#[event]
+#[derive(Debug, PartialEq)]
+pub struct CounterAccepted {
+    pub counter: Pubkey,
+    pub escrow: Pubkey,
+    pub maker: Pubkey,
+    pub taker: Pubkey,
+    pub deposit: u64,  // Mint A paid out of the escrow vault to the taker
+    pub receive: u64,  // Mint B paid out of the counter vault to the maker
+    pub fee: u64,  // Mint A skimmed to the treasury
+}
+
+#[event]
+#[derive(Debug, PartialEq)]
+pub struct CounterWithdrawn {
+    pub counter: Pubkey,
+    pub escrow: Pubkey,
+    pub taker: Pubkey,
+    pub amount: u64,  // Mint B returned to the taker
+}
@@ -0,0 +1,194 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{create_idempotent, get_associated_token_address_with_program_id, Create},
+    token_interface::{close_account, CloseAccount, Mint, TokenAccount, TransferChecked},
+};
+
+use super::transfer_fee::{gross_up, harvest_withheld_fees, transfer_with_fee};
+use crate::{errors::EscrowError, state::{BasketLeg, EscrowConfig}};
+
+// Basket legs pass their token accounts as remaining accounts, 3 per leg.
+pub const ACCOUNTS_PER_LEG: usize = 3;
+
+// Deposit legs in `take_basket` also pass the fee recipient's ATA.
+pub const ACCOUNTS_PER_FEE_LEG: usize = 4;
+
+/// Splits `remaining` into one chunk of `per_leg` accounts per leg, requiring
+/// exactly `legs` chunks.
+pub fn leg_accounts<'a, 'info>(
+    remaining: &'a [AccountInfo<'info>],
+    legs: usize,
+    per_leg: usize,
+) -> Result<std::slice::ChunksExact<'a, AccountInfo<'info>>> {
+    require_eq!(remaining.len(), legs * per_leg, EscrowError::BasketAccountsMismatch);
+    Ok(remaining.chunks_exact(per_leg))
+}
+
+/// The payer and programs every per-leg CPI needs. Each leg goes through the
+/// token program that owns its mint, so a basket can mix SPL Token and
+/// Token-2022 mints.
+pub struct LegCpi<'info> {
+    pub payer: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub token_2022_program: AccountInfo<'info>,
+    pub associated_token_program: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+}
+
+/// Where `release` sends the protocol fee.
+pub struct LegFee<'a, 'info> {
+    pub config: &'a EscrowConfig,
+    pub fee_recipient: &'a AccountInfo<'info>,
+}
+
+impl<'info> LegCpi<'info> {
+    /// Checks `mint` is the leg's mint and returns the token program that owns
+    /// it along with its decimals.
+    fn leg_mint(&self, mint: &AccountInfo<'info>, leg: &BasketLeg) -> Result<(&AccountInfo<'info>, u8)> {
+        require_keys_eq!(mint.key(), leg.mint, EscrowError::BasketAccountsMismatch);
+        let token_program = if *mint.owner == self.token_program.key() {
+            &self.token_program
+        } else if *mint.owner == self.token_2022_program.key() {
+            &self.token_2022_program
+        } else {
+            return err!(EscrowError::BasketAccountsMismatch);
+        };
+
+        Ok((token_program, Mint::try_deserialize(&mut &mint.try_borrow_data()?[..])?.decimals))
+    }
+
+    /// Checks `ata` is `authority`'s associated token account for `mint` and
+    /// creates it if it doesn't exist yet.
+    fn init_ata(
+        &self,
+        token_program: &AccountInfo<'info>,
+        ata: &AccountInfo<'info>,
+        authority: &AccountInfo<'info>,
+        mint: &AccountInfo<'info>,
+    ) -> Result<()> {
+        let expected = get_associated_token_address_with_program_id(authority.key, mint.key, token_program.key);
+        require_keys_eq!(ata.key(), expected, EscrowError::BasketAccountsMismatch);
+
+        let cpi_accounts = Create {
+            payer: self.payer.clone(),
+            associated_token: ata.clone(),
+            authority: authority.clone(),
+            mint: mint.clone(),
+            system_program: self.system_program.clone(),
+            token_program: token_program.clone(),
+        };
+
+        create_idempotent(CpiContext::new(self.associated_token_program.clone(), cpi_accounts))
+    }
+
+    /// Moves the leg's amount from the payer's token account into the vault
+    /// `basket` owns for the leg's mint. Accounts: `[mint, payer_ata, vault]`.
+    pub fn deposit(&self, leg: &BasketLeg, accounts: &[AccountInfo<'info>], basket: &AccountInfo<'info>) -> Result<()> {
+        let [mint, from, vault] = accounts else {
+            return err!(EscrowError::BasketAccountsMismatch);
+        };
+        let (token_program, decimals) = self.leg_mint(mint, leg)?;
+        self.init_ata(token_program, vault, basket, mint)?;
+
+        let cpi_accounts = TransferChecked {
+            from: from.clone(),
+            to: vault.clone(),
+            authority: self.payer.clone(),
+            mint: mint.clone(),
+        };
+
+        transfer_with_fee(CpiContext::new(token_program.clone(), cpi_accounts), leg.amount, decimals)
+    }
+
+    /// Pays the leg's amount from the payer's token account to `recipient`'s
+    /// ATA, grossed up so the full amount arrives on transfer-fee mints.
+    /// Accounts: `[mint, payer_ata, recipient_ata]`.
+    pub fn pay(&self, leg: &BasketLeg, accounts: &[AccountInfo<'info>], recipient: &AccountInfo<'info>) -> Result<()> {
+        let [mint, from, to] = accounts else {
+            return err!(EscrowError::BasketAccountsMismatch);
+        };
+        let (token_program, decimals) = self.leg_mint(mint, leg)?;
+        self.init_ata(token_program, to, recipient, mint)?;
+
+        let cpi_accounts = TransferChecked {
+            from: from.clone(),
+            to: to.clone(),
+            authority: self.payer.clone(),
+            mint: mint.clone(),
+        };
+
+        let amount = gross_up(mint, leg.amount)?;
+
+        transfer_with_fee(CpiContext::new(token_program.clone(), cpi_accounts), amount, decimals)
+    }
+
+    /// Empties the basket's vault for the leg's mint into `recipient`'s ATA
+    /// and closes the vault, rent to `maker`. With a `fee`, the protocol fee
+    /// on the vault goes to the fee recipient's ATA first; returns the fee.
+    /// Accounts: `[mint, vault, recipient_ata]`, plus `fee_recipient_ata` with
+    /// a fee. The mint must be writable so withheld transfer fees can be
+    /// harvested.
+    #[allow(clippy::too_many_arguments)]
+    pub fn release(
+        &self,
+        leg: &BasketLeg,
+        accounts: &[AccountInfo<'info>],
+        basket: &AccountInfo<'info>,
+        recipient: &AccountInfo<'info>,
+        maker: &AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+        fee: Option<LegFee<'_, 'info>>,
+    ) -> Result<u64> {
+        let (mint, vault, to, treasury) = match (accounts, &fee) {
+            ([mint, vault, to], None) => (mint, vault, to, None),
+            ([mint, vault, to, treasury], Some(fee)) => (mint, vault, to, Some((treasury, fee))),
+            _ => return err!(EscrowError::BasketAccountsMismatch),
+        };
+        let (token_program, decimals) = self.leg_mint(mint, leg)?;
+        let expected = get_associated_token_address_with_program_id(basket.key, mint.key, token_program.key);
+        require_keys_eq!(vault.key(), expected, EscrowError::BasketAccountsMismatch);
+        self.init_ata(token_program, to, recipient, mint)?;
+
+        // Send the whole balance, so tokens donated to the vault don't block closing it
+        let amount = TokenAccount::try_deserialize(&mut &vault.try_borrow_data()?[..])?.amount;
+
+        let send = |to: &AccountInfo<'info>, amount: u64| {
+            let cpi_accounts = TransferChecked {
+                from: vault.clone(),
+                to: to.clone(),
+                authority: basket.clone(),
+                mint: mint.clone(),
+            };
+
+            let cpi_context = CpiContext::new_with_signer(token_program.clone(), cpi_accounts, signer_seeds);
+
+            transfer_with_fee(cpi_context, amount, decimals)
+        };
+
+        let mut charged = 0;
+        if let Some((treasury, fee)) = treasury {
+            self.init_ata(token_program, treasury, fee.fee_recipient, mint)?;
+            charged = fee.config.fee_for(amount);
+            if charged > 0 {
+                send(treasury, charged)?;
+            }
+        }
+
+        send(to, amount - charged)?;
+
+        // Token-2022 won't close the vault while it holds withheld fees
+        harvest_withheld_fees(token_program, mint, vault)?;
+
+        let cpi_accounts = CloseAccount {
+            account: vault.clone(),
+            destination: maker.clone(),
+            authority: basket.clone(),
+        };
+
+        let cpi_context = CpiContext::new_with_signer(token_program.clone(), cpi_accounts, signer_seeds);
+
+        close_account(cpi_context)?;
+
+        Ok(charged)
+    }
+}
@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::EscrowError, state::{EscrowConfig, MAX_FEE_BPS}};
+
+// Creates the global config; the signer becomes its admin.
+// Only the program's upgrade authority can run it, so nobody can front-run
+// the deployer and claim the fee recipient.
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ EscrowError::Unauthorized)]
+    pub program: Program<'info, crate::program::AnchorEscrow>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ EscrowError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"config"],
+        bump,
+        space = 8 + EscrowConfig::INIT_SPACE,
+    )]
+    pub config: Account<'info, EscrowConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeConfig<'info> {
+    pub fn init_config(&mut self, fee_bps: u16, fee_recipient: Pubkey, bumps: &InitializeConfigBumps) -> Result<()> {
+        require!(fee_bps <= MAX_FEE_BPS, EscrowError::InvalidFeeBps);
+
+        self.config.set_inner(EscrowConfig {
+            admin: self.admin.key(),
+            fee_recipient,
+            fee_bps,
+            bump: bumps.config,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        has_one = admin @ EscrowError::Unauthorized,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, EscrowConfig>,
+}
+
+impl<'info> UpdateConfig<'info> {
+    pub fn update_fee(&mut self, fee_bps: u16, fee_recipient: Pubkey) -> Result<()> {
+        require!(fee_bps <= MAX_FEE_BPS, EscrowError::InvalidFeeBps);
+
+        self.config.fee_bps = fee_bps;
+        self.config.fee_recipient = fee_recipient;
+
+        Ok(())
+    }
+
+    pub fn transfer_admin(&mut self, new_admin: Pubkey) -> Result<()> {
+        self.config.admin = new_admin;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,383 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked, CloseAccount, close_account}};
+
+use super::transfer_fee::{harvest_withheld_fees, transfer_with_fee};
+use crate::{
+    errors::EscrowError,
+    events::{CounterAccepted, CounterMade, CounterWithdrawn},
+    state::{CounterOffer, Escrow, EscrowConfig},
+};
+
+// A taker proposes a different amount of mint B for an escrow, locking it in a
+// vault owned by the counter PDA (one per escrow and taker)
+#[derive(Accounts)]
+pub struct MakeCounter<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program,
+    )]
+    pub taker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        has_one = mint_b,
+        seeds = [b"escrow", escrow.maker.as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+    #[account(
+        init,
+        payer = taker,
+        seeds = [b"counter", escrow.key().as_ref(), taker.key().as_ref()],
+        bump,
+        space = 8 + CounterOffer::INIT_SPACE,
+    )]
+    pub counter: Box<Account<'info, CounterOffer>>,
+    #[account(
+        init,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = counter,
+        associated_token::token_program = token_program,
+    )]
+    pub counter_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MakeCounter<'info> {
+    pub fn init_counter(&mut self, amount: u64, bumps: &MakeCounterBumps) -> Result<()> {
+        let clock = Clock::get()?;
+
+        require!(amount > 0, EscrowError::InvalidCounter);
+        require!(!self.escrow.is_expired(clock.unix_timestamp), EscrowError::EscrowExpired);
+        if let Some(allowed) = self.escrow.taker {
+            require_keys_eq!(self.taker.key(), allowed, EscrowError::TakerNotAllowed);
+        }
+
+        self.counter.set_inner(CounterOffer {
+            escrow: self.escrow.key(),
+            taker: self.taker.key(),
+            amount: 0,
+            bump: bumps.counter,
+            created_at: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    pub fn deposit(&mut self, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = TransferChecked {
+            from: self.taker_ata_b.to_account_info(),
+            to: self.counter_vault.to_account_info(),
+            authority: self.taker.to_account_info(),
+            mint: self.mint_b.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        transfer_with_fee(cpi_ctx, amount, self.mint_b.decimals)?;
+
+        // Record what actually arrived; a transfer-fee mint withholds part of it
+        self.counter_vault.reload()?;
+        self.counter.amount = self.counter_vault.amount;
+
+        emit!(CounterMade {
+            counter: self.counter.key(),
+            escrow: self.escrow.key(),
+            taker: self.taker.key(),
+            amount: self.counter.amount,
+        });
+
+        Ok(())
+    }
+}
+
+// The maker takes the counter: its vault goes to the maker, the escrow vault
+// (less the protocol fee) to the taker, and both escrow and counter close
+#[derive(Accounts)]
+pub struct AcceptCounter<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+    #[account(mut)]
+    pub taker: SystemAccount<'info>,
+    #[account(mut)]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut)]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init_if_needed,
+        payer = maker,
+        associated_token::mint = mint_b,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program,
+    )]
+    pub maker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = maker,
+        associated_token::mint = mint_a,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program,
+    )]
+    pub taker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        has_one = mint_a,
+        has_one = mint_b,
+        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        close = taker,
+        has_one = escrow,
+        has_one = taker,
+        seeds = [b"counter", escrow.key().as_ref(), taker.key().as_ref()],
+        bump = counter.bump,
+    )]
+    pub counter: Box<Account<'info, CounterOffer>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = counter,
+        associated_token::token_program = token_program,
+    )]
+    pub counter_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    // Required: until `initialize_config` runs, accepts fail with AccountNotInitialized
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, EscrowConfig>>,
+    /// CHECK: only used as the treasury ATA's authority; must match the config
+    #[account(address = config.fee_recipient)]
+    pub fee_recipient: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = maker,
+        associated_token::mint = mint_a,
+        associated_token::authority = fee_recipient,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+//Transfer the counter vault to maker and close it
+//Transfer the protocol fee from vault to treasury, the rest to taker
+//Close vault account
+impl<'info> AcceptCounter<'info> {
+    /// Verifies the counter still holds at least the `amount` the maker saw,
+    /// so a taker can't swap in a smaller counter in front of the accept
+    pub fn verify_amount(&self, amount: u64) -> Result<()> {
+        require_gte!(self.counter_vault.amount, amount, EscrowError::CounterBelowAccepted);
+
+        Ok(())
+    }
+
+    pub fn release_counter_vault(&mut self) -> Result<()> {
+        let escrow_key = self.escrow.key();
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"counter",
+            escrow_key.as_ref(),
+            self.taker.key.as_ref(),
+            &[self.counter.bump]
+        ]];
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = TransferChecked {
+            from: self.counter_vault.to_account_info(),
+            to: self.maker_ata_b.to_account_info(),
+            authority: self.counter.to_account_info(),
+            mint: self.mint_b.to_account_info(),
+        };
+
+        let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, &signer_seeds);
+
+        transfer_with_fee(cpi_context, self.counter_vault.amount, self.mint_b.decimals)?;
+
+        // Token-2022 won't close the counter vault while it holds withheld fees
+        harvest_withheld_fees(&self.token_program.to_account_info(), &self.mint_b.to_account_info(), &self.counter_vault.to_account_info())?;
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = CloseAccount {
+            account: self.counter_vault.to_account_info(),
+            destination: self.taker.to_account_info(),
+            authority: self.counter.to_account_info(),
+        };
+
+        let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, &signer_seeds);
+
+        close_account(cpi_context)
+    }
+
+    pub fn withdraw_and_close_vault(&mut self) -> Result<()> {
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"escrow",
+            self.maker.key.as_ref(),
+            &self.escrow.seed.to_le_bytes()[..],
+            &[self.escrow.bump]
+        ]];
+
+        let fee = self.config.fee_for(self.vault.amount);
+        let payout = self.vault.amount - fee;
+
+        if fee > 0 {
+            let cpi_program = self.token_program.to_account_info();
+
+            let cpi_accounts = TransferChecked {
+                from: self.vault.to_account_info(),
+                to: self.treasury_ata.to_account_info(),
+                authority: self.escrow.to_account_info(),
+                mint: self.mint_a.to_account_info(),
+            };
+
+            let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, &signer_seeds);
+
+            transfer_with_fee(cpi_context, fee, self.mint_a.decimals)?;
+        }
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            to: self.taker_ata_a.to_account_info(),
+            authority: self.escrow.to_account_info(),
+            mint: self.mint_a.to_account_info(),
+        };
+
+        let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, &signer_seeds);
+
+        transfer_with_fee(cpi_context, payout, self.mint_a.decimals)?;
+
+        // Token-2022 won't close the vault while it holds withheld fees
+        harvest_withheld_fees(&self.token_program.to_account_info(), &self.mint_a.to_account_info(), &self.vault.to_account_info())?;
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = CloseAccount {
+            account: self.vault.to_account_info(),
+            destination: self.maker.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, &signer_seeds);
+
+        close_account(cpi_context)?;
+
+        emit!(CounterAccepted {
+            counter: self.counter.key(),
+            escrow: self.escrow.key(),
+            maker: self.maker.key(),
+            taker: self.taker.key(),
+            deposit: payout,
+            receive: self.counter_vault.amount,
+            fee,
+        });
+
+        Ok(())
+    }
+}
+
+// The taker takes back a counter the maker hasn't accepted. Works whether or
+// not the escrow is still open.
+#[derive(Accounts)]
+pub struct WithdrawCounter<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+    #[account(mut)]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program,
+    )]
+    pub taker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        close = taker,
+        has_one = taker,
+        seeds = [b"counter", counter.escrow.as_ref(), taker.key().as_ref()],
+        bump = counter.bump,
+    )]
+    pub counter: Box<Account<'info, CounterOffer>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = counter,
+        associated_token::token_program = token_program,
+    )]
+    pub counter_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> WithdrawCounter<'info> {
+    pub fn withdraw_and_close_vault(&mut self) -> Result<()> {
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"counter",
+            self.counter.escrow.as_ref(),
+            self.taker.key.as_ref(),
+            &[self.counter.bump]
+        ]];
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = TransferChecked {
+            from: self.counter_vault.to_account_info(),
+            to: self.taker_ata_b.to_account_info(),
+            authority: self.counter.to_account_info(),
+            mint: self.mint_b.to_account_info(),
+        };
+
+        let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, &signer_seeds);
+
+        transfer_with_fee(cpi_context, self.counter_vault.amount, self.mint_b.decimals)?;
+
+        // Token-2022 won't close the counter vault while it holds withheld fees
+        harvest_withheld_fees(&self.token_program.to_account_info(), &self.mint_b.to_account_info(), &self.counter_vault.to_account_info())?;
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = CloseAccount {
+            account: self.counter_vault.to_account_info(),
+            destination: self.taker.to_account_info(),
+            authority: self.counter.to_account_info(),
+        };
+
+        let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, &signer_seeds);
+
+        close_account(cpi_context)?;
+
+        emit!(CounterWithdrawn {
+            counter: self.counter.key(),
+            escrow: self.counter.escrow,
+            taker: self.taker.key(),
+            amount: self.counter_vault.amount,
+        });
+
+        Ok(())
+    }
+}
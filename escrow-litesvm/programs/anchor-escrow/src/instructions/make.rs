@@ -1,7 +1,9 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{associated_token::AssociatedToken, token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked, transfer_checked}};
+use anchor_spl::{associated_token::AssociatedToken, token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked}};
 
-use crate::state::Escrow;
+use super::transfer_fee::transfer_with_fee;
+
+use crate::{errors::EscrowError, events::EscrowMade, state::{DutchAuction, Escrow}};
 
 #[derive(Accounts)]
 #[instruction(seed: u64)]
@@ -14,6 +16,7 @@ pub struct Make<'info> {
         mut,
         associated_token::mint = mint_a,
         associated_token::authority = maker,
+        associated_token::token_program = token_program,
     )]
     pub maker_ata_a: InterfaceAccount<'info, TokenAccount>,
     #[account(
@@ -29,6 +32,7 @@ pub struct Make<'info> {
         payer = maker,
         associated_token::mint = mint_a,
         associated_token::authority = escrow,
+        associated_token::token_program = token_program,
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
     pub associated_token_program: Program<'info, AssociatedToken>,
@@ -37,22 +41,58 @@ pub struct Make<'info> {
 }
 
 impl<'info> Make<'info> {
-    pub fn init_escrow(&mut self, seed: u64, receive: u64, bumps: &MakeBumps) -> Result<()> {
+    pub fn init_escrow(
+        &mut self,
+        seed: u64,
+        receive: u64,
+        taker: Option<Pubkey>,
+        unlock_at: i64,
+        expires_at: Option<i64>,
+        bumps: &MakeBumps,
+    ) -> Result<()> {
         let clock = Clock::get()?;
 
+        // An expiry must leave takers a non-empty window in the future
+        if let Some(expires_at) = expires_at {
+            require!(
+                expires_at > clock.unix_timestamp && expires_at > unlock_at,
+                EscrowError::InvalidTimeWindow
+            );
+        }
+
         self.escrow.set_inner(Escrow {
             seed,
             maker: self.maker.key(),
             mint_a: self.mint_a.key(),
             mint_b: self.mint_b.key(),
             receive,
+            deposit: 0,
             bump: bumps.escrow,
             created_at: clock.unix_timestamp,
+            taker,
+            unlock_at,
+            expires_at,
+            auction: None,
         });
 
         Ok(())
     }
 
+    /// Turns the offer into a Dutch auction starting at `receive`
+    pub fn set_auction(&mut self, auction: Option<DutchAuction>) -> Result<()> {
+        // The price can only fall, and must reach its floor in the future
+        if let Some(auction) = auction {
+            require!(
+                auction.floor <= self.escrow.receive && auction.end_at > self.escrow.created_at,
+                EscrowError::InvalidAuction
+            );
+        }
+
+        self.escrow.auction = auction;
+
+        Ok(())
+    }
+
     pub fn deposit(&mut self, deposit: u64) -> Result<()> {
         let cpi_program = self.token_program.to_account_info();
 
@@ -65,7 +105,25 @@ impl<'info> Make<'info> {
 
         let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
 
-        transfer_checked(cpi_ctx, deposit, self.mint_a.decimals)?;
+        transfer_with_fee(cpi_ctx, deposit, self.mint_a.decimals)?;
+
+        // Record what actually arrived; a transfer-fee mint withholds part of it
+        self.vault.reload()?;
+        self.escrow.deposit = self.vault.amount;
+
+        emit!(EscrowMade {
+            escrow: self.escrow.key(),
+            maker: self.maker.key(),
+            mint_a: self.mint_a.key(),
+            mint_b: self.mint_b.key(),
+            seed: self.escrow.seed,
+            deposit: self.escrow.deposit,
+            receive: self.escrow.receive,
+            taker: self.escrow.taker,
+            unlock_at: self.escrow.unlock_at,
+            expires_at: self.escrow.expires_at,
+            auction: self.escrow.auction,
+        });
 
         Ok(())
     }
@@ -0,0 +1,78 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token::Token, token_2022::Token2022};
+
+use super::basket::{leg_accounts, LegCpi, ACCOUNTS_PER_LEG};
+use crate::{errors::EscrowError, events::BasketMade, state::{BasketEscrow, BasketLeg}};
+
+// Remaining accounts, one triple per deposit leg in order:
+// [mint (read), maker_ata (mut), vault (mut, the basket's ATA, created here)]
+// Each mint may belong to either token program.
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct MakeBasket<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+    #[account(
+        init,
+        payer = maker,
+        seeds = [b"basket", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + BasketEscrow::INIT_SPACE,
+    )]
+    pub basket: Account<'info, BasketEscrow>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Program<'info, Token>,
+    pub token_2022_program: Program<'info, Token2022>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MakeBasket<'info> {
+    pub fn init_basket(
+        &mut self,
+        seed: u64,
+        deposits: Vec<BasketLeg>,
+        requests: Vec<BasketLeg>,
+        bumps: &MakeBasketBumps,
+    ) -> Result<()> {
+        require!(
+            BasketEscrow::are_valid_legs(&deposits) && BasketEscrow::are_valid_legs(&requests),
+            EscrowError::InvalidBasket
+        );
+
+        self.basket.set_inner(BasketEscrow {
+            seed,
+            maker: self.maker.key(),
+            bump: bumps.basket,
+            created_at: Clock::get()?.unix_timestamp,
+            deposits,
+            requests,
+        });
+
+        Ok(())
+    }
+
+    pub fn deposit(&mut self, remaining: &[AccountInfo<'info>]) -> Result<()> {
+        let cpi = LegCpi {
+            payer: self.maker.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            token_2022_program: self.token_2022_program.to_account_info(),
+            associated_token_program: self.associated_token_program.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+        };
+        let basket = self.basket.to_account_info();
+
+        for (leg, accounts) in self.basket.deposits.iter().zip(leg_accounts(remaining, self.basket.deposits.len(), ACCOUNTS_PER_LEG)?) {
+            cpi.deposit(leg, accounts, &basket)?;
+        }
+
+        emit!(BasketMade {
+            basket: self.basket.key(),
+            maker: self.maker.key(),
+            seed: self.basket.seed,
+            deposits: self.basket.deposits.clone(),
+            requests: self.basket.requests.clone(),
+        });
+
+        Ok(())
+    }
+}
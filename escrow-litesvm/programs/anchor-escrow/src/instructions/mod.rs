@@ -1,7 +1,19 @@
+mod basket;
+mod transfer_fee;
+pub mod config;
+pub mod counter;
 pub mod make;
+pub mod make_basket;
 pub mod refund;
+pub mod refund_basket;
 pub mod take;
+pub mod take_basket;
 
+pub use config::*;
+pub use counter::*;
 pub use make::*;
+pub use make_basket::*;
 pub use refund::*;
-pub use take::*;
\ No newline at end of file
+pub use refund_basket::*;
+pub use take::*;
+pub use take_basket::*;
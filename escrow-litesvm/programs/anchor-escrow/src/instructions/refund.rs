@@ -1,17 +1,20 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked, CloseAccount, close_account};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked, CloseAccount, close_account};
 
-use crate::state::Escrow;
+use super::transfer_fee::{harvest_withheld_fees, transfer_with_fee};
+use crate::{errors::EscrowError, events::EscrowRefunded, state::Escrow};
 
 #[derive(Accounts)]
 pub struct Refund<'info> {
     #[account(mut)]
     maker: Signer<'info>,
+    #[account(mut)]
     mint_a: InterfaceAccount<'info, Mint>,
     #[account(
         mut,
         associated_token::mint = mint_a,
         associated_token::authority = maker,
+        associated_token::token_program = token_program,
     )]
     maker_ata_a: InterfaceAccount<'info, TokenAccount>,
     #[account(
@@ -27,6 +30,7 @@ pub struct Refund<'info> {
         mut,
         associated_token::mint = mint_a,
         associated_token::authority = escrow,
+        associated_token::token_program = token_program,
     )]
     vault: InterfaceAccount<'info, TokenAccount>,
     token_program: Interface<'info, TokenInterface>,
@@ -34,6 +38,18 @@ pub struct Refund<'info> {
 }
 
 impl<'info> Refund<'info> {
+    /// Verifies the escrow is not inside its committed take window
+    pub fn verify_refundable(&self) -> Result<()> {
+        let clock = Clock::get()?;
+
+        require!(
+            self.escrow.is_refundable(clock.unix_timestamp),
+            EscrowError::EscrowActive
+        );
+
+        Ok(())
+    }
+
     pub fn refund_and_close_vault(&mut self) -> Result<()> {
         let signer_seeds: [&[&[u8]]; 1] = [&[
             b"escrow",
@@ -53,7 +69,10 @@ impl<'info> Refund<'info> {
 
         let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, &signer_seeds);
 
-        transfer_checked(cpi_context, self.vault.amount, self.mint_a.decimals)?;
+        transfer_with_fee(cpi_context, self.vault.amount, self.mint_a.decimals)?;
+
+        // Token-2022 won't close the vault while it holds withheld fees
+        harvest_withheld_fees(&self.token_program.to_account_info(), &self.mint_a.to_account_info(), &self.vault.to_account_info())?;
 
         let cpi_program = self.token_program.to_account_info();
 
@@ -66,7 +85,14 @@ impl<'info> Refund<'info> {
         let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, &signer_seeds);
 
         close_account(cpi_context)?;
-        
+
+        emit!(EscrowRefunded {
+            escrow: self.escrow.key(),
+            maker: self.maker.key(),
+            mint_a: self.mint_a.key(),
+            amount: self.vault.amount,
+        });
+
         Ok(())
     }
 }
\ No newline at end of file
@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token::Token, token_2022::Token2022};
+
+use super::basket::{leg_accounts, LegCpi, ACCOUNTS_PER_LEG};
+use crate::{events::BasketRefunded, state::BasketEscrow};
+
+// Remaining accounts, one triple per deposit leg in order:
+// [mint (mut), vault (mut), maker_ata (mut, created if needed)]
+// Each mint may belong to either token program.
+#[derive(Accounts)]
+pub struct RefundBasket<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        seeds = [b"basket", maker.key().as_ref(), basket.seed.to_le_bytes().as_ref()],
+        bump = basket.bump,
+    )]
+    pub basket: Box<Account<'info, BasketEscrow>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Program<'info, Token>,
+    pub token_2022_program: Program<'info, Token2022>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RefundBasket<'info> {
+    pub fn refund_and_close_vaults(&mut self, remaining: &[AccountInfo<'info>]) -> Result<()> {
+        let cpi = LegCpi {
+            payer: self.maker.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            token_2022_program: self.token_2022_program.to_account_info(),
+            associated_token_program: self.associated_token_program.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+        };
+        let basket = self.basket.to_account_info();
+        let maker = self.maker.to_account_info();
+
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"basket",
+            self.maker.key.as_ref(),
+            &self.basket.seed.to_le_bytes()[..],
+            &[self.basket.bump]
+        ]];
+
+        for (leg, accounts) in self.basket.deposits.iter().zip(leg_accounts(remaining, self.basket.deposits.len(), ACCOUNTS_PER_LEG)?) {
+            cpi.release(leg, accounts, &basket, &maker, &maker, &signer_seeds, None)?;
+        }
+
+        emit!(BasketRefunded {
+            basket: self.basket.key(),
+            maker: self.maker.key(),
+        });
+
+        Ok(())
+    }
+}
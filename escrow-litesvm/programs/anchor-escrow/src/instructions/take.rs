@@ -1,13 +1,8 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{associated_token::AssociatedToken, token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked, transfer_checked, CloseAccount, close_account}};
+use anchor_spl::{associated_token::AssociatedToken, token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked, CloseAccount, close_account}};
 
-use crate::state::{Escrow, ESCROW_LOCK_DURATION};
-
-#[error_code]
-pub enum TakeError {
-    #[msg("Escrow is still locked. Must wait 5 days after creation.")]
-    EscrowStillLocked,
-}
+use super::transfer_fee::{gross_up, harvest_withheld_fees, transfer_with_fee};
+use crate::{errors::EscrowError, events::EscrowTaken, state::{Escrow, EscrowConfig}};
 
 //Create context
 #[derive(Accounts)]
@@ -16,6 +11,7 @@ pub struct Take<'info> {
     pub taker: Signer<'info>,
     #[account(mut)]
     pub maker: SystemAccount<'info>,
+    #[account(mut)]
     pub mint_a: Box<InterfaceAccount<'info, Mint>>,
     pub mint_b: Box<InterfaceAccount<'info, Mint>>,
     #[account(
@@ -23,12 +19,14 @@ pub struct Take<'info> {
         payer = taker,
         associated_token::mint = mint_a,
         associated_token::authority = taker,
+        associated_token::token_program = token_program,
     )]
     pub taker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
         mut,
         associated_token::mint = mint_b,
         associated_token::authority = taker,
+        associated_token::token_program = token_program,
     )]
     pub taker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
@@ -36,6 +34,7 @@ pub struct Take<'info> {
         payer = taker,
         associated_token::mint = mint_b,
         associated_token::authority = maker,
+        associated_token::token_program = token_program,
     )]
     pub maker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
@@ -52,32 +51,73 @@ pub struct Take<'info> {
         mut,
         associated_token::mint = mint_a,
         associated_token::authority = escrow,
+        associated_token::token_program = token_program,
     )]
     pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    // Required: until `initialize_config` runs, takes fail with AccountNotInitialized
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, EscrowConfig>>,
+    /// CHECK: only used as the treasury ATA's authority; must match the config
+    #[account(address = config.fee_recipient)]
+    pub fee_recipient: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_a,
+        associated_token::authority = fee_recipient,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_ata: Box<InterfaceAccount<'info, TokenAccount>>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
 //Deposit tokens from taker to maker
-//Transfer tokens from vault to taker
+//Transfer the protocol fee from vault to treasury, the rest to taker
 //Close vault account
 impl<'info> Take<'info> {
-    /// Verifies that at least 5 days have passed since escrow creation
-    pub fn verify_unlock_time(&self) -> Result<()> {
+    /// Verifies the escrow is unlocked and has not expired
+    pub fn verify_time_window(&self) -> Result<()> {
         let clock = Clock::get()?;
         let current_time = clock.unix_timestamp;
-        let unlock_time = self.escrow.created_at + ESCROW_LOCK_DURATION;
 
         require!(
-            current_time >= unlock_time,
-            TakeError::EscrowStillLocked
+            self.escrow.is_unlocked(current_time),
+            EscrowError::EscrowStillLocked
+        );
+        require!(
+            !self.escrow.is_expired(current_time),
+            EscrowError::EscrowExpired
         );
 
         Ok(())
     }
 
-    pub fn deposit(&mut self) -> Result<()> {
+    /// Verifies the taker is the one the maker reserved the escrow for, if any
+    pub fn verify_taker(&self) -> Result<()> {
+        if let Some(allowed) = self.escrow.taker {
+            require_keys_eq!(self.taker.key(), allowed, EscrowError::TakerNotAllowed);
+        }
+
+        Ok(())
+    }
+
+    /// Current price from the clock; fails if it exceeds the taker's `max_price`
+    pub fn current_price(&self, max_price: Option<u64>) -> Result<u64> {
+        let price = self.escrow.price_at(Clock::get()?.unix_timestamp);
+
+        if let Some(max_price) = max_price {
+            require_gte!(max_price, price, EscrowError::PriceAboveMax);
+        }
+
+        Ok(price)
+    }
+
+    pub fn deposit(&mut self, price: u64) -> Result<()> {
         let cpi_program = self.token_program.to_account_info();
 
         let cpi_accounts = TransferChecked {
@@ -89,10 +129,13 @@ impl<'info> Take<'info> {
 
         let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
 
-        transfer_checked(cpi_ctx, self.escrow.receive, self.mint_b.decimals)
+        // Gross up so the maker receives the full amount on transfer-fee mints
+        let amount = gross_up(&self.mint_b.to_account_info(), price)?;
+
+        transfer_with_fee(cpi_ctx, amount, self.mint_b.decimals)
     }
 
-    pub fn withdraw_and_close_vault(&mut self) -> Result<()> {
+    pub fn withdraw_and_close_vault(&mut self, price: u64) -> Result<()> {
         let signer_seeds: [&[&[u8]]; 1] = [&[
             b"escrow",
             self.maker.key.as_ref(),
@@ -100,6 +143,24 @@ impl<'info> Take<'info> {
             &[self.escrow.bump]
         ]];
 
+        let fee = self.config.fee_for(self.vault.amount);
+        let payout = self.vault.amount - fee;
+
+        if fee > 0 {
+            let cpi_program = self.token_program.to_account_info();
+
+            let cpi_accounts = TransferChecked {
+                from: self.vault.to_account_info(),
+                to: self.treasury_ata.to_account_info(),
+                authority: self.escrow.to_account_info(),
+                mint: self.mint_a.to_account_info(),
+            };
+
+            let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, &signer_seeds);
+
+            transfer_with_fee(cpi_context, fee, self.mint_a.decimals)?;
+        }
+
         let cpi_program = self.token_program.to_account_info();
 
         let cpi_accounts = TransferChecked {
@@ -111,7 +172,10 @@ impl<'info> Take<'info> {
 
         let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, &signer_seeds);
 
-        transfer_checked(cpi_context, self.vault.amount, self.mint_a.decimals)?;
+        transfer_with_fee(cpi_context, payout, self.mint_a.decimals)?;
+
+        // Token-2022 won't close the vault while it holds withheld fees
+        harvest_withheld_fees(&self.token_program.to_account_info(), &self.mint_a.to_account_info(), &self.vault.to_account_info())?;
 
         let cpi_program = self.token_program.to_account_info();
 
@@ -123,6 +187,19 @@ impl<'info> Take<'info> {
 
         let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, &signer_seeds);
 
-        close_account(cpi_context)
+        close_account(cpi_context)?;
+
+        emit!(EscrowTaken {
+            escrow: self.escrow.key(),
+            maker: self.maker.key(),
+            taker: self.taker.key(),
+            mint_a: self.mint_a.key(),
+            mint_b: self.mint_b.key(),
+            deposit: payout,
+            receive: price,
+            fee,
+        });
+
+        Ok(())
     }
 }
\ No newline at end of file
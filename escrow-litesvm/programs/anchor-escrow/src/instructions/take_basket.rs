@@ -0,0 +1,88 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token::Token, token_2022::Token2022};
+
+use super::basket::{leg_accounts, LegCpi, LegFee, ACCOUNTS_PER_FEE_LEG, ACCOUNTS_PER_LEG};
+use crate::{events::BasketTaken, state::{BasketEscrow, EscrowConfig}};
+
+// Remaining accounts, one triple per request leg, then four per deposit leg:
+// requests: [mint (read), taker_ata (mut), maker_ata (mut, created if needed)]
+// deposits: [mint (mut), vault (mut), taker_ata (mut, created if needed),
+//            fee_recipient_ata (mut, created if needed)]
+// Each mint may belong to either token program.
+#[derive(Accounts)]
+pub struct TakeBasket<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        seeds = [b"basket", maker.key().as_ref(), basket.seed.to_le_bytes().as_ref()],
+        bump = basket.bump,
+    )]
+    pub basket: Box<Account<'info, BasketEscrow>>,
+    // Required: until `initialize_config` runs, basket takes fail with AccountNotInitialized
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, EscrowConfig>>,
+    /// CHECK: only used as the treasury ATAs' authority; must match the config
+    #[account(address = config.fee_recipient)]
+    pub fee_recipient: UncheckedAccount<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Program<'info, Token>,
+    pub token_2022_program: Program<'info, Token2022>,
+    pub system_program: Program<'info, System>,
+}
+
+//Pay every requested mint from taker to maker
+//Transfer the protocol fee from each vault to treasury, the rest to taker
+//Close every vault
+impl<'info> TakeBasket<'info> {
+    pub fn settle(&mut self, remaining: &[AccountInfo<'info>]) -> Result<()> {
+        let cpi = LegCpi {
+            payer: self.taker.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            token_2022_program: self.token_2022_program.to_account_info(),
+            associated_token_program: self.associated_token_program.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+        };
+        let basket = self.basket.to_account_info();
+        let taker = self.taker.to_account_info();
+        let maker = self.maker.to_account_info();
+        let fee_recipient = self.fee_recipient.to_account_info();
+
+        let (request_accounts, deposit_accounts) =
+            remaining.split_at(remaining.len().min(self.basket.requests.len() * ACCOUNTS_PER_LEG));
+
+        for (leg, accounts) in self.basket.requests.iter().zip(leg_accounts(request_accounts, self.basket.requests.len(), ACCOUNTS_PER_LEG)?) {
+            cpi.pay(leg, accounts, &maker)?;
+        }
+
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"basket",
+            self.maker.key.as_ref(),
+            &self.basket.seed.to_le_bytes()[..],
+            &[self.basket.bump]
+        ]];
+
+        let mut fees = Vec::with_capacity(self.basket.deposits.len());
+        let deposit_legs = leg_accounts(deposit_accounts, self.basket.deposits.len(), ACCOUNTS_PER_FEE_LEG)?;
+        for (leg, accounts) in self.basket.deposits.iter().zip(deposit_legs) {
+            let fee = LegFee { config: &self.config, fee_recipient: &fee_recipient };
+            fees.push(cpi.release(leg, accounts, &basket, &taker, &maker, &signer_seeds, Some(fee))?);
+        }
+
+        emit!(BasketTaken {
+            basket: self.basket.key(),
+            maker: self.maker.key(),
+            taker: self.taker.key(),
+            fees,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,127 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token_2022::spl_token_2022::{
+        self,
+        extension::{
+            transfer_fee::{TransferFeeAmount, TransferFeeConfig},
+            BaseStateWithExtensions, StateWithExtensions,
+        },
+        state::{Account as TokenAccountState, Mint},
+    },
+    token_2022_extensions::transfer_fee::{
+        harvest_withheld_tokens_to_mint, transfer_checked_with_fee, HarvestWithheldTokensToMint,
+        TransferCheckedWithFee,
+    },
+    token_interface::{transfer_checked, TransferChecked},
+};
+
+use crate::errors::EscrowError;
+
+// Token-2022 mints with the TransferFee extension withhold a fee from the
+// recipient's side of every transfer. These helpers quote that fee for the
+// current epoch, so the program can gross up payments and assert the fee it
+// expects when transferring.
+
+fn fee_config(mint: &AccountInfo) -> Result<Option<TransferFeeConfig>> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(None);
+    }
+
+    let data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+
+    Ok(mint.get_extension::<TransferFeeConfig>().ok().copied())
+}
+
+/// Fee `mint` withholds on a transfer of `amount`; 0 without the extension.
+pub fn transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    let Some(config) = fee_config(mint)? else {
+        return Ok(0);
+    };
+
+    config
+        .calculate_epoch_fee(Clock::get()?.epoch, amount)
+        .ok_or(error!(EscrowError::InvalidTransferFee))
+}
+
+/// Amount to send so that `net` arrives after the transfer fee.
+pub fn gross_up(mint: &AccountInfo, net: u64) -> Result<u64> {
+    let Some(config) = fee_config(mint)? else {
+        return Ok(net);
+    };
+
+    let fee = config
+        .get_epoch_fee(Clock::get()?.epoch)
+        .calculate_inverse_fee(net)
+        .ok_or(error!(EscrowError::InvalidTransferFee))?;
+    let gross = net.checked_add(fee).ok_or(error!(EscrowError::InvalidTransferFee))?;
+
+    // Rounding in the inverse can leave the recipient a unit short
+    require_gte!(gross - transfer_fee(mint, gross)?, net, EscrowError::InvalidTransferFee);
+
+    Ok(gross)
+}
+
+/// `transfer_checked` that, for transfer-fee mints, goes through
+/// `transfer_checked_with_fee` with the quoted fee, so the transfer fails
+/// rather than withhold more than expected.
+pub fn transfer_with_fee<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
+    amount: u64,
+    decimals: u8,
+) -> Result<()> {
+    if fee_config(&ctx.accounts.mint)?.is_none() {
+        return transfer_checked(ctx, amount, decimals);
+    }
+
+    let fee = transfer_fee(&ctx.accounts.mint, amount)?;
+    let cpi_accounts = TransferCheckedWithFee {
+        token_program_id: ctx.program.clone(),
+        source: ctx.accounts.from,
+        mint: ctx.accounts.mint,
+        destination: ctx.accounts.to,
+        authority: ctx.accounts.authority,
+    };
+
+    transfer_checked_with_fee(
+        CpiContext::new_with_signer(ctx.program, cpi_accounts, ctx.signer_seeds),
+        amount,
+        decimals,
+        fee,
+    )
+}
+
+/// Fees withheld in token account `account`; 0 without the extension.
+fn withheld_fees(account: &AccountInfo) -> Result<u64> {
+    if *account.owner != spl_token_2022::ID {
+        return Ok(0);
+    }
+
+    let data = account.try_borrow_data()?;
+    let account = StateWithExtensions::<TokenAccountState>::unpack(&data)?;
+
+    Ok(account
+        .get_extension::<TransferFeeAmount>()
+        .map_or(0, |fees| fees.withheld_amount.into()))
+}
+
+/// Moves the fees withheld in `account` to `mint`, which must be writable.
+/// Token-2022 won't close an account holding withheld fees, so vaults call
+/// this before `close_account`. Harvesting is permissionless; no-op when
+/// nothing is withheld.
+pub fn harvest_withheld_fees<'info>(
+    token_program: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    account: &AccountInfo<'info>,
+) -> Result<()> {
+    if withheld_fees(account)? == 0 {
+        return Ok(());
+    }
+
+    let cpi_accounts = HarvestWithheldTokensToMint {
+        token_program_id: token_program.clone(),
+        mint: mint.clone(),
+    };
+
+    harvest_withheld_tokens_to_mint(CpiContext::new(token_program.clone(), cpi_accounts), vec![account.clone()])
+}
@@ -4,10 +4,16 @@
 use anchor_lang::prelude::*;
 
 mod state;
+mod errors;
 mod instructions;
 mod tests;
+pub mod events;
+
+#[cfg(any(test, feature = "client"))]
+pub mod client;
 
 use instructions::*;
+use state::{BasketLeg, DutchAuction};
 
 declare_id!("FircrADQ2wgGuvpm8qneNCfKM7o5zoHTWnDQxngpTQ3J");
 
@@ -15,18 +21,77 @@ declare_id!("FircrADQ2wgGuvpm8qneNCfKM7o5zoHTWnDQxngpTQ3J");
 pub mod anchor_escrow {
     use super::*;
 
-    pub fn make(ctx: Context<Make>, seed: u64, deposit: u64, receive: u64) -> Result<()> {
-        ctx.accounts.init_escrow(seed, receive, &ctx.bumps)?;
+    #[allow(clippy::too_many_arguments)]
+    pub fn make(
+        ctx: Context<Make>,
+        seed: u64,
+        deposit: u64,
+        receive: u64,
+        taker: Option<Pubkey>,
+        unlock_at: i64,
+        expires_at: Option<i64>,
+        auction: Option<DutchAuction>,
+    ) -> Result<()> {
+        ctx.accounts.init_escrow(seed, receive, taker, unlock_at, expires_at, &ctx.bumps)?;
+        ctx.accounts.set_auction(auction)?;
         ctx.accounts.deposit(deposit)
     }
 
+    pub fn initialize_config(ctx: Context<InitializeConfig>, fee_bps: u16, fee_recipient: Pubkey) -> Result<()> {
+        ctx.accounts.init_config(fee_bps, fee_recipient, &ctx.bumps)
+    }
+
+    pub fn update_config(ctx: Context<UpdateConfig>, fee_bps: u16, fee_recipient: Pubkey) -> Result<()> {
+        ctx.accounts.update_fee(fee_bps, fee_recipient)
+    }
+
+    pub fn transfer_admin(ctx: Context<UpdateConfig>, new_admin: Pubkey) -> Result<()> {
+        ctx.accounts.transfer_admin(new_admin)
+    }
+
     pub fn refund(ctx: Context<Refund>) -> Result<()> {
+        ctx.accounts.verify_refundable()?;
         ctx.accounts.refund_and_close_vault()
     }
 
-    pub fn take(ctx: Context<Take>) -> Result<()> {
-        ctx.accounts.verify_unlock_time()?;
-        ctx.accounts.deposit()?;
+    pub fn take(ctx: Context<Take>, max_price: Option<u64>) -> Result<()> {
+        ctx.accounts.verify_time_window()?;
+        ctx.accounts.verify_taker()?;
+        let price = ctx.accounts.current_price(max_price)?;
+        ctx.accounts.deposit(price)?;
+        ctx.accounts.withdraw_and_close_vault(price)
+    }
+
+    pub fn make_basket(
+        ctx: Context<MakeBasket>,
+        seed: u64,
+        deposits: Vec<BasketLeg>,
+        requests: Vec<BasketLeg>,
+    ) -> Result<()> {
+        ctx.accounts.init_basket(seed, deposits, requests, &ctx.bumps)?;
+        ctx.accounts.deposit(ctx.remaining_accounts)
+    }
+
+    pub fn take_basket(ctx: Context<TakeBasket>) -> Result<()> {
+        ctx.accounts.settle(ctx.remaining_accounts)
+    }
+
+    pub fn refund_basket(ctx: Context<RefundBasket>) -> Result<()> {
+        ctx.accounts.refund_and_close_vaults(ctx.remaining_accounts)
+    }
+
+    pub fn make_counter(ctx: Context<MakeCounter>, amount: u64) -> Result<()> {
+        ctx.accounts.init_counter(amount, &ctx.bumps)?;
+        ctx.accounts.deposit(amount)
+    }
+
+    pub fn accept_counter(ctx: Context<AcceptCounter>, amount: u64) -> Result<()> {
+        ctx.accounts.verify_amount(amount)?;
+        ctx.accounts.release_counter_vault()?;
+        ctx.accounts.withdraw_and_close_vault()
+    }
+
+    pub fn withdraw_counter(ctx: Context<WithdrawCounter>) -> Result<()> {
         ctx.accounts.withdraw_and_close_vault()
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+// Most mints a basket can deposit or request. Bounds the account size and the
+// remaining accounts take/refund pass (3 per leg).
+pub const MAX_BASKET_LEGS: usize = 4;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace, Debug, PartialEq)]
+pub struct BasketLeg {
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[account]
+#[derive(InitSpace, Debug)]
+pub struct BasketEscrow {
+    pub seed: u64,
+    pub maker: Pubkey,
+    pub bump: u8,
+    pub created_at: i64,  // Unix timestamp when the basket was created
+    #[max_len(MAX_BASKET_LEGS)]
+    pub deposits: Vec<BasketLeg>,  // Mints the maker locked up, one vault each
+    #[max_len(MAX_BASKET_LEGS)]
+    pub requests: Vec<BasketLeg>,  // Mints the maker wants in return
+}
+
+impl BasketEscrow {
+    /// A side of the basket needs 1..=MAX_BASKET_LEGS legs with non-zero
+    /// amounts and no mint listed twice.
+    pub fn are_valid_legs(legs: &[BasketLeg]) -> bool {
+        (1..=MAX_BASKET_LEGS).contains(&legs.len())
+            && legs.iter().all(|leg| leg.amount > 0)
+            && legs
+                .iter()
+                .enumerate()
+                .all(|(i, leg)| legs[..i].iter().all(|other| other.mint != leg.mint))
+    }
+}
@@ -0,0 +1,22 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace, Debug)]
+pub struct EscrowConfig {
+    pub admin: Pubkey,  // Only signer allowed to change the config
+    pub fee_recipient: Pubkey,  // Wallet whose mint A ATA receives the fee
+    pub fee_bps: u16,  // Fee skimmed from the mint A paid out on take, in basis points
+    pub bump: u8,
+}
+
+impl EscrowConfig {
+    /// Fee on `amount`, rounded down.
+    pub fn fee_for(&self, amount: u64) -> u64 {
+        (amount as u128 * self.fee_bps as u128 / BPS_DENOMINATOR as u128) as u64
+    }
+}
+
+pub const BPS_DENOMINATOR: u16 = 10_000;
+
+// Fees are capped at 10%
+pub const MAX_FEE_BPS: u16 = 1_000;
@@ -0,0 +1,11 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace, Debug)]
+pub struct CounterOffer {
+    pub escrow: Pubkey,  // Escrow the offer is for
+    pub taker: Pubkey,
+    pub amount: u64,  // Mint B proposed, locked in the counter's vault (net of any transfer fee)
+    pub bump: u8,
+    pub created_at: i64,  // Unix timestamp when the counter was posted
+}
@@ -1,5 +1,14 @@
 use anchor_lang::prelude::*;
 
+// Dutch-auction pricing: the mint B asked for falls from `receive` at
+// `created_at` to `floor` at `end_at`, then stays there
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace, Debug, PartialEq)]
+pub struct DutchAuction {
+    pub floor: u64,  // Lowest price, reached at end_at
+    pub end_at: i64,  // Unix timestamp the decay ends
+    pub steps: u32,  // 0 = decays linearly; n = drops in n equal steps
+}
+
 #[account]
 #[derive(InitSpace, Debug)]
 pub struct Escrow {
@@ -8,9 +17,46 @@ pub struct Escrow {
     pub mint_a: Pubkey,
     pub mint_b: Pubkey,
     pub receive: u64,
+    pub deposit: u64,  // Mint A that reached the vault, net of any transfer fee
     pub bump: u8,
     pub created_at: i64,  // Unix timestamp when escrow was created
+    pub taker: Option<Pubkey>,  // Only wallet allowed to take; None = anyone
+    pub unlock_at: i64,  // Unix timestamp from which the escrow can be taken
+    pub expires_at: Option<i64>,  // Unix timestamp from which it can no longer be taken; None = never
+    pub auction: Option<DutchAuction>,  // Decaying price; None = fixed at receive
 }
 
-// 5 days in seconds
-pub const ESCROW_LOCK_DURATION: i64 = 5 * 24 * 60 * 60;
\ No newline at end of file
+impl Escrow {
+    pub fn is_unlocked(&self, now: i64) -> bool {
+        now >= self.unlock_at
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+
+    /// The maker may refund before takers are let in or once the offer has
+    /// expired. An escrow with an expiry is committed to takers in between;
+    /// one without an expiry can be refunded at any time.
+    pub fn is_refundable(&self, now: i64) -> bool {
+        !self.is_unlocked(now) || self.expires_at.is_none() || self.is_expired(now)
+    }
+
+    /// Mint B asked for at `now`. Auction prices are rounded up in the maker's favour.
+    pub fn price_at(&self, now: i64) -> u64 {
+        let Some(auction) = self.auction else {
+            return self.receive;
+        };
+
+        let duration = (auction.end_at - self.created_at) as u128;
+        let elapsed = (now - self.created_at).clamp(0, auction.end_at - self.created_at) as u128;
+        let range = (self.receive - auction.floor) as u128;
+
+        let discount = match auction.steps {
+            0 => range * elapsed / duration,
+            steps => range * (elapsed * steps as u128 / duration) / steps as u128,
+        };
+
+        self.receive - discount as u64
+    }
+}
@@ -1,3 +1,9 @@
+pub mod basket;
+pub mod config;
+pub mod counter;
 pub mod escrow;
 
-pub use escrow::*;
\ No newline at end of file
+pub use basket::*;
+pub use config::*;
+pub use counter::*;
+pub use escrow::*;
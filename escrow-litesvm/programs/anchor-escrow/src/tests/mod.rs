@@ -15,7 +15,7 @@ mod tests {
             },
             token::spl_token
         },
-        litesvm::LiteSVM,
+        litesvm::{types::TransactionResult, LiteSVM},
         litesvm_token::{
             spl_token::ID as TOKEN_PROGRAM_ID,
             CreateAssociatedTokenAccount,
@@ -24,29 +24,52 @@ mod tests {
         solana_rpc_client::rpc_client::RpcClient,
         solana_account::Account,
         solana_clock::Clock,
-        solana_instruction::Instruction,
+        solana_instruction::{AccountMeta, Instruction},
         solana_keypair::Keypair,
         solana_message::Message,
         solana_native_token::LAMPORTS_PER_SOL,
         solana_pubkey::Pubkey,
-        solana_sdk_ids::system_program::ID as SYSTEM_PROGRAM_ID,
+        solana_sdk_ids::{bpf_loader_upgradeable, system_program::ID as SYSTEM_PROGRAM_ID},
         solana_signer::Signer,
         solana_transaction::Transaction,
         solana_address::Address,
         std::{
             path::PathBuf,
             str::FromStr
+        },
+        crate::{
+            client::EscrowEvent,
+            events::{
+                BasketMade, BasketRefunded, BasketTaken, CounterAccepted, CounterMade, CounterWithdrawn,
+                EscrowMade, EscrowRefunded, EscrowTaken
+            },
+            state::{BasketLeg, DutchAuction}
         }
     };
 
-    // 5 days in seconds (must match program constant)
+    // 5 days in seconds (the unlock delay the original tests give their escrows)
     const FIVE_DAYS_SECONDS: i64 = 5 * 24 * 60 * 60;
 
     static PROGRAM_ID: Pubkey = crate::ID;
 
+    // Fee recipient every test's config starts with (fee 0)
+    const TREASURY: Pubkey = Pubkey::new_from_array([7u8; 32]);
+
     // Setup function to initialize LiteSVM and create a payer keypair
     // Also loads an account from devnet into the LiteSVM environment (for testing purposes)
     fn setup() -> (LiteSVM, Keypair) {
+        let (mut program, payer) = setup_without_config();
+
+        // Create the global config with no fee; the payer (the upgrade authority) is its admin
+        let ix = config_ix(&payer.pubkey(), crate::instruction::InitializeConfig { fee_bps: 0, fee_recipient: TREASURY });
+        send(&mut program, ix, &payer).expect("Failed to initialize config");
+
+        // Return the LiteSVM instance and payer keypair
+        (program, payer)
+    }
+
+    // Same as `setup`, but stops before creating the config
+    fn setup_without_config() -> (LiteSVM, Keypair) {
         // Initialize LiteSVM and payer
         let mut program = LiteSVM::new();
         let payer = Keypair::new();
@@ -62,7 +85,7 @@ mod tests {
     
         let program_data = std::fs::read(so_path).expect("Failed to read program SO file");
     
-        program.add_program(PROGRAM_ID, &program_data);
+        add_upgradeable_program(&mut program, &program_data, &payer.pubkey());
 
         // Example on how to Load an account from devnet
         // LiteSVM does not have access to real Solana network data since it does not have network access,
@@ -84,11 +107,44 @@ mod tests {
         }).unwrap();
 
         msg!("Lamports of fetched account: {}", fetched_account.lamports);
-    
-        // Return the LiteSVM instance and payer keypair
+
         (program, payer)
     }
 
+    fn program_data_address() -> Pubkey {
+        Pubkey::find_program_address(&[PROGRAM_ID.as_ref()], &bpf_loader_upgradeable::ID).0
+    }
+
+    // Deploys the program under the upgradeable loader with `authority` as its
+    // upgrade authority. `add_program` uses the non-upgradeable loader, which
+    // has no ProgramData account for `initialize_config` to check.
+    fn add_upgradeable_program(program: &mut LiteSVM, elf: &[u8], authority: &Pubkey) {
+        // UpgradeableLoaderState::ProgramData { slot: 0, upgrade_authority_address: Some(authority) }
+        let mut data = 3u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(authority.as_ref());
+        data.extend_from_slice(elf);
+        program.set_account(program_data_address(), Account {
+            lamports: program.minimum_balance_for_rent_exemption(data.len()),
+            data,
+            owner: bpf_loader_upgradeable::ID,
+            executable: false,
+            rent_epoch: 0,
+        }).unwrap();
+
+        // UpgradeableLoaderState::Program { programdata_address }
+        let mut data = 2u32.to_le_bytes().to_vec();
+        data.extend_from_slice(program_data_address().as_ref());
+        program.set_account(PROGRAM_ID, Account {
+            lamports: program.minimum_balance_for_rent_exemption(data.len()),
+            data,
+            owner: bpf_loader_upgradeable::ID,
+            executable: true,
+            rent_epoch: 0,
+        }).unwrap();
+    }
+
     #[test]
     fn test_make() {
 
@@ -155,7 +211,7 @@ mod tests {
                 token_program: token_program,
                 system_program: system_program,
             }.to_account_metas(None),
-            data: crate::instruction::Make {deposit: 10, seed: 123u64, receive: 10 }.data(),
+            data: crate::instruction::Make {deposit: 10, seed: 123u64, receive: 10, taker: None, unlock_at: 0, expires_at: None, auction: None }.data(),
         };
 
         // Create and send the transaction containing the "Make" instruction
@@ -186,6 +242,7 @@ mod tests {
         assert_eq!(escrow_data.mint_a, mint_a);
         assert_eq!(escrow_data.mint_b, mint_b);
         assert_eq!(escrow_data.receive, 10);
+        assert_eq!(escrow_data.taker, None);
 
     }
 
@@ -267,7 +324,11 @@ mod tests {
             data: crate::instruction::Make {
                 deposit: deposit_amount,
                 seed,
-                receive: receive_amount
+                receive: receive_amount,
+                taker: None,
+                unlock_at: program.get_sysvar::<Clock>().unix_timestamp + FIVE_DAYS_SECONDS,
+                expires_at: None,
+                auction: None
             }.data(),
         };
 
@@ -321,11 +382,14 @@ mod tests {
                 maker_ata_b,
                 escrow,
                 vault,
+                config: config_pda(),
+                fee_recipient: TREASURY,
+                treasury_ata: associated_token::get_associated_token_address(&TREASURY, &mint_a),
                 associated_token_program,
                 token_program,
                 system_program,
             }.to_account_metas(None),
-            data: crate::instruction::Take {}.data(),
+            data: crate::instruction::Take { max_price: None }.data(),
         };
 
         let message = Message::new(&[take_ix], Some(&taker));
@@ -435,7 +499,11 @@ mod tests {
             data: crate::instruction::Make {
                 deposit: 100,
                 seed,
-                receive: 50
+                receive: 50,
+                taker: None,
+                unlock_at: program.get_sysvar::<Clock>().unix_timestamp + FIVE_DAYS_SECONDS,
+                expires_at: None,
+                auction: None
             }.data(),
         };
 
@@ -471,11 +539,14 @@ mod tests {
                 maker_ata_b,
                 escrow,
                 vault,
+                config: config_pda(),
+                fee_recipient: TREASURY,
+                treasury_ata: associated_token::get_associated_token_address(&TREASURY, &mint_a),
                 associated_token_program,
                 token_program,
                 system_program,
             }.to_account_metas(None),
-            data: crate::instruction::Take {}.data(),
+            data: crate::instruction::Take { max_price: None }.data(),
         };
 
         let message = Message::new(&[take_ix], Some(&taker));
@@ -498,6 +569,18 @@ mod tests {
 
         // Verify escrow still exists (wasn't closed)
         assert!(program.get_account(&escrow).is_some(), "Escrow should still exist");
+
+        // The reserved taker can fill it
+        fund_taker(&mut program, &payer, &counterparty, &mint_b, 1000);
+        let ix = take_ix(&counterparty.pubkey(), &maker, &mint_a, &mint_b, &escrow, &vault);
+        let message = Message::new(&[ix], Some(&counterparty.pubkey()));
+        let transaction = Transaction::new(&[&counterparty], message, program.latest_blockhash());
+        program.send_transaction(transaction).unwrap();
+
+        let taker_ata_a = associated_token::get_associated_token_address(&counterparty.pubkey(), &mint_a);
+        let taker_ata_a_account = program.get_account(&taker_ata_a).unwrap();
+        let taker_ata_a_data = spl_token::state::Account::unpack(&taker_ata_a_account.data).unwrap();
+        assert_eq!(taker_ata_a_data.amount, 100);
     }
 
     #[test]
@@ -570,7 +653,11 @@ mod tests {
             data: crate::instruction::Make {
                 deposit: deposit_amount,
                 seed,
-                receive: 100
+                receive: 100,
+                taker: None,
+                unlock_at: program.get_sysvar::<Clock>().unix_timestamp + FIVE_DAYS_SECONDS,
+                expires_at: None,
+                auction: None
             }.data(),
         };
 
@@ -634,4 +721,1191 @@ mod tests {
         }
     }
 
-}
\ No newline at end of file
+    // Helper: "Make" arguments for an escrow anyone can take right away and that never expires
+    fn make_args(seed: u64, deposit: u64, receive: u64) -> crate::instruction::Make {
+        crate::instruction::Make {
+            seed,
+            deposit,
+            receive,
+            taker: None,
+            unlock_at: 0,
+            expires_at: None,
+            auction: None,
+        }
+    }
+
+    // Helper: creates Mint A and Mint B, funds the maker's ATA A and runs "Make", which must succeed
+    // Returns (mint_a, mint_b, escrow, vault, make transaction logs)
+    fn make_escrow(
+        program: &mut LiteSVM,
+        payer: &Keypair,
+        args: crate::instruction::Make,
+    ) -> (Pubkey, Pubkey, Pubkey, Pubkey, Vec<String>) {
+        make_escrow_with(program, payer, args, &TOKEN_PROGRAM_ID)
+    }
+
+    // Helper: `make_escrow` with both mints under `token_program`
+    fn make_escrow_with(
+        program: &mut LiteSVM,
+        payer: &Keypair,
+        args: crate::instruction::Make,
+        token_program: &Pubkey,
+    ) -> (Pubkey, Pubkey, Pubkey, Pubkey, Vec<String>) {
+        let (result, mint_a, mint_b, escrow, vault) = try_make_escrow(program, payer, args, token_program);
+        (mint_a, mint_b, escrow, vault, result.unwrap().logs)
+    }
+
+    // Helper: like `make_escrow_with`, but returns the "Make" result instead of unwrapping it
+    fn try_make_escrow(
+        program: &mut LiteSVM,
+        payer: &Keypair,
+        args: crate::instruction::Make,
+        token_program: &Pubkey,
+    ) -> (TransactionResult, Pubkey, Pubkey, Pubkey, Pubkey) {
+        let maker = payer.pubkey();
+
+        let mint_a = CreateMint::new(program, payer)
+            .decimals(6)
+            .authority(&maker)
+            .token_program_id(token_program)
+            .send()
+            .unwrap();
+        let mint_b = CreateMint::new(program, payer)
+            .decimals(6)
+            .authority(&maker)
+            .token_program_id(token_program)
+            .send()
+            .unwrap();
+
+        let (result, escrow, vault) = try_make_escrow_for(program, payer, args, &mint_a, &mint_b);
+        (result, mint_a, mint_b, escrow, vault)
+    }
+
+    // Helper: funds the maker's ATA A with `args.deposit` and sends "Make" for existing mints
+    // Returns (make result, escrow, vault)
+    fn try_make_escrow_for(
+        program: &mut LiteSVM,
+        payer: &Keypair,
+        args: crate::instruction::Make,
+        mint_a: &Pubkey,
+        mint_b: &Pubkey,
+    ) -> (TransactionResult, Pubkey, Pubkey) {
+        let maker = payer.pubkey();
+        let token_program = program.get_account(mint_a).unwrap().owner;
+
+        let maker_ata_a = CreateAssociatedTokenAccount::new(program, payer, mint_a)
+            .owner(&maker)
+            .token_program_id(&token_program)
+            .send()
+            .unwrap();
+        MintTo::new(program, payer, mint_a, &maker_ata_a, args.deposit)
+            .token_program_id(&token_program)
+            .send()
+            .unwrap();
+
+        let escrow = Pubkey::find_program_address(
+            &[b"escrow", maker.as_ref(), &args.seed.to_le_bytes()],
+            &PROGRAM_ID
+        ).0;
+        let vault = associated_token::get_associated_token_address_with_program_id(&escrow, mint_a, &token_program);
+
+        let make_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::Make {
+                maker,
+                mint_a: *mint_a,
+                mint_b: *mint_b,
+                maker_ata_a,
+                escrow,
+                vault,
+                associated_token_program: spl_associated_token_account::ID,
+                token_program,
+                system_program: SYSTEM_PROGRAM_ID,
+            }.to_account_metas(None),
+            data: args.data(),
+        };
+
+        (send(program, make_ix, payer), escrow, vault)
+    }
+
+    // Helper: creates a 6-decimal Token-2022 mint charging a `fee_bps` transfer fee
+    fn create_fee_mint(program: &mut LiteSVM, payer: &Keypair, fee_bps: u16) -> Pubkey {
+        use spl_token_2022::extension::{transfer_fee, ExtensionType};
+
+        let mint = Keypair::new();
+        let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(
+            &[ExtensionType::TransferFeeConfig]
+        ).unwrap();
+
+        let instructions = [
+            solana_system_interface::instruction::create_account(
+                &payer.pubkey(),
+                &mint.pubkey(),
+                program.minimum_balance_for_rent_exemption(space),
+                space as u64,
+                &spl_token_2022::ID,
+            ),
+            transfer_fee::instruction::initialize_transfer_fee_config(
+                &spl_token_2022::ID,
+                &mint.pubkey(),
+                Some(&payer.pubkey()),
+                Some(&payer.pubkey()),
+                fee_bps,
+                u64::MAX,
+            ).unwrap(),
+            spl_token_2022::instruction::initialize_mint2(
+                &spl_token_2022::ID,
+                &mint.pubkey(),
+                &payer.pubkey(),
+                None,
+                6,
+            ).unwrap(),
+        ];
+
+        let message = Message::new(&instructions, Some(&payer.pubkey()));
+        let transaction = Transaction::new(&[payer, &mint], message, program.latest_blockhash());
+        program.send_transaction(transaction).expect("Failed to create transfer-fee mint");
+
+        mint.pubkey()
+    }
+
+    // Helper: airdrops SOL to the taker and gives them `amount` of Mint B in their ATA
+    fn fund_taker(program: &mut LiteSVM, payer: &Keypair, taker: &Keypair, mint_b: &Pubkey, amount: u64) {
+        program
+            .airdrop(&taker.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Failed to airdrop SOL to taker");
+
+        let token_program = program.get_account(mint_b).unwrap().owner;
+        let taker_ata_b = CreateAssociatedTokenAccount::new(program, taker, mint_b)
+            .owner(&taker.pubkey())
+            .token_program_id(&token_program)
+            .send()
+            .unwrap();
+        MintTo::new(program, payer, mint_b, &taker_ata_b, amount)
+            .token_program_id(&token_program)
+            .send()
+            .unwrap();
+    }
+
+    // Helper: builds the "Take" instruction; taker_ata_a and maker_ata_b are init_if_needed
+    fn take_ix(taker: &Pubkey, maker: &Pubkey, mint_a: &Pubkey, mint_b: &Pubkey, escrow: &Pubkey, vault: &Pubkey) -> Instruction {
+        take_ix_with(taker, maker, mint_a, mint_b, escrow, vault, &TOKEN_PROGRAM_ID)
+    }
+
+    // Helper: `take_ix` for mints under `token_program`, paying the fee to the config's TREASURY
+    fn take_ix_with(
+        taker: &Pubkey,
+        maker: &Pubkey,
+        mint_a: &Pubkey,
+        mint_b: &Pubkey,
+        escrow: &Pubkey,
+        vault: &Pubkey,
+        token_program: &Pubkey,
+    ) -> Instruction {
+        let ata = |wallet, mint| associated_token::get_associated_token_address_with_program_id(wallet, mint, token_program);
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::Take {
+                taker: *taker,
+                maker: *maker,
+                mint_a: *mint_a,
+                mint_b: *mint_b,
+                taker_ata_a: ata(taker, mint_a),
+                taker_ata_b: ata(taker, mint_b),
+                maker_ata_b: ata(maker, mint_b),
+                escrow: *escrow,
+                vault: *vault,
+                config: config_pda(),
+                fee_recipient: TREASURY,
+                treasury_ata: ata(&TREASURY, mint_a),
+                associated_token_program: spl_associated_token_account::ID,
+                token_program: *token_program,
+                system_program: SYSTEM_PROGRAM_ID,
+            }.to_account_metas(None),
+            data: crate::instruction::Take { max_price: None }.data(),
+        }
+    }
+
+    fn config_pda() -> Pubkey {
+        Pubkey::find_program_address(&[b"config"], &PROGRAM_ID).0
+    }
+
+    // Helper: builds "InitializeConfig" with `admin` paying for the config PDA
+    fn config_ix(admin: &Pubkey, args: crate::instruction::InitializeConfig) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::InitializeConfig {
+                admin: *admin,
+                program: PROGRAM_ID,
+                program_data: program_data_address(),
+                config: config_pda(),
+                system_program: SYSTEM_PROGRAM_ID,
+            }.to_account_metas(None),
+            data: args.data(),
+        }
+    }
+
+    // Helper: builds an admin instruction ("UpdateConfig" or "TransferAdmin") signed by `admin`
+    fn update_config_ix(admin: &Pubkey, data: Vec<u8>) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::UpdateConfig {
+                admin: *admin,
+                config: config_pda(),
+            }.to_account_metas(None),
+            data,
+        }
+    }
+
+    // Helper: reads a token account's balance
+    fn token_balance(program: &LiteSVM, account: &Pubkey) -> u64 {
+        let data = program.get_account(account).unwrap().data;
+        u64::from_le_bytes(data[64..72].try_into().unwrap())
+    }
+
+    // Helper: creates a mint with `decimals` and gives `owner` `amount` of it in their ATA
+    fn mint_to_ata(program: &mut LiteSVM, payer: &Keypair, owner: &Pubkey, decimals: u8, amount: u64) -> Pubkey {
+        let mint = CreateMint::new(program, payer)
+            .decimals(decimals)
+            .authority(&payer.pubkey())
+            .send()
+            .unwrap();
+        let ata = CreateAssociatedTokenAccount::new(program, payer, &mint)
+            .owner(owner)
+            .send()
+            .unwrap();
+        MintTo::new(program, payer, &mint, &ata, amount)
+            .send()
+            .unwrap();
+        mint
+    }
+
+    fn ata(wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+        associated_token::get_associated_token_address(wallet, mint)
+    }
+
+    fn basket_pda(maker: &Pubkey, seed: u64) -> Pubkey {
+        Pubkey::find_program_address(&[b"basket", maker.as_ref(), &seed.to_le_bytes()], &PROGRAM_ID).0
+    }
+
+    // Helper: `wallet`'s ATA for `mint`, under whichever token program owns the mint
+    fn leg_ata(program: &LiteSVM, wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+        let token_program = program.get_account(mint).unwrap().owner;
+        associated_token::get_associated_token_address_with_program_id(wallet, mint, &token_program)
+    }
+
+    // Helper: appends each leg's remaining accounts ([mint, from, to, ...]), all writable
+    // Mints are writable so vault releases can harvest withheld transfer fees
+    fn with_legs<const N: usize>(mut ix: Instruction, legs: impl IntoIterator<Item = [Pubkey; N]>) -> Instruction {
+        for leg in legs {
+            ix.accounts.extend(leg.into_iter().map(|account| AccountMeta::new(account, false)));
+        }
+        ix
+    }
+
+    // Helper: builds "MakeBasket", depositing from the maker's ATAs into the basket's vaults
+    fn make_basket_ix(program: &LiteSVM, maker: &Pubkey, seed: u64, deposits: &[BasketLeg], requests: &[BasketLeg]) -> Instruction {
+        let basket = basket_pda(maker, seed);
+        let ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::MakeBasket {
+                maker: *maker,
+                basket,
+                associated_token_program: spl_associated_token_account::ID,
+                token_program: TOKEN_PROGRAM_ID,
+                token_2022_program: spl_token_2022::ID,
+                system_program: SYSTEM_PROGRAM_ID,
+            }.to_account_metas(None),
+            data: crate::instruction::MakeBasket {
+                seed,
+                deposits: deposits.to_vec(),
+                requests: requests.to_vec(),
+            }.data(),
+        };
+        let ata = |wallet, mint| leg_ata(program, wallet, mint);
+        with_legs(ix, deposits.iter().map(|leg| [leg.mint, ata(maker, &leg.mint), ata(&basket, &leg.mint)]))
+    }
+
+    // Helper: builds "TakeBasket"; request triples come first, then deposit legs with the
+    // config TREASURY's ATA for the fee
+    fn take_basket_ix(program: &LiteSVM, taker: &Pubkey, maker: &Pubkey, seed: u64, deposits: &[BasketLeg], requests: &[BasketLeg]) -> Instruction {
+        let basket = basket_pda(maker, seed);
+        let ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::TakeBasket {
+                taker: *taker,
+                maker: *maker,
+                basket,
+                config: config_pda(),
+                fee_recipient: TREASURY,
+                associated_token_program: spl_associated_token_account::ID,
+                token_program: TOKEN_PROGRAM_ID,
+                token_2022_program: spl_token_2022::ID,
+                system_program: SYSTEM_PROGRAM_ID,
+            }.to_account_metas(None),
+            data: crate::instruction::TakeBasket {}.data(),
+        };
+        let ata = |wallet, mint| leg_ata(program, wallet, mint);
+        let ix = with_legs(ix, requests.iter().map(|leg| [leg.mint, ata(taker, &leg.mint), ata(maker, &leg.mint)]));
+        with_legs(ix, deposits.iter().map(|leg| {
+            [leg.mint, ata(&basket, &leg.mint), ata(taker, &leg.mint), ata(&TREASURY, &leg.mint)]
+        }))
+    }
+
+    // Helper: builds "RefundBasket", returning every vault to the maker's ATAs
+    fn refund_basket_ix(program: &LiteSVM, maker: &Pubkey, seed: u64, deposits: &[BasketLeg]) -> Instruction {
+        let basket = basket_pda(maker, seed);
+        let ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::RefundBasket {
+                maker: *maker,
+                basket,
+                associated_token_program: spl_associated_token_account::ID,
+                token_program: TOKEN_PROGRAM_ID,
+                token_2022_program: spl_token_2022::ID,
+                system_program: SYSTEM_PROGRAM_ID,
+            }.to_account_metas(None),
+            data: crate::instruction::RefundBasket {}.data(),
+        };
+        let ata = |wallet, mint| leg_ata(program, wallet, mint);
+        with_legs(ix, deposits.iter().map(|leg| [leg.mint, ata(&basket, &leg.mint), ata(maker, &leg.mint)]))
+    }
+
+    // Helper: a basket swapping an NFT plus 500 tokens from the maker for 100 and 7 of two
+    // mints held by `taker`. Returns (deposits, requests)
+    fn bundle_basket(program: &mut LiteSVM, payer: &Keypair, taker: &Keypair) -> (Vec<BasketLeg>, Vec<BasketLeg>) {
+        let maker = payer.pubkey();
+        program
+            .airdrop(&taker.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Failed to airdrop SOL to taker");
+
+        let nft = mint_to_ata(program, payer, &maker, 0, 1);
+        let token = mint_to_ata(program, payer, &maker, 6, 500);
+        let wanted_x = mint_to_ata(program, payer, &taker.pubkey(), 6, 1000);
+        let wanted_y = mint_to_ata(program, payer, &taker.pubkey(), 0, 10);
+
+        (
+            vec![BasketLeg { mint: nft, amount: 1 }, BasketLeg { mint: token, amount: 500 }],
+            vec![BasketLeg { mint: wanted_x, amount: 100 }, BasketLeg { mint: wanted_y, amount: 7 }],
+        )
+    }
+
+    fn counter_pda(escrow: &Pubkey, taker: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"counter", escrow.as_ref(), taker.as_ref()], &PROGRAM_ID).0
+    }
+
+    // Helper: builds "MakeCounter", locking `amount` of the taker's Mint B
+    fn make_counter_ix(taker: &Pubkey, mint_b: &Pubkey, escrow: &Pubkey, amount: u64) -> Instruction {
+        let counter = counter_pda(escrow, taker);
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::MakeCounter {
+                taker: *taker,
+                mint_b: *mint_b,
+                taker_ata_b: ata(taker, mint_b),
+                escrow: *escrow,
+                counter,
+                counter_vault: ata(&counter, mint_b),
+                associated_token_program: spl_associated_token_account::ID,
+                token_program: TOKEN_PROGRAM_ID,
+                system_program: SYSTEM_PROGRAM_ID,
+            }.to_account_metas(None),
+            data: crate::instruction::MakeCounter { amount }.data(),
+        }
+    }
+
+    // Helper: builds "AcceptCounter" for `taker`'s counter on the maker's escrow, expecting `amount`
+    fn accept_counter_ix(
+        maker: &Pubkey,
+        taker: &Pubkey,
+        mint_a: &Pubkey,
+        mint_b: &Pubkey,
+        escrow: &Pubkey,
+        vault: &Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        let counter = counter_pda(escrow, taker);
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::AcceptCounter {
+                maker: *maker,
+                taker: *taker,
+                mint_a: *mint_a,
+                mint_b: *mint_b,
+                maker_ata_b: ata(maker, mint_b),
+                taker_ata_a: ata(taker, mint_a),
+                escrow: *escrow,
+                vault: *vault,
+                counter,
+                counter_vault: ata(&counter, mint_b),
+                config: config_pda(),
+                fee_recipient: TREASURY,
+                treasury_ata: ata(&TREASURY, mint_a),
+                associated_token_program: spl_associated_token_account::ID,
+                token_program: TOKEN_PROGRAM_ID,
+                system_program: SYSTEM_PROGRAM_ID,
+            }.to_account_metas(None),
+            data: crate::instruction::AcceptCounter { amount }.data(),
+        }
+    }
+
+    // Helper: builds "WithdrawCounter", returning the counter's Mint B to the taker
+    fn withdraw_counter_ix(taker: &Pubkey, mint_b: &Pubkey, escrow: &Pubkey) -> Instruction {
+        let counter = counter_pda(escrow, taker);
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::WithdrawCounter {
+                taker: *taker,
+                mint_b: *mint_b,
+                taker_ata_b: ata(taker, mint_b),
+                counter,
+                counter_vault: ata(&counter, mint_b),
+                token_program: TOKEN_PROGRAM_ID,
+                system_program: SYSTEM_PROGRAM_ID,
+            }.to_account_metas(None),
+            data: crate::instruction::WithdrawCounter {}.data(),
+        }
+    }
+
+    // Helper: builds the "Refund" instruction for the maker's ATA A
+    fn refund_ix(maker: &Pubkey, mint_a: &Pubkey, escrow: &Pubkey, vault: &Pubkey) -> Instruction {
+        refund_ix_with(maker, mint_a, escrow, vault, &TOKEN_PROGRAM_ID)
+    }
+
+    // Helper: `refund_ix` for a Mint A under `token_program`
+    fn refund_ix_with(maker: &Pubkey, mint_a: &Pubkey, escrow: &Pubkey, vault: &Pubkey, token_program: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::Refund {
+                maker: *maker,
+                mint_a: *mint_a,
+                maker_ata_a: associated_token::get_associated_token_address_with_program_id(maker, mint_a, token_program),
+                escrow: *escrow,
+                vault: *vault,
+                token_program: *token_program,
+                system_program: SYSTEM_PROGRAM_ID,
+            }.to_account_metas(None),
+            data: crate::instruction::Refund {}.data(),
+        }
+    }
+
+    // Helper: signs `ix` with `signer` as fee payer and sends it
+    fn send(program: &mut LiteSVM, ix: Instruction, signer: &Keypair) -> TransactionResult {
+        let message = Message::new(&[ix], Some(&signer.pubkey()));
+        let transaction = Transaction::new(&[signer], message, program.latest_blockhash());
+        program.send_transaction(transaction)
+    }
+
+    // Helper: sets the clock to `unix_timestamp`
+    fn warp_to(program: &mut LiteSVM, unix_timestamp: i64) {
+        let mut clock = program.get_sysvar::<Clock>();
+        clock.unix_timestamp = unix_timestamp;
+        program.set_sysvar::<Clock>(&clock);
+    }
+
+    // Helper: asserts the transaction failed and its logs name `error`
+    fn assert_failed_with(result: TransactionResult, error: &str) {
+        let err = result.expect_err("transaction should fail");
+        let logs = err.meta.logs.join("\n");
+        assert!(logs.contains(error), "Expected '{}' error, got: {}", error, logs);
+    }
+
+    #[test]
+    fn test_take_reserved_for_taker() {
+        let (mut program, payer) = setup();
+        let maker = payer.pubkey();
+
+        // The maker reserves the escrow for one counterparty
+        let counterparty = Keypair::new();
+        let (mint_a, mint_b, escrow, vault, _) = make_escrow(&mut program, &payer, crate::instruction::Make {
+            taker: Some(counterparty.pubkey()),
+            ..make_args(222, 100, 50)
+        });
+
+        let escrow_account = program.get_account(&escrow).unwrap();
+        let escrow_data = crate::state::Escrow::try_deserialize(&mut escrow_account.data.as_ref()).unwrap();
+        assert_eq!(escrow_data.taker, Some(counterparty.pubkey()));
+
+        // Anyone else is rejected
+        let stranger = Keypair::new();
+        fund_taker(&mut program, &payer, &stranger, &mint_b, 1000);
+        let ix = take_ix(&stranger.pubkey(), &maker, &mint_a, &mint_b, &escrow, &vault);
+        let message = Message::new(&[ix], Some(&stranger.pubkey()));
+        let transaction = Transaction::new(&[&stranger], message, program.latest_blockhash());
+        assert_failed_with(program.send_transaction(transaction), "TakerNotAllowed");
+        assert!(program.get_account(&escrow).is_some(), "Escrow should still exist");
+    }
+
+    #[test]
+    fn test_events() {
+        let (mut program, payer) = setup();
+        let maker = payer.pubkey();
+
+        // "Make" logs EscrowMade
+        let (mint_a, mint_b, escrow, vault, logs) = make_escrow(&mut program, &payer, make_args(333, 100, 50));
+        assert_eq!(
+            EscrowEvent::from_logs(&logs),
+            [EscrowEvent::Made(EscrowMade {
+                escrow,
+                maker,
+                mint_a,
+                mint_b,
+                seed: 333,
+                deposit: 100,
+                receive: 50,
+                taker: None,
+                unlock_at: 0,
+                expires_at: None,
+                auction: None,
+            })]
+        );
+
+        // "Take" logs EscrowTaken
+        let taker = Keypair::new();
+        fund_taker(&mut program, &payer, &taker, &mint_b, 1000);
+        let ix = take_ix(&taker.pubkey(), &maker, &mint_a, &mint_b, &escrow, &vault);
+        let message = Message::new(&[ix], Some(&taker.pubkey()));
+        let transaction = Transaction::new(&[&taker], message, program.latest_blockhash());
+        let tx = program.send_transaction(transaction).unwrap();
+        assert_eq!(
+            EscrowEvent::from_logs(&tx.logs),
+            [EscrowEvent::Taken(EscrowTaken {
+                escrow,
+                maker,
+                taker: taker.pubkey(),
+                mint_a,
+                mint_b,
+                deposit: 100,
+                receive: 50,
+                fee: 0,
+            })]
+        );
+
+        // "Refund" logs EscrowRefunded
+        let (mint_a, _, escrow, vault, _) = make_escrow(&mut program, &payer, make_args(334, 200, 50));
+        let tx = send(&mut program, refund_ix(&maker, &mint_a, &escrow, &vault), &payer).unwrap();
+        assert_eq!(
+            EscrowEvent::from_logs(&tx.logs),
+            [EscrowEvent::Refunded(EscrowRefunded { escrow, maker, mint_a, amount: 200 })]
+        );
+    }
+
+    #[test]
+    fn test_take_time_window() {
+        let (mut program, payer) = setup();
+        let maker = payer.pubkey();
+        let now = program.get_sysvar::<Clock>().unix_timestamp;
+        let window = |seed| crate::instruction::Make {
+            unlock_at: now + 100,
+            expires_at: Some(now + 200),
+            ..make_args(seed, 100, 50)
+        };
+
+        let (mint_a, mint_b, escrow, vault, _) = make_escrow(&mut program, &payer, window(1));
+        let taker = Keypair::new();
+        fund_taker(&mut program, &payer, &taker, &mint_b, 1000);
+
+        // One second before unlock_at the escrow is still locked
+        warp_to(&mut program, now + 99);
+        let ix = take_ix(&taker.pubkey(), &maker, &mint_a, &mint_b, &escrow, &vault);
+        assert_failed_with(send(&mut program, ix.clone(), &taker), "EscrowStillLocked");
+
+        // From unlock_at it can be taken
+        warp_to(&mut program, now + 100);
+        program.expire_blockhash();
+        send(&mut program, ix, &taker).unwrap();
+        assert!(program.get_account(&escrow).map_or(true, |acc| acc.lamports == 0));
+
+        // A second escrow with the same window is never taken
+        warp_to(&mut program, now);
+        let (mint_a, mint_b, escrow, vault, _) = make_escrow(&mut program, &payer, window(2));
+        fund_taker(&mut program, &payer, &taker, &mint_b, 1000);
+
+        // Inside the window the maker is committed
+        warp_to(&mut program, now + 199);
+        let refund = refund_ix(&maker, &mint_a, &escrow, &vault);
+        assert_failed_with(send(&mut program, refund.clone(), &payer), "EscrowActive");
+
+        // From expires_at takers are turned away and the maker can refund
+        warp_to(&mut program, now + 200);
+        let ix = take_ix(&taker.pubkey(), &maker, &mint_a, &mint_b, &escrow, &vault);
+        assert_failed_with(send(&mut program, ix, &taker), "EscrowExpired");
+        program.expire_blockhash();
+        send(&mut program, refund, &payer).unwrap();
+        assert!(program.get_account(&escrow).map_or(true, |acc| acc.lamports == 0));
+    }
+
+    #[test]
+    fn test_make_rejects_invalid_time_window() {
+        let (mut program, payer) = setup();
+        let now = program.get_sysvar::<Clock>().unix_timestamp;
+
+        // Expiry at or before unlock_at leaves no window to take in
+        let args = crate::instruction::Make {
+            unlock_at: now + 100,
+            expires_at: Some(now + 100),
+            ..make_args(1, 100, 50)
+        };
+        let (result, ..) = try_make_escrow(&mut program, &payer, args, &TOKEN_PROGRAM_ID);
+        assert_failed_with(result, "InvalidTimeWindow");
+
+        // Expiry already in the past
+        let args = crate::instruction::Make {
+            expires_at: Some(now),
+            ..make_args(2, 100, 50)
+        };
+        let (result, ..) = try_make_escrow(&mut program, &payer, args, &TOKEN_PROGRAM_ID);
+        assert_failed_with(result, "InvalidTimeWindow");
+    }
+
+    #[test]
+    fn test_config_admin() {
+        let (mut program, payer) = setup();
+
+        let config_account = program.get_account(&config_pda()).unwrap();
+        let config = crate::state::EscrowConfig::try_deserialize(&mut config_account.data.as_ref()).unwrap();
+        assert_eq!(config.admin, payer.pubkey());
+        assert_eq!(config.fee_recipient, TREASURY);
+        assert_eq!(config.fee_bps, 0);
+
+        // The config can only be created once
+        let ix = config_ix(&payer.pubkey(), crate::instruction::InitializeConfig { fee_bps: 0, fee_recipient: TREASURY });
+        program.expire_blockhash();
+        assert!(send(&mut program, ix, &payer).is_err(), "Config should not be re-initialized");
+
+        // Fees above the cap are rejected
+        let data = crate::instruction::UpdateConfig { fee_bps: 1_001, fee_recipient: TREASURY }.data();
+        assert_failed_with(send(&mut program, update_config_ix(&payer.pubkey(), data), &payer), "InvalidFeeBps");
+
+        // Only the admin can change the config
+        let stranger = Keypair::new();
+        program.airdrop(&stranger.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        let data = crate::instruction::UpdateConfig { fee_bps: 100, fee_recipient: stranger.pubkey() }.data();
+        assert_failed_with(send(&mut program, update_config_ix(&stranger.pubkey(), data), &stranger), "Unauthorized");
+
+        // After handing over admin, the old admin is locked out and the new one is in
+        let data = crate::instruction::TransferAdmin { new_admin: stranger.pubkey() }.data();
+        send(&mut program, update_config_ix(&payer.pubkey(), data), &payer).unwrap();
+
+        let data = crate::instruction::UpdateConfig { fee_bps: 100, fee_recipient: payer.pubkey() }.data();
+        assert_failed_with(send(&mut program, update_config_ix(&payer.pubkey(), data.clone()), &payer), "Unauthorized");
+        send(&mut program, update_config_ix(&stranger.pubkey(), data), &stranger).unwrap();
+
+        let config_account = program.get_account(&config_pda()).unwrap();
+        let config = crate::state::EscrowConfig::try_deserialize(&mut config_account.data.as_ref()).unwrap();
+        assert_eq!(config.admin, stranger.pubkey());
+        assert_eq!(config.fee_recipient, payer.pubkey());
+        assert_eq!(config.fee_bps, 100);
+    }
+
+    #[test]
+    fn test_config_requires_upgrade_authority() {
+        let (mut program, payer) = setup_without_config();
+        let maker = payer.pubkey();
+
+        // Escrows can be made before the config exists, but not taken
+        let (mint_a, mint_b, escrow, vault, _) = make_escrow(&mut program, &payer, make_args(555, 100, 50));
+        let taker = Keypair::new();
+        fund_taker(&mut program, &payer, &taker, &mint_b, 1000);
+        let ix = take_ix(&taker.pubkey(), &maker, &mint_a, &mint_b, &escrow, &vault);
+        assert_failed_with(send(&mut program, ix, &taker), "AccountNotInitialized");
+
+        // Only the upgrade authority can create the config
+        let ix = config_ix(&taker.pubkey(), crate::instruction::InitializeConfig { fee_bps: 1_000, fee_recipient: taker.pubkey() });
+        assert_failed_with(send(&mut program, ix, &taker), "Unauthorized");
+        assert!(program.get_account(&config_pda()).map_or(true, |acc| acc.lamports == 0));
+
+        let ix = config_ix(&maker, crate::instruction::InitializeConfig { fee_bps: 0, fee_recipient: TREASURY });
+        send(&mut program, ix, &payer).unwrap();
+
+        program.expire_blockhash();
+        let ix = take_ix(&taker.pubkey(), &maker, &mint_a, &mint_b, &escrow, &vault);
+        send(&mut program, ix, &taker).unwrap();
+        assert_eq!(token_balance(&program, &ata(&maker, &mint_b)), 50);
+    }
+
+    // Sets a 2.5% fee, then takes an escrow of 1000 Mint A under `token_program`
+    fn take_with_fee(token_program: &Pubkey) {
+        let (mut program, payer) = setup();
+        let maker = payer.pubkey();
+
+        let data = crate::instruction::UpdateConfig { fee_bps: 250, fee_recipient: TREASURY }.data();
+        send(&mut program, update_config_ix(&maker, data), &payer).unwrap();
+
+        let (mint_a, mint_b, escrow, vault, _) = make_escrow_with(&mut program, &payer, make_args(444, 1000, 50), token_program);
+        let taker = Keypair::new();
+        fund_taker(&mut program, &payer, &taker, &mint_b, 1000);
+
+        let ix = take_ix_with(&taker.pubkey(), &maker, &mint_a, &mint_b, &escrow, &vault, token_program);
+        let tx = send(&mut program, ix, &taker).unwrap();
+
+        // The treasury gets 2.5% of the vault, the taker the rest; the maker is paid in full
+        let ata = |wallet, mint| associated_token::get_associated_token_address_with_program_id(wallet, mint, token_program);
+        assert_eq!(token_balance(&program, &ata(&TREASURY, &mint_a)), 25);
+        assert_eq!(token_balance(&program, &ata(&taker.pubkey(), &mint_a)), 975);
+        assert_eq!(token_balance(&program, &ata(&maker, &mint_b)), 50);
+        assert!(program.get_account(&vault).map_or(true, |acc| acc.lamports == 0));
+
+        assert_eq!(
+            EscrowEvent::from_logs(&tx.logs),
+            [EscrowEvent::Taken(EscrowTaken {
+                escrow,
+                maker,
+                taker: taker.pubkey(),
+                mint_a,
+                mint_b,
+                deposit: 975,
+                receive: 50,
+                fee: 25,
+            })]
+        );
+    }
+
+    #[test]
+    fn test_take_with_fee() {
+        take_with_fee(&TOKEN_PROGRAM_ID);
+    }
+
+    #[test]
+    fn test_take_with_fee_token_2022() {
+        take_with_fee(&spl_token_2022::ID);
+    }
+
+    #[test]
+    fn test_basket_take() {
+        let (mut program, payer) = setup();
+        let maker = payer.pubkey();
+        let taker = Keypair::new();
+        let (deposits, requests) = bundle_basket(&mut program, &payer, &taker);
+        let basket = basket_pda(&maker, 1);
+
+        // Every deposit lands in its own vault
+        let ix = make_basket_ix(&program, &maker, 1, &deposits, &requests);
+        let tx = send(&mut program, ix, &payer).unwrap();
+        for leg in &deposits {
+            assert_eq!(token_balance(&program, &ata(&basket, &leg.mint)), leg.amount);
+            assert_eq!(token_balance(&program, &ata(&maker, &leg.mint)), 0);
+        }
+        assert_eq!(
+            EscrowEvent::from_logs(&tx.logs),
+            [EscrowEvent::BasketMade(BasketMade {
+                basket,
+                maker,
+                seed: 1,
+                deposits: deposits.clone(),
+                requests: requests.clone(),
+            })]
+        );
+
+        let basket_account = program.get_account(&basket).unwrap();
+        let basket_data = crate::state::BasketEscrow::try_deserialize(&mut basket_account.data.as_ref()).unwrap();
+        assert_eq!(basket_data.deposits, deposits);
+        assert_eq!(basket_data.requests, requests);
+
+        // Take settles every leg at once; the maker's request ATAs are created on the fly
+        let ix = take_basket_ix(&program, &taker.pubkey(), &maker, 1, &deposits, &requests);
+        let tx = send(&mut program, ix, &taker).unwrap();
+        for leg in &deposits {
+            assert_eq!(token_balance(&program, &ata(&taker.pubkey(), &leg.mint)), leg.amount);
+            assert!(program.get_account(&ata(&basket, &leg.mint)).map_or(true, |acc| acc.lamports == 0));
+        }
+        for leg in &requests {
+            assert_eq!(token_balance(&program, &ata(&maker, &leg.mint)), leg.amount);
+        }
+        assert!(program.get_account(&basket).map_or(true, |acc| acc.lamports == 0));
+        assert_eq!(
+            EscrowEvent::from_logs(&tx.logs),
+            [EscrowEvent::BasketTaken(BasketTaken { basket, maker, taker: taker.pubkey(), fees: vec![0, 0] })]
+        );
+    }
+
+    #[test]
+    fn test_basket_refund() {
+        let (mut program, payer) = setup();
+        let maker = payer.pubkey();
+        let taker = Keypair::new();
+        let (deposits, requests) = bundle_basket(&mut program, &payer, &taker);
+        let basket = basket_pda(&maker, 2);
+
+        let ix = make_basket_ix(&program, &maker, 2, &deposits, &requests);
+        send(&mut program, ix, &payer).unwrap();
+
+        // Refund returns every vault to the maker and closes it
+        let ix = refund_basket_ix(&program, &maker, 2, &deposits);
+        let tx = send(&mut program, ix, &payer).unwrap();
+        for leg in &deposits {
+            assert_eq!(token_balance(&program, &ata(&maker, &leg.mint)), leg.amount);
+            assert!(program.get_account(&ata(&basket, &leg.mint)).map_or(true, |acc| acc.lamports == 0));
+        }
+        assert!(program.get_account(&basket).map_or(true, |acc| acc.lamports == 0));
+        assert_eq!(
+            EscrowEvent::from_logs(&tx.logs),
+            [EscrowEvent::BasketRefunded(BasketRefunded { basket, maker })]
+        );
+    }
+
+    #[test]
+    fn test_basket_rejects_bad_legs() {
+        let (mut program, payer) = setup();
+        let maker = payer.pubkey();
+        let taker = Keypair::new();
+        let (deposits, requests) = bundle_basket(&mut program, &payer, &taker);
+
+        // A mint listed twice, an empty side or a zero amount is rejected
+        let twice = [deposits[0], deposits[0]];
+        let zero = [BasketLeg { amount: 0, ..requests[0] }];
+        for (deposits, requests) in [(&twice[..], &requests[..]), (&deposits[..], &[][..]), (&deposits[..], &zero[..])] {
+            let ix = make_basket_ix(&program, &maker, 3, deposits, requests);
+            assert_failed_with(send(&mut program, ix, &payer), "InvalidBasket");
+        }
+
+        let ix = make_basket_ix(&program, &maker, 3, &deposits, &requests);
+        send(&mut program, ix, &payer).unwrap();
+
+        // Missing a leg's accounts
+        let mut ix = take_basket_ix(&program, &taker.pubkey(), &maker, 3, &deposits, &requests);
+        ix.accounts.truncate(ix.accounts.len() - 4);
+        assert_failed_with(send(&mut program, ix, &taker), "BasketAccountsMismatch");
+
+        // Legs out of order
+        let reversed: Vec<_> = deposits.iter().rev().copied().collect();
+        let ix = take_basket_ix(&program, &taker.pubkey(), &maker, 3, &reversed, &requests);
+        assert_failed_with(send(&mut program, ix, &taker), "BasketAccountsMismatch");
+        assert!(program.get_account(&basket_pda(&maker, 3)).is_some(), "Basket should still exist");
+    }
+
+    #[test]
+    fn test_basket_mixed_token_programs_with_fee() {
+        let (mut program, payer) = setup();
+        let maker = payer.pubkey();
+
+        let data = crate::instruction::UpdateConfig { fee_bps: 250, fee_recipient: TREASURY }.data();
+        send(&mut program, update_config_ix(&maker, data), &payer).unwrap();
+
+        // An SPL Token deposit and a 1% transfer-fee Token-2022 deposit, for a Token-2022 request
+        let token = mint_to_ata(&mut program, &payer, &maker, 6, 1000);
+        let fee_token = create_fee_mint(&mut program, &payer, 100);
+        fund_taker(&mut program, &payer, &payer, &fee_token, 1000);
+        let wanted = create_fee_mint(&mut program, &payer, 100);
+        let taker = Keypair::new();
+        fund_taker(&mut program, &payer, &taker, &wanted, 1000);
+
+        let deposits = [BasketLeg { mint: token, amount: 1000 }, BasketLeg { mint: fee_token, amount: 1000 }];
+        let requests = [BasketLeg { mint: wanted, amount: 100 }];
+        let basket = basket_pda(&maker, 4);
+
+        let ix = make_basket_ix(&program, &maker, 4, &deposits, &requests);
+        send(&mut program, ix, &payer).unwrap();
+        assert_eq!(token_balance(&program, &leg_ata(&program, &basket, &fee_token)), 990);
+
+        let ix = take_basket_ix(&program, &taker.pubkey(), &maker, 4, &deposits, &requests);
+        let tx = send(&mut program, ix, &taker).unwrap();
+        let balance = |wallet: &Pubkey, mint: &Pubkey| token_balance(&program, &leg_ata(&program, wallet, mint));
+
+        // The maker nets the full request despite the transfer fee
+        assert_eq!(balance(&maker, &wanted), 100);
+
+        // 2.5% of each vault goes to the treasury: 25 of 1000, and 24 of 990 (23 after its 1% fee)
+        assert_eq!(balance(&TREASURY, &token), 25);
+        assert_eq!(balance(&taker.pubkey(), &token), 975);
+        assert_eq!(balance(&TREASURY, &fee_token), 23);
+        assert_eq!(balance(&taker.pubkey(), &fee_token), 966 - 10);
+
+        // Both vaults close; the fee withheld on the deposit is harvested to the mint
+        for mint in [token, fee_token] {
+            assert!(program.get_account(&leg_ata(&program, &basket, &mint)).map_or(true, |acc| acc.lamports == 0));
+        }
+        assert_eq!(withheld_in_mint(&program, &fee_token), 10);
+        assert!(program.get_account(&basket).map_or(true, |acc| acc.lamports == 0));
+        assert_eq!(
+            EscrowEvent::from_logs(&tx.logs),
+            [EscrowEvent::BasketTaken(BasketTaken { basket, maker, taker: taker.pubkey(), fees: vec![25, 24] })]
+        );
+    }
+
+    #[test]
+    fn test_take_with_transfer_fees() {
+        let (mut program, payer) = setup();
+        let maker = payer.pubkey();
+        let token_2022 = spl_token_2022::ID;
+
+        // Mint A withholds 1% per transfer, Mint B 5%
+        let mint_a = create_fee_mint(&mut program, &payer, 100);
+        let mint_b = create_fee_mint(&mut program, &payer, 500);
+
+        // The vault only gets 990 of the 1000 deposited, and the escrow records that
+        let (result, escrow, vault) = try_make_escrow_for(&mut program, &payer, make_args(555, 1000, 200), &mint_a, &mint_b);
+        let logs = result.unwrap().logs;
+        assert_eq!(token_balance(&program, &vault), 990);
+
+        let escrow_account = program.get_account(&escrow).unwrap();
+        let escrow_data = crate::state::Escrow::try_deserialize(&mut escrow_account.data.as_ref()).unwrap();
+        assert_eq!(escrow_data.deposit, 990);
+        let Some(EscrowEvent::Made(made)) = EscrowEvent::from_logs(&logs).pop() else {
+            panic!("Make should log EscrowMade");
+        };
+        assert_eq!(made.deposit, 990);
+
+        // The taker pays 211 of Mint B so that the maker nets exactly 200 after the 5% fee
+        let taker = Keypair::new();
+        fund_taker(&mut program, &payer, &taker, &mint_b, 1000);
+        let ix = take_ix_with(&taker.pubkey(), &maker, &mint_a, &mint_b, &escrow, &vault, &token_2022);
+        send(&mut program, ix, &taker).unwrap();
+
+        let ata = |wallet, mint| associated_token::get_associated_token_address_with_program_id(wallet, mint, &token_2022);
+        assert_eq!(token_balance(&program, &ata(&maker, &mint_b)), 200);
+        assert_eq!(token_balance(&program, &ata(&taker.pubkey(), &mint_b)), 1000 - 211);
+
+        // The whole vault goes out; Mint A's fee is withheld from what the taker receives
+        assert_eq!(token_balance(&program, &ata(&taker.pubkey(), &mint_a)), 990 - 10);
+
+        // The 10 withheld on the deposit are harvested to the mint, so the vault closes
+        assert!(program.get_account(&vault).map_or(true, |acc| acc.lamports == 0), "Vault should be closed");
+        assert!(program.get_account(&escrow).map_or(true, |acc| acc.lamports == 0), "Escrow should be closed");
+        assert_eq!(withheld_in_mint(&program, &mint_a), 10);
+    }
+
+    #[test]
+    fn test_refund_with_transfer_fee() {
+        let (mut program, payer) = setup();
+        let maker = payer.pubkey();
+        let token_2022 = spl_token_2022::ID;
+
+        let mint_a = create_fee_mint(&mut program, &payer, 100);
+        let mint_b = create_fee_mint(&mut program, &payer, 100);
+        let (result, escrow, vault) = try_make_escrow_for(&mut program, &payer, make_args(556, 1000, 200), &mint_a, &mint_b);
+        result.unwrap();
+
+        // Refund returns the vault's actual balance, less the fee on the way back
+        send(&mut program, refund_ix_with(&maker, &mint_a, &escrow, &vault, &token_2022), &payer).unwrap();
+        let maker_ata_a = associated_token::get_associated_token_address_with_program_id(&maker, &mint_a, &token_2022);
+        assert_eq!(token_balance(&program, &maker_ata_a), 990 - 10);
+
+        // The fee withheld on the deposit is harvested to the mint, so the vault closes
+        assert!(program.get_account(&vault).map_or(true, |acc| acc.lamports == 0), "Vault should be closed");
+        assert!(program.get_account(&escrow).map_or(true, |acc| acc.lamports == 0), "Escrow should be closed");
+        assert_eq!(withheld_in_mint(&program, &mint_a), 10);
+    }
+
+    // Helper: transfer fees harvested into a Token-2022 mint
+    fn withheld_in_mint(program: &LiteSVM, mint: &Pubkey) -> u64 {
+        use spl_token_2022::extension::{transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions};
+
+        let account = program.get_account(mint).unwrap();
+        let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&account.data).unwrap();
+        mint.get_extension::<TransferFeeConfig>().unwrap().withheld_amount.into()
+    }
+
+    // Helper: makes an escrow of 100 Mint A priced by `auction` from 1000 Mint B at `start`,
+    // then takes it `elapsed` seconds later with the given max price
+    fn take_auction_at(
+        program: &mut LiteSVM,
+        payer: &Keypair,
+        seed: u64,
+        start: i64,
+        elapsed: i64,
+        max_price: Option<u64>,
+        auction: DutchAuction,
+    ) -> TransactionResult {
+        let maker = payer.pubkey();
+        warp_to(program, start);
+        let args = crate::instruction::Make {
+            auction: Some(auction),
+            ..make_args(seed, 100, 1000)
+        };
+        let (mint_a, mint_b, escrow, vault, _) = make_escrow(program, payer, args);
+        let taker = Keypair::new();
+        fund_taker(program, payer, &taker, &mint_b, 1000);
+
+        warp_to(program, start + elapsed);
+        let mut ix = take_ix(&taker.pubkey(), &maker, &mint_a, &mint_b, &escrow, &vault);
+        ix.data = crate::instruction::Take { max_price }.data();
+        send(program, ix, &taker)
+    }
+
+    // Helper: the EscrowTaken event in a successful take's logs
+    fn taken_event(result: TransactionResult) -> EscrowTaken {
+        match EscrowEvent::from_logs(&result.unwrap().logs).pop() {
+            Some(EscrowEvent::Taken(taken)) => taken,
+            other => panic!("Take should log EscrowTaken, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_dutch_auction_linear() {
+        let (mut program, payer) = setup();
+        let now = program.get_sysvar::<Clock>().unix_timestamp;
+        let linear = DutchAuction { floor: 200, end_at: now + 800, steps: 0 };
+
+        // Halfway through, the price has fallen from 1000 to 600
+        let result = take_auction_at(&mut program, &payer, 1, now, 400, Some(599), linear);
+        assert_failed_with(result, "PriceAboveMax");
+        let taken = taken_event(take_auction_at(&mut program, &payer, 2, now, 400, Some(600), linear));
+        assert_eq!(taken.receive, 600);
+        assert_eq!(token_balance(&program, &ata(&payer.pubkey(), &taken.mint_b)), 600);
+
+        // Past the end it stays at the floor
+        let taken = taken_event(take_auction_at(&mut program, &payer, 3, now, 10_000, None, linear));
+        assert_eq!(taken.receive, 200);
+    }
+
+    #[test]
+    fn test_dutch_auction_steps() {
+        let (mut program, payer) = setup();
+        let now = program.get_sysvar::<Clock>().unix_timestamp;
+        let steps = DutchAuction { floor: 200, end_at: now + 800, steps: 4 };
+
+        // Four drops of 200, one every 200 seconds: 1000, 800, 600, 400, then the floor
+        for (seed, (elapsed, price)) in [(0, 1000), (199, 1000), (200, 800), (599, 600), (799, 400), (800, 200)].into_iter().enumerate() {
+            let taken = taken_event(take_auction_at(&mut program, &payer, seed as u64, now, elapsed, Some(price), steps));
+            assert_eq!(taken.receive, price, "price {} seconds in", elapsed);
+        }
+    }
+
+    #[test]
+    fn test_make_rejects_invalid_auction() {
+        let (mut program, payer) = setup();
+        let now = program.get_sysvar::<Clock>().unix_timestamp;
+
+        // A floor above the start price
+        let args = crate::instruction::Make {
+            auction: Some(DutchAuction { floor: 1001, end_at: now + 800, steps: 0 }),
+            ..make_args(1, 100, 1000)
+        };
+        let (result, ..) = try_make_escrow(&mut program, &payer, args, &TOKEN_PROGRAM_ID);
+        assert_failed_with(result, "InvalidAuction");
+
+        // An end that has already passed
+        let args = crate::instruction::Make {
+            auction: Some(DutchAuction { floor: 200, end_at: now, steps: 0 }),
+            ..make_args(2, 100, 1000)
+        };
+        let (result, ..) = try_make_escrow(&mut program, &payer, args, &TOKEN_PROGRAM_ID);
+        assert_failed_with(result, "InvalidAuction");
+    }
+
+    #[test]
+    fn test_counter_accept() {
+        let (mut program, payer) = setup();
+        let maker = payer.pubkey();
+        let (mint_a, mint_b, escrow, vault, _) = make_escrow(&mut program, &payer, make_args(777, 100, 50));
+
+        // The taker offers 40 instead of 50, locked in the counter's vault
+        let taker = Keypair::new();
+        fund_taker(&mut program, &payer, &taker, &mint_b, 1000);
+        let counter = counter_pda(&escrow, &taker.pubkey());
+        let tx = send(&mut program, make_counter_ix(&taker.pubkey(), &mint_b, &escrow, 40), &taker).unwrap();
+        assert_eq!(token_balance(&program, &ata(&counter, &mint_b)), 40);
+        assert_eq!(token_balance(&program, &ata(&taker.pubkey(), &mint_b)), 960);
+        assert_eq!(
+            EscrowEvent::from_logs(&tx.logs),
+            [EscrowEvent::CounterMade(CounterMade { counter, escrow, taker: taker.pubkey(), amount: 40 })]
+        );
+
+        let counter_account = program.get_account(&counter).unwrap();
+        let counter_data = crate::state::CounterOffer::try_deserialize(&mut counter_account.data.as_ref()).unwrap();
+        assert_eq!(counter_data.escrow, escrow);
+        assert_eq!(counter_data.taker, taker.pubkey());
+        assert_eq!(counter_data.amount, 40);
+
+        // Only the maker can accept
+        let ix = accept_counter_ix(&taker.pubkey(), &taker.pubkey(), &mint_a, &mint_b, &escrow, &vault, 40);
+        assert!(send(&mut program, ix, &taker).is_err(), "Only the maker should accept");
+
+        // Accepting settles both sides and closes everything
+        let ix = accept_counter_ix(&maker, &taker.pubkey(), &mint_a, &mint_b, &escrow, &vault, 40);
+        let tx = send(&mut program, ix, &payer).unwrap();
+        assert_eq!(token_balance(&program, &ata(&maker, &mint_b)), 40);
+        assert_eq!(token_balance(&program, &ata(&taker.pubkey(), &mint_a)), 100);
+        for account in [escrow, vault, counter, ata(&counter, &mint_b)] {
+            assert!(program.get_account(&account).map_or(true, |acc| acc.lamports == 0));
+        }
+        assert_eq!(
+            EscrowEvent::from_logs(&tx.logs),
+            [EscrowEvent::CounterAccepted(CounterAccepted {
+                counter,
+                escrow,
+                maker,
+                taker: taker.pubkey(),
+                deposit: 100,
+                receive: 40,
+                fee: 0,
+            })]
+        );
+    }
+
+    #[test]
+    fn test_counter_withdraw() {
+        let (mut program, payer) = setup();
+        let maker = payer.pubkey();
+        let (mint_a, mint_b, escrow, vault, _) = make_escrow(&mut program, &payer, make_args(778, 100, 50));
+
+        let taker = Keypair::new();
+        fund_taker(&mut program, &payer, &taker, &mint_b, 1000);
+        let counter = counter_pda(&escrow, &taker.pubkey());
+        send(&mut program, make_counter_ix(&taker.pubkey(), &mint_b, &escrow, 40), &taker).unwrap();
+
+        // The taker pulls the counter back; the escrow stays open
+        let tx = send(&mut program, withdraw_counter_ix(&taker.pubkey(), &mint_b, &escrow), &taker).unwrap();
+        assert_eq!(token_balance(&program, &ata(&taker.pubkey(), &mint_b)), 1000);
+        assert!(program.get_account(&counter).map_or(true, |acc| acc.lamports == 0));
+        assert!(program.get_account(&escrow).is_some(), "Escrow should still exist");
+        assert_eq!(
+            EscrowEvent::from_logs(&tx.logs),
+            [EscrowEvent::CounterWithdrawn(CounterWithdrawn { counter, escrow, taker: taker.pubkey(), amount: 40 })]
+        );
+
+        // A withdrawn counter can no longer be accepted
+        let ix = accept_counter_ix(&maker, &taker.pubkey(), &mint_a, &mint_b, &escrow, &vault, 40);
+        assert!(send(&mut program, ix, &payer).is_err(), "Withdrawn counter should not be accepted");
+    }
+
+    #[test]
+    fn test_counter_accept_rejects_lowered_counter() {
+        let (mut program, payer) = setup();
+        let maker = payer.pubkey();
+        let (mint_a, mint_b, escrow, vault, _) = make_escrow(&mut program, &payer, make_args(781, 100, 50));
+
+        let taker = Keypair::new();
+        fund_taker(&mut program, &payer, &taker, &mint_b, 1000);
+        send(&mut program, make_counter_ix(&taker.pubkey(), &mint_b, &escrow, 40), &taker).unwrap();
+
+        // The maker decides to accept 40, but the taker re-posts 10 at the same PDA first
+        let stale_accept = accept_counter_ix(&maker, &taker.pubkey(), &mint_a, &mint_b, &escrow, &vault, 40);
+        send(&mut program, withdraw_counter_ix(&taker.pubkey(), &mint_b, &escrow), &taker).unwrap();
+        program.expire_blockhash();
+        send(&mut program, make_counter_ix(&taker.pubkey(), &mint_b, &escrow, 10), &taker).unwrap();
+
+        assert_failed_with(send(&mut program, stale_accept, &payer), "CounterBelowAccepted");
+        assert_eq!(token_balance(&program, &vault), 100);
+        assert_eq!(token_balance(&program, &ata(&counter_pda(&escrow, &taker.pubkey()), &mint_b)), 10);
+
+        // Accepting the amount actually on offer still works
+        let ix = accept_counter_ix(&maker, &taker.pubkey(), &mint_a, &mint_b, &escrow, &vault, 10);
+        send(&mut program, ix, &payer).unwrap();
+        assert_eq!(token_balance(&program, &ata(&maker, &mint_b)), 10);
+        assert_eq!(token_balance(&program, &ata(&taker.pubkey(), &mint_a)), 100);
+    }
+
+    #[test]
+    fn test_counter_rejects_invalid() {
+        let (mut program, payer) = setup();
+
+        // Zero amounts are rejected
+        let (_, mint_b, escrow, _, _) = make_escrow(&mut program, &payer, make_args(779, 100, 50));
+        let taker = Keypair::new();
+        fund_taker(&mut program, &payer, &taker, &mint_b, 1000);
+        let ix = make_counter_ix(&taker.pubkey(), &mint_b, &escrow, 0);
+        assert_failed_with(send(&mut program, ix, &taker), "InvalidCounter");
+
+        // A reserved escrow only takes counters from its taker
+        let (_, mint_b, escrow, _, _) = make_escrow(&mut program, &payer, crate::instruction::Make {
+            taker: Some(Keypair::new().pubkey()),
+            ..make_args(780, 100, 50)
+        });
+        fund_taker(&mut program, &payer, &taker, &mint_b, 1000);
+        let ix = make_counter_ix(&taker.pubkey(), &mint_b, &escrow, 40);
+        assert_failed_with(send(&mut program, ix, &taker), "TakerNotAllowed");
+    }
+
+}
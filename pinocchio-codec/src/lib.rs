@@ -0,0 +1,196 @@
+//! Bounds-checked little-endian decoding for Pinocchio instruction data.
+//!
+//! Replaces unaligned `*(data.as_ptr().add(n) as *const u64)` reads: every
+//! read checks the remaining length first and fails with
+//! `ProgramError::InvalidInstructionData` instead of reading past the buffer.
+//! Nothing is allocated; values are copied straight out of the input slice.
+#![no_std]
+
+use pinocchio::error::ProgramError;
+
+/// A fixed-size value decoded from the front of a byte slice.
+pub trait FromBytes: Sized {
+    /// Number of bytes the encoding occupies; `read` must consume exactly this.
+    const LEN: usize;
+
+    fn read(reader: &mut Reader) -> Result<Self, ProgramError>;
+
+    /// Decodes `Self` from the start of `data`, rejecting anything shorter than
+    /// `LEN` up front. Trailing bytes are ignored.
+    fn from_bytes(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() < Self::LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let mut reader = Reader::new(data);
+        let value = Self::read(&mut reader)?;
+        debug_assert_eq!(data.len() - reader.remaining(), Self::LEN, "LEN doesn't match read");
+        Ok(value)
+    }
+}
+
+/// Cursor over instruction data.
+pub struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Takes the next `N` bytes.
+    pub fn read_array<const N: usize>(&mut self) -> Result<[u8; N], ProgramError> {
+        let (head, rest) = self
+            .data
+            .split_first_chunk::<N>()
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        self.data = rest;
+        Ok(*head)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, ProgramError> {
+        self.read_array::<1>().map(|[b]| b)
+    }
+
+    pub fn read_u16(&mut self) -> Result<u16, ProgramError> {
+        self.read_array().map(u16::from_le_bytes)
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, ProgramError> {
+        self.read_array().map(u64::from_le_bytes)
+    }
+
+    pub fn read_i64(&mut self) -> Result<i64, ProgramError> {
+        self.read_array().map(i64::from_le_bytes)
+    }
+
+    pub fn read<T: FromBytes>(&mut self) -> Result<T, ProgramError> {
+        T::read(self)
+    }
+}
+
+impl FromBytes for u8 {
+    const LEN: usize = 1;
+
+    fn read(reader: &mut Reader) -> Result<Self, ProgramError> {
+        reader.read_u8()
+    }
+}
+
+impl FromBytes for u16 {
+    const LEN: usize = 2;
+
+    fn read(reader: &mut Reader) -> Result<Self, ProgramError> {
+        reader.read_u16()
+    }
+}
+
+impl FromBytes for u64 {
+    const LEN: usize = 8;
+
+    fn read(reader: &mut Reader) -> Result<Self, ProgramError> {
+        reader.read_u64()
+    }
+}
+
+impl FromBytes for i64 {
+    const LEN: usize = 8;
+
+    fn read(reader: &mut Reader) -> Result<Self, ProgramError> {
+        reader.read_i64()
+    }
+}
+
+impl<const N: usize> FromBytes for [u8; N] {
+    const LEN: usize = N;
+
+    fn read(reader: &mut Reader) -> Result<Self, ProgramError> {
+        reader.read_array()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Pair {
+        tag: u8,
+        value: u64,
+    }
+
+    impl FromBytes for Pair {
+        const LEN: usize = 9;
+
+        fn read(reader: &mut Reader) -> Result<Self, ProgramError> {
+            Ok(Self {
+                tag: reader.read()?,
+                value: reader.read()?,
+            })
+        }
+    }
+
+    #[test]
+    fn reads_little_endian_fields_in_order() {
+        let mut data = [0u8; 9];
+        data[0] = 7;
+        data[1..].copy_from_slice(&0x0102_0304_0506_0708u64.to_le_bytes());
+
+        let pair = Pair::from_bytes(&data).unwrap();
+        assert_eq!(pair.tag, 7);
+        assert_eq!(pair.value, 0x0102_0304_0506_0708);
+    }
+
+    #[test]
+    fn short_input_is_rejected() {
+        assert_eq!(
+            Pair::from_bytes(&[7, 1, 2, 3]).err(),
+            Some(ProgramError::InvalidInstructionData)
+        );
+        assert_eq!(u64::from_bytes(&[]).err(), Some(ProgramError::InvalidInstructionData));
+    }
+
+    #[test]
+    #[should_panic(expected = "LEN doesn't match read")]
+    fn mismatched_len_is_caught() {
+        struct Short;
+
+        impl FromBytes for Short {
+            const LEN: usize = 2;
+
+            fn read(reader: &mut Reader) -> Result<Self, ProgramError> {
+                reader.read_u8().map(|_| Short)
+            }
+        }
+
+        let _ = Short::from_bytes(&[1, 2]);
+    }
+
+    #[test]
+    fn reads_at_unaligned_offsets() {
+        let mut data = [0u8; 17];
+        data[1..9].copy_from_slice(&(-5i64).to_le_bytes());
+        data[9..].copy_from_slice(&u64::MAX.to_le_bytes());
+
+        let mut reader = Reader::new(&data);
+        assert_eq!(reader.read_u8().unwrap(), 0);
+        assert_eq!(reader.read_i64().unwrap(), -5);
+        assert_eq!(reader.read_u64().unwrap(), u64::MAX);
+        assert_eq!(reader.remaining(), 0);
+    }
+
+    #[test]
+    fn reads_u16() {
+        let mut data = [0u8; 3];
+        data[1..].copy_from_slice(&0xBEEFu16.to_le_bytes());
+
+        let mut reader = Reader::new(&data);
+        assert_eq!(reader.read_u8().unwrap(), 0);
+        assert_eq!(reader.read_u16().unwrap(), 0xBEEF);
+        assert_eq!(reader.remaining(), 0);
+        assert_eq!(u16::from_bytes(&[0xEF]).err(), Some(ProgramError::InvalidInstructionData));
+    }
+}
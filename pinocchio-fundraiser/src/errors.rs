@@ -0,0 +1,79 @@
+use pinocchio::error::ProgramError;
+
+/// Fundraiser failures, surfaced to clients as `ProgramError::Custom(code)`.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FundraiserError {
+    /// Fundraiser address does not match `["fundraiser", maker, bump]`
+    InvalidFundraiserPda = 0,
+    /// Contributor state address does not match `["contributor", fundraiser, contributor, bump]`
+    InvalidContributorPda = 1,
+    /// Fundraiser or contributor account has the wrong size or alignment
+    InvalidStateData = 2,
+    /// mint_to_raise is not a valid mint
+    InvalidMint = 3,
+    /// Vault is not a token account for mint_to_raise owned by the fundraiser
+    InvalidVault = 4,
+    /// Maker account does not match the fundraiser's stored maker
+    MakerMismatch = 5,
+    /// amount_to_raise is not above the minimum for the mint's decimals
+    TargetTooSmall = 6,
+    /// Contribute was called with an amount of zero
+    ZeroContribution = 7,
+    /// A single contribution exceeds the per-contributor cap
+    ContributionTooLarge = 8,
+    /// This contribution would push the contributor's total over the cap
+    ContributorCapExceeded = 9,
+    /// Contribute after the fundraising period has ended
+    FundraiserEnded = 10,
+    /// Refund before the fundraising period has ended
+    FundraiserNotEnded = 11,
+    /// Checker called before the vault holds the target amount
+    TargetNotMet = 12,
+    /// Refund requested although the target was met
+    TargetMet = 13,
+    /// Initialize with a funding mode other than 0 or 1
+    InvalidMode = 14,
+    /// A stretch goal must be above the target (0 for none)
+    InvalidStretchGoal = 15,
+    /// Refund on a keep-what-you-raise fundraiser
+    RefundsDisabled = 16,
+    /// Release schedule is unknown or its tranches/vesting duration don't fit it
+    InvalidReleaseSchedule = 17,
+    /// Checker called on a fundraiser that already settled
+    AlreadySettled = 18,
+    /// Release or vote before checker settled the fundraiser
+    NotSettled = 19,
+    /// Release with no newly unlocked proceeds
+    NothingToRelease = 20,
+    /// Vote on a fundraiser without a milestone tranche awaiting approval
+    NoPendingTranche = 21,
+    /// The contributor already voted for the pending tranche
+    AlreadyVoted = 22,
+    /// Contribution cap, minimum contribution or hard cap are inconsistent
+    InvalidContributionLimits = 23,
+    /// A single contribution is below the fundraiser's minimum
+    ContributionTooSmall = 24,
+    /// This contribution would push the total raised over the hard cap
+    HardCapExceeded = 25,
+    /// end_time is not after both start_time and the current time
+    InvalidCampaignWindow = 26,
+    /// Contribute before the fundraiser's start_time
+    FundraiserNotStarted = 27,
+    /// Withdraw penalty above 10_000 basis points
+    InvalidWithdrawPenalty = 28,
+    /// Withdraw of zero or more than the contributor's amount
+    InvalidWithdrawAmount = 29,
+    /// maker_ata is not a token account owned by the maker
+    InvalidMakerAta = 30,
+    /// Refund of unapproved milestone tranches before voting closes
+    VotingOpen = 31,
+    /// Vote on a milestone tranche after voting closed
+    VotingClosed = 32,
+}
+
+impl From<FundraiserError> for ProgramError {
+    fn from(e: FundraiserError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
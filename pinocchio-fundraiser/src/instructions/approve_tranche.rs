@@ -0,0 +1,66 @@
+use pinocchio::{
+    AccountView, ProgramResult,
+    error::ProgramError,
+    sysvars::{Sysvar, clock::Clock},
+};
+
+use crate::errors::FundraiserError;
+use crate::states::{Fundraiser, Contributor};
+
+/// Vote to approve the pending milestone tranche, weighted by the contributor's
+/// amount. The tranche unlocks once votes pass half of the amount raised;
+/// voting closes `vote_period` seconds after settlement
+/// Accounts: [contributor(s), fundraiser, contributor_state]
+/// Data: (none)
+pub fn process_approve_tranche(
+    accounts: &[AccountView],
+    _data: &[u8],
+) -> ProgramResult {
+    let [
+        contributor,
+        fundraiser,
+        contributor_state,
+        ..
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !contributor.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Load fundraiser state
+    let fund_state = Fundraiser::from_account_info(fundraiser)?;
+
+    if !fund_state.is_settled() {
+        return Err(FundraiserError::NotSettled.into());
+    }
+    if !fund_state.has_pending_tranche() {
+        return Err(FundraiserError::NoPendingTranche.into());
+    }
+    if fund_state.voting_closed(Clock::get()?.unix_timestamp) {
+        return Err(FundraiserError::VotingClosed.into());
+    }
+
+    // Load contributor state and verify it belongs to this fundraiser
+    let contrib_state = Contributor::from_account_info(contributor_state)?;
+    contrib_state.verify_address(contributor_state, fundraiser, contributor)?;
+
+    // One vote per contributor per tranche
+    let pending = fund_state.tranches_approved + 1;
+    if contrib_state.last_vote >= pending {
+        return Err(FundraiserError::AlreadyVoted.into());
+    }
+    contrib_state.last_vote = pending;
+
+    // Approve on a strict majority of the contributed amount
+    let votes = fund_state.votes() + contrib_state.amount();
+    if votes as u128 * 2 > fund_state.current_amount() as u128 {
+        fund_state.tranches_approved = pending;
+        fund_state.set_votes(0);
+    } else {
+        fund_state.set_votes(votes);
+    }
+
+    Ok(())
+}
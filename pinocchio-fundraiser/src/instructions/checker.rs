@@ -2,11 +2,18 @@ use pinocchio::{
     AccountView, ProgramResult,
     cpi::{Seed, Signer},
     error::ProgramError,
+    sysvars::{Sysvar, clock::Clock},
 };
 
-use crate::states::Fundraiser;
+use crate::errors::FundraiserError;
+use crate::states::{Fundraiser, ReleaseSchedule};
 
-/// Check if fundraiser target is met, transfer vault to maker, close fundraiser
+/// Check if the fundraiser can pay out, transfer vault to maker, close fundraiser.
+/// Pays out early once the final goal is reached, otherwise after the end: with
+/// the target met, or regardless in keep-what-you-raise mode. With a vesting or
+/// milestone release schedule the proceeds are only locked in for `Release`.
+/// Once an ended fundraiser holds no contributions (a failed raise fully
+/// refunded), the maker sweeps whatever was donated to the vault and closes it
 /// Accounts: [maker(s), fundraiser, vault, maker_ata, token_program]
 /// Data: (none)
 pub fn process_checker(
@@ -32,24 +39,30 @@ pub fn process_checker(
 
     // Verify maker
     if fund_state.maker() != *maker.address() {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(FundraiserError::MakerMismatch.into());
+    }
+
+    if fund_state.is_settled() {
+        return Err(FundraiserError::AlreadySettled.into());
     }
 
-    let amount_to_raise = fund_state.amount_to_raise();
     let bump = fund_state.bump;
 
-    // Read vault balance (offset 64 in SPL Token account layout)
-    let vault_amount = {
-        let vault_data = vault.try_borrow()?;
-        if vault_data.len() < 72 {
-            return Err(ProgramError::InvalidAccountData);
-        }
-        unsafe { *(vault_data.as_ptr().add(64) as *const u64) }
-    };
+    // The whole vault is paid out, so tokens sent straight to it aren't stranded
+    let vault_amount = fund_state.vault_balance(vault, fundraiser)?;
+
+    // Check the fundraiser can settle on what was contributed; tokens sent
+    // straight to the vault don't count towards the goals
+    let now = Clock::get()?.unix_timestamp;
+    let refunded = fund_state.has_ended(now) && fund_state.current_amount() == 0;
+    if !refunded && !fund_state.can_claim(fund_state.current_amount(), now) {
+        return Err(FundraiserError::TargetNotMet.into());
+    }
 
-    // Check target met
-    if vault_amount < amount_to_raise {
-        return Err(ProgramError::InvalidArgument);
+    // Scheduled releases pay out later through Release
+    if !refunded && fund_state.release() != ReleaseSchedule::Immediate {
+        fund_state.settle(vault_amount, now);
+        return Ok(());
     }
 
     // Build fundraiser PDA signer
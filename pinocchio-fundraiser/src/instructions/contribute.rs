@@ -3,9 +3,10 @@ use pinocchio::{
     error::ProgramError,
     sysvars::{Sysvar, clock::Clock},
 };
+use pinocchio_codec::FromBytes;
 
+use crate::errors::FundraiserError;
 use crate::states::{Fundraiser, Contributor};
-use crate::{MAX_CONTRIBUTION_PERCENTAGE, PERCENTAGE_SCALER, SECONDS_TO_DAYS};
 
 /// Contribute tokens to the fundraiser
 /// Accounts: [contributor(s), fundraiser, vault, contributor_ata, contributor_state, token_program]
@@ -29,39 +30,51 @@ pub fn process_contribute(
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    if data.len() < 8 {
-        return Err(ProgramError::InvalidInstructionData);
-    }
-    let amount = unsafe { *(data.as_ptr() as *const u64) };
+    let amount = u64::from_bytes(data)?;
 
     // Load fundraiser state
     let fund_state = Fundraiser::from_account_info(fundraiser)?;
-    let amount_to_raise = fund_state.amount_to_raise();
-    let max_contribution = (amount_to_raise * MAX_CONTRIBUTION_PERCENTAGE) / PERCENTAGE_SCALER;
+    let max_contribution = fund_state.max_contribution();
 
     // Validate amount > 0
     if amount == 0 {
-        return Err(ProgramError::InvalidArgument);
+        return Err(FundraiserError::ZeroContribution.into());
     }
 
-    // Validate amount <= 10% of target
+    // Validate amount within the fundraiser's per-contribution limits
+    if amount < fund_state.min_contribution() {
+        return Err(FundraiserError::ContributionTooSmall.into());
+    }
     if amount > max_contribution {
-        return Err(ProgramError::InvalidArgument);
+        return Err(FundraiserError::ContributionTooLarge.into());
+    }
+
+    // Check the total raised stays within the hard cap
+    if let Some(hard_cap) = fund_state.hard_cap() {
+        if fund_state.current_amount() + amount > hard_cap {
+            return Err(FundraiserError::HardCapExceeded.into());
+        }
     }
 
-    // Check fundraising duration has NOT been exceeded
-    let current_time = Clock::get()?.unix_timestamp;
-    let days_elapsed = ((current_time - fund_state.time_started()) / SECONDS_TO_DAYS) as u8;
-    if days_elapsed > fund_state.duration {
-        return Err(ProgramError::InvalidArgument);
+    // Check the fundraiser is open: started, not settled and not ended
+    let now = Clock::get()?.unix_timestamp;
+    if !fund_state.has_started(now) {
+        return Err(FundraiserError::FundraiserNotStarted.into());
+    }
+    if fund_state.is_settled() || fund_state.has_ended(now) {
+        return Err(FundraiserError::FundraiserEnded.into());
     }
 
-    // Load contributor state
+    // Contributions must land in the fundraiser's vault
+    fund_state.vault_balance(vault, fundraiser)?;
+
+    // Load contributor state and verify it belongs to this fundraiser
     let contrib_state = Contributor::from_account_info(contributor_state)?;
+    contrib_state.verify_address(contributor_state, fundraiser, contributor)?;
 
     // Check per-contributor cap
     if contrib_state.amount() + amount > max_contribution {
-        return Err(ProgramError::InvalidArgument);
+        return Err(FundraiserError::ContributorCapExceeded.into());
     }
 
     // Transfer tokens from contributor_ata to vault
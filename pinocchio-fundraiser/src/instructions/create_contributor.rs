@@ -4,9 +4,11 @@ use pinocchio::{
     error::ProgramError,
     sysvars::{Sysvar, rent::Rent},
 };
+use pinocchio_codec::FromBytes;
 use pinocchio_pubkey::derive_address;
 use pinocchio_system::instructions::CreateAccount;
 
+use crate::errors::FundraiserError;
 use crate::states::Contributor;
 
 /// Create a contributor state account (separate from contribute to avoid init_if_needed)
@@ -29,10 +31,7 @@ pub fn process_create_contributor(
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    if data.is_empty() {
-        return Err(ProgramError::InvalidInstructionData);
-    }
-    let bump = data[0];
+    let bump = u8::from_bytes(data)?;
 
     // Verify contributor PDA
     let seed = [
@@ -42,7 +41,9 @@ pub fn process_create_contributor(
         &[bump],
     ];
     let contributor_pda = derive_address(&seed, None, &crate::ID.to_bytes());
-    assert_eq!(contributor_pda, *contributor_state.address().as_array());
+    if contributor_pda != *contributor_state.address().as_array() {
+        return Err(FundraiserError::InvalidContributorPda.into());
+    }
 
     // Build signer seeds
     let bump_bytes = [bump];
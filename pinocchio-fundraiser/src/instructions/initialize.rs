@@ -4,15 +4,104 @@ use pinocchio::{
     error::ProgramError,
     sysvars::{Sysvar, clock::Clock, rent::Rent},
 };
+use pinocchio_codec::{FromBytes, Reader};
 use pinocchio_pubkey::derive_address;
 use pinocchio_system::instructions::CreateAccount;
 
-use crate::states::Fundraiser;
-use crate::MIN_AMOUNT_TO_RAISE;
+use crate::errors::FundraiserError;
+use crate::states::{Fundraiser, FundingMode, ReleaseSchedule, MAX_TRANCHES};
+use crate::{BPS_SCALER, MAX_CONTRIBUTION_PERCENTAGE, MIN_AMOUNT_TO_RAISE, PERCENTAGE_SCALER};
+
+/// Initialize payload: [amount_to_raise: u64, start_time: i64, end_time: i64, bump: u8,
+/// mode: u8, stretch_goal: u64, release: u8, tranches: u8, vesting_duration: i64,
+/// cap_percentage: u8, max_contribution: u64, min_contribution: u64, hard_cap: u64,
+/// withdraw_penalty_bps: u16, vote_period: i64]
+pub struct InitializeData {
+    pub amount_to_raise: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub bump: u8,
+    pub mode: u8,
+    pub stretch_goal: u64,
+    pub release: u8,
+    pub tranches: u8,
+    pub vesting_duration: i64,
+    pub cap_percentage: u8,
+    pub max_contribution: u64,
+    pub min_contribution: u64,
+    pub hard_cap: u64,
+    pub withdraw_penalty_bps: u16,
+    pub vote_period: i64,
+}
+
+impl FromBytes for InitializeData {
+    const LEN: usize = 26 + 8 + 10 + 25 + 2 + 8;
+
+    fn read(reader: &mut Reader) -> Result<Self, ProgramError> {
+        Ok(Self {
+            amount_to_raise: reader.read()?,
+            start_time: reader.read()?,
+            end_time: reader.read()?,
+            bump: reader.read()?,
+            mode: reader.read()?,
+            stretch_goal: reader.read()?,
+            release: reader.read()?,
+            tranches: reader.read()?,
+            vesting_duration: reader.read()?,
+            cap_percentage: reader.read()?,
+            max_contribution: reader.read()?,
+            min_contribution: reader.read()?,
+            hard_cap: reader.read()?,
+            withdraw_penalty_bps: reader.read()?,
+            vote_period: reader.read()?,
+        })
+    }
+}
+
+/// Linear vesting needs a positive duration and milestones 1..=MAX_TRANCHES
+/// tranches and a positive vote period; parameters the schedule doesn't use
+/// must be 0.
+fn validate_release(
+    release: ReleaseSchedule,
+    tranches: u8,
+    vesting_duration: i64,
+    vote_period: i64,
+) -> ProgramResult {
+    let valid = match release {
+        ReleaseSchedule::Immediate => tranches == 0 && vesting_duration == 0 && vote_period == 0,
+        ReleaseSchedule::Linear => tranches == 0 && vesting_duration > 0 && vote_period == 0,
+        ReleaseSchedule::Milestones => {
+            (1..=MAX_TRANCHES).contains(&tranches) && vesting_duration == 0 && vote_period > 0
+        }
+    };
+    if !valid {
+        return Err(FundraiserError::InvalidReleaseSchedule.into());
+    }
+    Ok(())
+}
+
+/// Per-contributor cap from either a percentage of the target or an absolute
+/// amount; with neither set it defaults to MAX_CONTRIBUTION_PERCENTAGE.
+fn resolve_max_contribution(
+    amount_to_raise: u64,
+    cap_percentage: u8,
+    max_contribution: u64,
+) -> Result<u64, ProgramError> {
+    let percentage = match (cap_percentage, max_contribution) {
+        (0, 0) => MAX_CONTRIBUTION_PERCENTAGE,
+        (0, cap) => return Ok(cap),
+        (pct, 0) if pct as u64 <= PERCENTAGE_SCALER => pct as u64,
+        _ => return Err(FundraiserError::InvalidContributionLimits.into()),
+    };
+    Ok(((amount_to_raise as u128 * percentage as u128) / PERCENTAGE_SCALER as u128) as u64)
+}
 
 /// Initialize a new fundraiser
 /// Accounts: [maker(s,m), fundraiser, mint_to_raise, system_program]
-/// Data: [amount_to_raise: u64, duration: u8, bump: u8]
+/// Data: [amount_to_raise: u64, start_time: i64, end_time: i64, bump: u8, mode: u8, stretch_goal: u64,
+///        release: u8, tranches: u8, vesting_duration: i64,
+///        cap_percentage: u8, max_contribution: u64, min_contribution: u64, hard_cap: u64,
+///        withdraw_penalty_bps: u16, vote_period: i64]
 pub fn process_initialize(
     accounts: &[AccountView],
     data: &[u8],
@@ -31,30 +120,71 @@ pub fn process_initialize(
     }
 
     // Parse instruction data
-    if data.len() < 10 {
-        return Err(ProgramError::InvalidInstructionData);
-    }
-    let amount_to_raise = unsafe { *(data.as_ptr() as *const u64) };
-    let duration = data[8];
-    let bump = data[9];
+    let InitializeData {
+        amount_to_raise,
+        start_time,
+        end_time,
+        bump,
+        mode,
+        stretch_goal,
+        release,
+        tranches,
+        vesting_duration,
+        cap_percentage,
+        max_contribution,
+        min_contribution,
+        hard_cap,
+        withdraw_penalty_bps,
+        vote_period,
+    } = InitializeData::from_bytes(data)?;
+    let mode = FundingMode::try_from(mode)?;
+    let release = ReleaseSchedule::try_from(release)?;
 
     // Read mint decimals (offset 44 in SPL Token mint layout)
     let mint_data = mint_to_raise.try_borrow()?;
     if mint_data.len() < 45 {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(FundraiserError::InvalidMint.into());
     }
     let decimals = mint_data[44];
 
     // Validate amount > MIN_AMOUNT_TO_RAISE^decimals
     let min_amount = MIN_AMOUNT_TO_RAISE.pow(decimals as u32);
     if amount_to_raise <= min_amount {
-        return Err(ProgramError::InvalidArgument);
+        return Err(FundraiserError::TargetTooSmall.into());
+    }
+
+    // The campaign may start in the future, but must not be over already
+    if end_time <= start_time || end_time <= Clock::get()?.unix_timestamp {
+        return Err(FundraiserError::InvalidCampaignWindow.into());
+    }
+
+    // A stretch goal (0 for none) must lie above the target
+    if stretch_goal != 0 && stretch_goal <= amount_to_raise {
+        return Err(FundraiserError::InvalidStretchGoal.into());
+    }
+    validate_release(release, tranches, vesting_duration, vote_period)?;
+
+    // Contribution limits must leave room to contribute, and a hard cap must
+    // leave the final goal reachable
+    let max_contribution = resolve_max_contribution(amount_to_raise, cap_percentage, max_contribution)?;
+    let final_goal = stretch_goal.max(amount_to_raise);
+    if max_contribution == 0
+        || min_contribution > max_contribution
+        || (hard_cap != 0 && hard_cap < final_goal)
+    {
+        return Err(FundraiserError::InvalidContributionLimits.into());
+    }
+
+    if withdraw_penalty_bps as u64 > BPS_SCALER {
+        return Err(FundraiserError::InvalidWithdrawPenalty.into());
     }
 
     // Verify fundraiser PDA
     let seed = [b"fundraiser".as_ref(), maker.address().as_ref(), &[bump]];
     let fundraiser_pda = derive_address(&seed, None, &crate::ID.to_bytes());
-    assert_eq!(fundraiser_pda, *fundraiser.address().as_array());
+    if fundraiser_pda != *fundraiser.address().as_array() {
+        return Err(FundraiserError::InvalidFundraiserPda.into());
+    }
 
     // Build signer seeds
     let bump_bytes = [bump];
@@ -80,9 +210,19 @@ pub fn process_initialize(
     state.set_mint_to_raise(mint_to_raise.address());
     state.set_amount_to_raise(amount_to_raise);
     state.set_current_amount(0);
-    state.set_time_started(Clock::get()?.unix_timestamp);
-    state.duration = duration;
+    state.set_start_time(start_time);
+    state.set_end_time(end_time);
     state.bump = bump;
+    state.set_mode(mode);
+    state.set_stretch_goal((stretch_goal != 0).then_some(stretch_goal));
+    state.set_release(release);
+    state.tranches = tranches;
+    state.set_vesting_duration(vesting_duration);
+    state.set_vote_period(vote_period);
+    state.set_max_contribution(max_contribution);
+    state.set_min_contribution(min_contribution);
+    state.set_hard_cap((hard_cap != 0).then_some(hard_cap));
+    state.set_withdraw_penalty_bps(withdraw_penalty_bps);
 
     Ok(())
 }
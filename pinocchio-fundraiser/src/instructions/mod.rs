@@ -3,12 +3,18 @@ pub mod create_contributor;
 pub mod contribute;
 pub mod checker;
 pub mod refund;
+pub mod release;
+pub mod approve_tranche;
+pub mod withdraw;
 
 pub use initialize::*;
 pub use create_contributor::*;
 pub use contribute::*;
 pub use checker::*;
 pub use refund::*;
+pub use release::*;
+pub use approve_tranche::*;
+pub use withdraw::*;
 
 use pinocchio::error::ProgramError;
 
@@ -18,6 +24,9 @@ pub enum FundraiserInstruction {
     Contribute = 2,
     Checker = 3,
     Refund = 4,
+    Release = 5,
+    ApproveTranche = 6,
+    Withdraw = 7,
 }
 
 impl TryFrom<&u8> for FundraiserInstruction {
@@ -30,6 +39,9 @@ impl TryFrom<&u8> for FundraiserInstruction {
             2 => Ok(FundraiserInstruction::Contribute),
             3 => Ok(FundraiserInstruction::Checker),
             4 => Ok(FundraiserInstruction::Refund),
+            5 => Ok(FundraiserInstruction::Release),
+            6 => Ok(FundraiserInstruction::ApproveTranche),
+            7 => Ok(FundraiserInstruction::Withdraw),
             _ => Err(ProgramError::InvalidInstructionData),
         }
     }
@@ -5,10 +5,11 @@ use pinocchio::{
     sysvars::{Sysvar, clock::Clock},
 };
 
-use crate::states::{Fundraiser, Contributor};
-use crate::SECONDS_TO_DAYS;
+use crate::errors::FundraiserError;
+use crate::states::{Fundraiser, Contributor, FundingMode};
 
-/// Refund a contributor if fundraiser expired and target not met
+/// Refund a contributor if an all-or-nothing fundraiser expired and target not met,
+/// or their share of the milestone tranches left unapproved when voting closed
 /// Accounts: [contributor(s), maker, fundraiser, vault, contributor_ata, contributor_state, token_program]
 /// Data: (none)
 pub fn process_refund(
@@ -36,31 +37,48 @@ pub fn process_refund(
 
     // Verify maker matches
     if fund_state.maker() != *maker.address() {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(FundraiserError::MakerMismatch.into());
     }
 
-    // Check duration exceeded
-    let current_time = Clock::get()?.unix_timestamp;
-    let days_elapsed = ((current_time - fund_state.time_started()) / SECONDS_TO_DAYS) as u8;
-    if days_elapsed <= fund_state.duration {
-        return Err(ProgramError::InvalidArgument);
-    }
+    let now = Clock::get()?.unix_timestamp;
+    let settled = fund_state.is_settled();
+    if settled {
+        // Settled proceeds belong to the maker's release schedule, except
+        // milestone tranches still unapproved once voting closes
+        if !fund_state.has_pending_tranche() {
+            return Err(FundraiserError::TargetMet.into());
+        }
+        if !fund_state.voting_closed(now) {
+            return Err(FundraiserError::VotingOpen.into());
+        }
+        fund_state.vault_balance(vault, fundraiser)?;
+    } else {
+        // Keep-what-you-raise fundraisers pay out whatever they collect
+        if fund_state.mode() == FundingMode::KeepWhatYouRaise {
+            return Err(FundraiserError::RefundsDisabled.into());
+        }
 
-    // Check target NOT met
-    let vault_amount = {
-        let vault_data = vault.try_borrow()?;
-        if vault_data.len() < 72 {
-            return Err(ProgramError::InvalidAccountData);
+        // Check the fundraiser has ended
+        if !fund_state.has_ended(now) {
+            return Err(FundraiserError::FundraiserNotEnded.into());
+        }
+
+        // Check target NOT met by contributions; tokens sent straight to the
+        // vault don't count
+        fund_state.vault_balance(vault, fundraiser)?;
+        if fund_state.current_amount() >= fund_state.amount_to_raise() {
+            return Err(FundraiserError::TargetMet.into());
         }
-        unsafe { *(vault_data.as_ptr().add(64) as *const u64) }
-    };
-    if vault_amount >= fund_state.amount_to_raise() {
-        return Err(ProgramError::InvalidArgument);
     }
 
-    // Load contributor state
+    // Load contributor state and verify it belongs to the signer
     let contrib_state = Contributor::from_account_info(contributor_state)?;
-    let refund_amount = contrib_state.amount();
+    contrib_state.verify_address(contributor_state, fundraiser, contributor)?;
+    let refund_amount = if settled {
+        fund_state.locked_share(contrib_state.amount())
+    } else {
+        contrib_state.amount()
+    };
     let bump = fund_state.bump;
 
     // Build fundraiser PDA signer
@@ -80,9 +98,12 @@ pub fn process_refund(
         amount: refund_amount,
     }.invoke_signed(&[signer])?;
 
-    // Update fundraiser current_amount
-    let fund_state = Fundraiser::from_account_info(fundraiser)?;
-    fund_state.set_current_amount(fund_state.current_amount() - refund_amount);
+    // Update fundraiser current_amount; after settlement it stays the basis
+    // every locked share is computed from
+    if !settled {
+        let fund_state = Fundraiser::from_account_info(fundraiser)?;
+        fund_state.set_current_amount(fund_state.current_amount() - refund_amount);
+    }
 
     // Close contributor state account — return lamports to contributor
     let contrib_lamports = contributor_state.lamports();
@@ -0,0 +1,88 @@
+use pinocchio::{
+    AccountView, ProgramResult,
+    cpi::{Seed, Signer},
+    error::ProgramError,
+    sysvars::{Sysvar, clock::Clock},
+};
+
+use crate::errors::FundraiserError;
+use crate::states::Fundraiser;
+
+/// Pay the maker the proceeds unlocked so far by the release schedule; closes
+/// the fundraiser once everything has been released
+/// Accounts: [maker(s,m), fundraiser, vault, maker_ata, token_program]
+/// Data: (none)
+pub fn process_release(
+    accounts: &[AccountView],
+    _data: &[u8],
+) -> ProgramResult {
+    let [
+        maker,
+        fundraiser,
+        vault,
+        maker_ata,
+        _token_program @ ..
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !maker.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Load fundraiser state
+    let fund_state = Fundraiser::from_account_info(fundraiser)?;
+
+    // Verify maker
+    if fund_state.maker() != *maker.address() {
+        return Err(FundraiserError::MakerMismatch.into());
+    }
+
+    if !fund_state.is_settled() {
+        return Err(FundraiserError::NotSettled.into());
+    }
+    fund_state.vault_balance(vault, fundraiser)?;
+
+    // Only pay out what unlocked since the last release
+    let released = fund_state.released();
+    let amount = fund_state.unlocked(Clock::get()?.unix_timestamp) - released;
+    if amount == 0 {
+        return Err(FundraiserError::NothingToRelease.into());
+    }
+    let fully_released = released + amount == fund_state.settled_amount();
+    let bump = fund_state.bump;
+
+    // Build fundraiser PDA signer
+    let bump_bytes = [bump];
+    let signer_seed = [
+        Seed::from(b"fundraiser"),
+        Seed::from(maker.address().as_array()),
+        Seed::from(&bump_bytes),
+    ];
+    let signer = Signer::from(&signer_seed);
+
+    // Transfer the unlocked tokens to maker
+    pinocchio_token::instructions::Transfer {
+        from: vault,
+        to: maker_ata,
+        authority: fundraiser,
+        amount,
+    }.invoke_signed(&[signer])?;
+
+    if !fully_released {
+        let fund_state = Fundraiser::from_account_info(fundraiser)?;
+        fund_state.set_released(released + amount);
+        return Ok(());
+    }
+
+    // Close fundraiser account — return lamports to maker
+    let fundraiser_lamports = fundraiser.lamports();
+    maker.set_lamports(maker.lamports() + fundraiser_lamports);
+    fundraiser.set_lamports(0);
+    unsafe {
+        let mut data = fundraiser.try_borrow_mut()?;
+        core::ptr::write_bytes(data.as_mut_ptr(), 0, data.len());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use pinocchio::{
+    AccountView, ProgramResult,
+    cpi::{Seed, Signer},
+    error::ProgramError,
+    sysvars::{Sysvar, clock::Clock},
+};
+use pinocchio_codec::FromBytes;
+use pinocchio_token::state::TokenAccount;
+
+use crate::errors::FundraiserError;
+use crate::states::{Fundraiser, Contributor};
+use crate::BPS_SCALER;
+
+/// Withdraw part or all of a contribution while the fundraiser is running. The
+/// fundraiser's withdraw penalty goes to the maker; the contributor state is
+/// closed once its amount reaches zero
+/// Accounts: [contributor(s,m), maker, fundraiser, vault, contributor_ata, contributor_state, maker_ata, token_program]
+/// Data: [amount: u64]
+pub fn process_withdraw(
+    accounts: &[AccountView],
+    data: &[u8],
+) -> ProgramResult {
+    let [
+        contributor,
+        maker,
+        fundraiser,
+        vault,
+        contributor_ata,
+        contributor_state,
+        maker_ata,
+        _token_program @ ..
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !contributor.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let amount = u64::from_bytes(data)?;
+
+    // Load fundraiser state
+    let fund_state = Fundraiser::from_account_info(fundraiser)?;
+
+    // Verify maker matches
+    if fund_state.maker() != *maker.address() {
+        return Err(FundraiserError::MakerMismatch.into());
+    }
+
+    // Only while contributions are still open
+    if fund_state.is_settled() || fund_state.has_ended(Clock::get()?.unix_timestamp) {
+        return Err(FundraiserError::FundraiserEnded.into());
+    }
+
+    fund_state.vault_balance(vault, fundraiser)?;
+
+    // Load contributor state and verify it belongs to this fundraiser
+    let contrib_state = Contributor::from_account_info(contributor_state)?;
+    contrib_state.verify_address(contributor_state, fundraiser, contributor)?;
+
+    let contributed = contrib_state.amount();
+    if amount == 0 || amount > contributed {
+        return Err(FundraiserError::InvalidWithdrawAmount.into());
+    }
+
+    let penalty = ((amount as u128 * fund_state.withdraw_penalty_bps() as u128)
+        / BPS_SCALER as u128) as u64;
+    let bump = fund_state.bump;
+
+    // Build fundraiser PDA signer
+    let bump_bytes = [bump];
+    let signer_seed = [
+        Seed::from(b"fundraiser"),
+        Seed::from(maker.address().as_ref()),
+        Seed::from(&bump_bytes),
+    ];
+
+    // Transfer the withdrawal minus penalty back to contributor
+    pinocchio_token::instructions::Transfer {
+        from: vault,
+        to: contributor_ata,
+        authority: fundraiser,
+        amount: amount - penalty,
+    }.invoke_signed(&[Signer::from(&signer_seed)])?;
+
+    // Pay the penalty to the maker
+    if penalty > 0 {
+        if TokenAccount::from_account_view(maker_ata)?.owner() != maker.address() {
+            return Err(FundraiserError::InvalidMakerAta.into());
+        }
+        pinocchio_token::instructions::Transfer {
+            from: vault,
+            to: maker_ata,
+            authority: fundraiser,
+            amount: penalty,
+        }.invoke_signed(&[Signer::from(&signer_seed)])?;
+    }
+
+    // Update fundraiser current_amount
+    let fund_state = Fundraiser::from_account_info(fundraiser)?;
+    fund_state.set_current_amount(fund_state.current_amount() - amount);
+
+    let remaining = contributed - amount;
+    if remaining > 0 {
+        let contrib_state = Contributor::from_account_info(contributor_state)?;
+        contrib_state.set_amount(remaining);
+        return Ok(());
+    }
+
+    // Close contributor state account — return lamports to contributor
+    let contrib_lamports = contributor_state.lamports();
+    contributor.set_lamports(contributor.lamports() + contrib_lamports);
+    contributor_state.set_lamports(0);
+    unsafe {
+        let mut data = contributor_state.try_borrow_mut()?;
+        core::ptr::write_bytes(data.as_mut_ptr(), 0, data.len());
+    }
+
+    Ok(())
+}
@@ -3,6 +3,7 @@ use pinocchio::{AccountView, entrypoint, Address, ProgramResult, address::declar
 
 use crate::instructions::FundraiserInstruction;
 
+mod errors;
 mod states;
 mod instructions;
 mod tests;
@@ -12,16 +13,18 @@ entrypoint!(process_instruction);
 declare_id!("FUNDrXoH7qEm2GhQyGbg6MaiMjfXaVeRfEfjkiGBLbq6");
 
 pub const MIN_AMOUNT_TO_RAISE: u64 = 3;
-pub const SECONDS_TO_DAYS: i64 = 86400;
 pub const MAX_CONTRIBUTION_PERCENTAGE: u64 = 10;
 pub const PERCENTAGE_SCALER: u64 = 100;
+pub const BPS_SCALER: u64 = 10_000;
 
 pub fn process_instruction(
     program_id: &Address,
     accounts: &[AccountView],
     instruction_data: &[u8],
 ) -> ProgramResult {
-    assert_eq!(program_id, &ID);
+    if program_id != &ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
 
     let (discriminator, data) = instruction_data.split_first()
         .ok_or(ProgramError::InvalidInstructionData)?;
@@ -32,6 +35,9 @@ pub fn process_instruction(
         FundraiserInstruction::Contribute => instructions::process_contribute(accounts, data)?,
         FundraiserInstruction::Checker => instructions::process_checker(accounts, data)?,
         FundraiserInstruction::Refund => instructions::process_refund(accounts, data)?,
+        FundraiserInstruction::Release => instructions::process_release(accounts, data)?,
+        FundraiserInstruction::ApproveTranche => instructions::process_approve_tranche(accounts, data)?,
+        FundraiserInstruction::Withdraw => instructions::process_withdraw(accounts, data)?,
     }
     Ok(())
 }
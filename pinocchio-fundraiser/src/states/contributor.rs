@@ -1,14 +1,19 @@
 use pinocchio::{AccountView, error::ProgramError};
+use pinocchio_pubkey::derive_address;
+
+use crate::errors::FundraiserError;
 
 /// Contributor state account layout (48 bytes):
-/// contributor[32] | amount:u64 | bump:u8 | _padding[7]
+/// contributor[32] | amount:u64 | bump:u8 | last_vote:u8 | _padding[6]
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Contributor {
     contributor: [u8; 32],
     amount: [u8; 8],
     pub bump: u8,
-    _padding: [u8; 7],
+    /// Milestone tranche last voted for, counted from 1; 0 if never voted
+    pub last_vote: u8,
+    _padding: [u8; 6],
 }
 
 impl Contributor {
@@ -17,10 +22,10 @@ impl Contributor {
     pub fn from_account_info(account_info: &AccountView) -> Result<&mut Self, ProgramError> {
         let mut data = account_info.try_borrow_mut()?;
         if data.len() != Self::LEN {
-            return Err(ProgramError::InvalidAccountData);
+            return Err(FundraiserError::InvalidStateData.into());
         }
         if (data.as_ptr() as usize) % core::mem::align_of::<Self>() != 0 {
-            return Err(ProgramError::InvalidAccountData);
+            return Err(FundraiserError::InvalidStateData.into());
         }
         Ok(unsafe { &mut *(data.as_mut_ptr() as *mut Self) })
     }
@@ -33,6 +38,26 @@ impl Contributor {
         self.contributor.copy_from_slice(contributor.as_ref());
     }
 
+    /// Check `contributor_state` is `contributor`'s PDA for `fundraiser`.
+    pub fn verify_address(
+        &self,
+        contributor_state: &AccountView,
+        fundraiser: &AccountView,
+        contributor: &AccountView,
+    ) -> Result<(), ProgramError> {
+        let seed = [
+            b"contributor".as_ref(),
+            fundraiser.address().as_ref(),
+            contributor.address().as_ref(),
+            &[self.bump],
+        ];
+        let contributor_pda = derive_address(&seed, None, &crate::ID.to_bytes());
+        if contributor_pda != *contributor_state.address().as_array() {
+            return Err(FundraiserError::InvalidContributorPda.into());
+        }
+        Ok(())
+    }
+
     pub fn amount(&self) -> u64 {
         u64::from_le_bytes(self.amount)
     }
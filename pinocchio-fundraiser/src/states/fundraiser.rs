@@ -1,8 +1,67 @@
-use pinocchio::{AccountView, error::ProgramError};
+use pinocchio::{AccountView, Address, error::ProgramError};
+use pinocchio_token::state::TokenAccount;
 
-/// Fundraiser state account layout (96 bytes):
+use crate::errors::FundraiserError;
+
+const ASSOCIATED_TOKEN_PROGRAM_ID: [u8; 32] =
+    pinocchio_pubkey::pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+/// How a fundraiser settles once it ends.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FundingMode {
+    /// The maker claims only if the target is met; otherwise contributors refund
+    AllOrNothing = 0,
+    /// The maker claims whatever was raised once the fundraiser ends; no refunds
+    KeepWhatYouRaise = 1,
+}
+
+impl TryFrom<u8> for FundingMode {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(FundingMode::AllOrNothing),
+            1 => Ok(FundingMode::KeepWhatYouRaise),
+            _ => Err(FundraiserError::InvalidMode.into()),
+        }
+    }
+}
+
+/// Most milestone tranches a release schedule can be split into.
+pub const MAX_TRANCHES: u8 = 10;
+
+/// How the maker receives the proceeds once the fundraiser settles.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReleaseSchedule {
+    /// Checker pays out the whole vault and closes the fundraiser
+    Immediate = 0,
+    /// Proceeds unlock linearly over `vesting_duration` seconds after settlement
+    Linear = 1,
+    /// Proceeds unlock in equal tranches, each approved by contributor vote
+    Milestones = 2,
+}
+
+impl TryFrom<u8> for ReleaseSchedule {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ReleaseSchedule::Immediate),
+            1 => Ok(ReleaseSchedule::Linear),
+            2 => Ok(ReleaseSchedule::Milestones),
+            _ => Err(FundraiserError::InvalidReleaseSchedule.into()),
+        }
+    }
+}
+
+/// Fundraiser state account layout (184 bytes):
 /// maker[32] | mint_to_raise[32] | amount_to_raise:u64 | current_amount:u64 |
-/// time_started:i64 | duration:u8 | bump:u8 | _padding[6]
+/// start_time:i64 | end_time:i64 | bump:u8 | mode:u8 | release:u8 | tranches:u8 |
+/// tranches_approved:u8 | settled:u8 | withdraw_penalty_bps:u16 | stretch_goal:u64 |
+/// vesting_duration:i64 | settled_at:i64 | settled_amount:u64 | released:u64 | votes:u64 |
+/// max_contribution:u64 | min_contribution:u64 | hard_cap:u64 | vote_period:i64
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Fundraiser {
@@ -10,22 +69,37 @@ pub struct Fundraiser {
     mint_to_raise: [u8; 32],
     amount_to_raise: [u8; 8],
     current_amount: [u8; 8],
-    time_started: [u8; 8],
-    pub duration: u8,
+    start_time: [u8; 8],
+    end_time: [u8; 8],
     pub bump: u8,
-    _padding: [u8; 6],
+    mode: u8,
+    release: u8,
+    pub tranches: u8,
+    pub tranches_approved: u8,
+    settled: u8,
+    withdraw_penalty_bps: [u8; 2],
+    stretch_goal: [u8; 8],
+    vesting_duration: [u8; 8],
+    settled_at: [u8; 8],
+    settled_amount: [u8; 8],
+    released: [u8; 8],
+    votes: [u8; 8],
+    max_contribution: [u8; 8],
+    min_contribution: [u8; 8],
+    hard_cap: [u8; 8],
+    vote_period: [u8; 8],
 }
 
 impl Fundraiser {
-    pub const LEN: usize = 96;
+    pub const LEN: usize = 184;
 
     pub fn from_account_info(account_info: &AccountView) -> Result<&mut Self, ProgramError> {
         let mut data = account_info.try_borrow_mut()?;
         if data.len() != Self::LEN {
-            return Err(ProgramError::InvalidAccountData);
+            return Err(FundraiserError::InvalidStateData.into());
         }
         if (data.as_ptr() as usize) % core::mem::align_of::<Self>() != 0 {
-            return Err(ProgramError::InvalidAccountData);
+            return Err(FundraiserError::InvalidStateData.into());
         }
         Ok(unsafe { &mut *(data.as_mut_ptr() as *mut Self) })
     }
@@ -46,6 +120,28 @@ impl Fundraiser {
         self.mint_to_raise.copy_from_slice(mint.as_ref());
     }
 
+    /// Check `vault` is the associated token account of `fundraiser` for
+    /// `mint_to_raise`, and return its balance. Other accounts the fundraiser
+    /// owns would split the raised tokens across several balances.
+    pub fn vault_balance(&self, vault: &AccountView, fundraiser: &AccountView) -> Result<u64, ProgramError> {
+        let seeds = [
+            fundraiser.address().as_ref(),
+            pinocchio_token::ID.as_ref(),
+            self.mint_to_raise.as_ref(),
+        ];
+        let (ata, _) =
+            Address::find_program_address(&seeds, &Address::from(ASSOCIATED_TOKEN_PROGRAM_ID));
+        if vault.address() != &ata {
+            return Err(FundraiserError::InvalidVault.into());
+        }
+        let vault = TokenAccount::from_account_view(vault)
+            .map_err(|_| FundraiserError::InvalidVault)?;
+        if vault.owner() != fundraiser.address() || *vault.mint() != self.mint_to_raise() {
+            return Err(FundraiserError::InvalidVault.into());
+        }
+        Ok(vault.amount())
+    }
+
     pub fn amount_to_raise(&self) -> u64 {
         u64::from_le_bytes(self.amount_to_raise)
     }
@@ -62,11 +158,211 @@ impl Fundraiser {
         self.current_amount = amount.to_le_bytes();
     }
 
-    pub fn time_started(&self) -> i64 {
-        i64::from_le_bytes(self.time_started)
+    pub fn start_time(&self) -> i64 {
+        i64::from_le_bytes(self.start_time)
+    }
+
+    pub fn set_start_time(&mut self, time: i64) {
+        self.start_time = time.to_le_bytes();
+    }
+
+    pub fn end_time(&self) -> i64 {
+        i64::from_le_bytes(self.end_time)
+    }
+
+    pub fn set_end_time(&mut self, time: i64) {
+        self.end_time = time.to_le_bytes();
+    }
+
+    pub fn mode(&self) -> FundingMode {
+        // Only valid modes are ever written
+        FundingMode::try_from(self.mode).unwrap_or(FundingMode::AllOrNothing)
+    }
+
+    pub fn set_mode(&mut self, mode: FundingMode) {
+        self.mode = mode as u8;
+    }
+
+    /// Goal above the target that settles the fundraiser early, if set.
+    pub fn stretch_goal(&self) -> Option<u64> {
+        match u64::from_le_bytes(self.stretch_goal) {
+            0 => None,
+            goal => Some(goal),
+        }
+    }
+
+    pub fn set_stretch_goal(&mut self, goal: Option<u64>) {
+        self.stretch_goal = goal.unwrap_or(0).to_le_bytes();
+    }
+
+    /// The goal that settles early: the stretch goal, or the target without one.
+    pub fn final_goal(&self) -> u64 {
+        self.stretch_goal().unwrap_or(self.amount_to_raise())
+    }
+
+    /// Whether the fundraising period has begun; it opens at `start_time`.
+    pub fn has_started(&self, now: i64) -> bool {
+        now >= self.start_time()
+    }
+
+    /// Whether the fundraising period is over; it closes at `end_time`.
+    pub fn has_ended(&self, now: i64) -> bool {
+        now >= self.end_time()
+    }
+
+    /// Whether the maker may claim `raised` at `now`. Reaching the final goal
+    /// settles early; otherwise the fundraiser runs to its end, after which an
+    /// all-or-nothing raise needs the target and a flexible one pays out as is.
+    pub fn can_claim(&self, raised: u64, now: i64) -> bool {
+        if raised >= self.final_goal() {
+            return true;
+        }
+        if !self.has_ended(now) {
+            return false;
+        }
+        match self.mode() {
+            FundingMode::AllOrNothing => raised >= self.amount_to_raise(),
+            FundingMode::KeepWhatYouRaise => true,
+        }
+    }
+
+    pub fn release(&self) -> ReleaseSchedule {
+        // Only valid schedules are ever written
+        ReleaseSchedule::try_from(self.release).unwrap_or(ReleaseSchedule::Immediate)
+    }
+
+    pub fn set_release(&mut self, release: ReleaseSchedule) {
+        self.release = release as u8;
+    }
+
+    pub fn vesting_duration(&self) -> i64 {
+        i64::from_le_bytes(self.vesting_duration)
+    }
+
+    pub fn set_vesting_duration(&mut self, duration: i64) {
+        self.vesting_duration = duration.to_le_bytes();
+    }
+
+    /// Whether checker has locked in the proceeds for a scheduled release.
+    pub fn is_settled(&self) -> bool {
+        self.settled != 0
+    }
+
+    /// Lock in `amount` as the proceeds to release, starting at `now`.
+    pub fn settle(&mut self, amount: u64, now: i64) {
+        self.settled = 1;
+        self.settled_at = now.to_le_bytes();
+        self.settled_amount = amount.to_le_bytes();
+    }
+
+    pub fn settled_at(&self) -> i64 {
+        i64::from_le_bytes(self.settled_at)
+    }
+
+    pub fn settled_amount(&self) -> u64 {
+        u64::from_le_bytes(self.settled_amount)
+    }
+
+    pub fn released(&self) -> u64 {
+        u64::from_le_bytes(self.released)
+    }
+
+    pub fn set_released(&mut self, amount: u64) {
+        self.released = amount.to_le_bytes();
+    }
+
+    /// Seconds after settlement contributors have to approve milestone tranches.
+    pub fn vote_period(&self) -> i64 {
+        i64::from_le_bytes(self.vote_period)
+    }
+
+    pub fn set_vote_period(&mut self, period: i64) {
+        self.vote_period = period.to_le_bytes();
+    }
+
+    /// Whether a milestone tranche is still awaiting approval.
+    pub fn has_pending_tranche(&self) -> bool {
+        self.release() == ReleaseSchedule::Milestones && self.tranches_approved < self.tranches
+    }
+
+    /// Whether milestone voting is over; tranches unapproved by then stay
+    /// locked and contributors can refund them.
+    pub fn voting_closed(&self, now: i64) -> bool {
+        now >= self.settled_at().saturating_add(self.vote_period())
+    }
+
+    /// The share of the proceeds in unapproved tranches owed for a contribution
+    /// of `amount`.
+    pub fn locked_share(&self, amount: u64) -> u64 {
+        let pending = (self.tranches - self.tranches_approved) as u128;
+        let locked = self.settled_amount() as u128 * pending / self.tranches as u128;
+        (amount as u128 * locked)
+            .checked_div(self.current_amount() as u128)
+            .unwrap_or(0) as u64
+    }
+
+    /// Contributor weight voting for the pending tranche.
+    pub fn votes(&self) -> u64 {
+        u64::from_le_bytes(self.votes)
+    }
+
+    pub fn set_votes(&mut self, votes: u64) {
+        self.votes = votes.to_le_bytes();
+    }
+
+    /// Share of an early withdrawal kept as a penalty, in basis points.
+    pub fn withdraw_penalty_bps(&self) -> u16 {
+        u16::from_le_bytes(self.withdraw_penalty_bps)
+    }
+
+    pub fn set_withdraw_penalty_bps(&mut self, bps: u16) {
+        self.withdraw_penalty_bps = bps.to_le_bytes();
+    }
+
+    /// Most a single contributor may contribute in total.
+    pub fn max_contribution(&self) -> u64 {
+        u64::from_le_bytes(self.max_contribution)
+    }
+
+    pub fn set_max_contribution(&mut self, amount: u64) {
+        self.max_contribution = amount.to_le_bytes();
+    }
+
+    /// Smallest amount accepted in one contribution.
+    pub fn min_contribution(&self) -> u64 {
+        u64::from_le_bytes(self.min_contribution)
+    }
+
+    pub fn set_min_contribution(&mut self, amount: u64) {
+        self.min_contribution = amount.to_le_bytes();
+    }
+
+    /// Most the fundraiser may raise in total, if capped.
+    pub fn hard_cap(&self) -> Option<u64> {
+        match u64::from_le_bytes(self.hard_cap) {
+            0 => None,
+            cap => Some(cap),
+        }
+    }
+
+    pub fn set_hard_cap(&mut self, cap: Option<u64>) {
+        self.hard_cap = cap.unwrap_or(0).to_le_bytes();
     }
 
-    pub fn set_time_started(&mut self, time: i64) {
-        self.time_started = time.to_le_bytes();
+    /// Proceeds unlocked for the maker at `now`, including those already released.
+    pub fn unlocked(&self, now: i64) -> u64 {
+        let total = self.settled_amount() as u128;
+        let unlocked = match self.release() {
+            ReleaseSchedule::Immediate => total,
+            ReleaseSchedule::Linear => {
+                let vesting = self.vesting_duration();
+                let elapsed = (now - self.settled_at()).clamp(0, vesting);
+                total * elapsed as u128 / vesting as u128
+            }
+            ReleaseSchedule::Milestones => {
+                total * self.tranches_approved as u128 / self.tranches as u128
+            }
+        };
+        unlocked as u64
     }
 }
@@ -2,19 +2,22 @@
 mod tests {
     use std::path::PathBuf;
 
-    use litesvm::LiteSVM;
+    use litesvm::{types::TransactionResult, LiteSVM};
     use litesvm_token::{
         spl_token::{self},
-        CreateAssociatedTokenAccount, CreateMint, MintTo,
+        CreateAccount, CreateAssociatedTokenAccount, CreateMint, MintTo,
     };
     use solana_clock::Clock;
-    use solana_instruction::{AccountMeta, Instruction};
+    use solana_instruction::{error::InstructionError, AccountMeta, Instruction};
     use solana_keypair::Keypair;
     use solana_message::Message;
     use solana_native_token::LAMPORTS_PER_SOL;
     use solana_pubkey::Pubkey;
     use solana_signer::Signer;
     use solana_transaction::Transaction;
+    use solana_transaction_error::TransactionError;
+
+    use crate::errors::FundraiserError;
 
     const TOKEN_PROGRAM_ID: Pubkey = spl_token::ID;
 
@@ -37,13 +40,100 @@ mod tests {
         (svm, payer)
     }
 
-    /// Helper: initialize a fundraiser and return (mint, fundraiser_pda, fundraiser_bump, vault)
+    /// Initialize arguments; `InitParams::new` is an all-or-nothing fundraiser
+    /// starting right away, without a stretch goal, hard cap or withdraw penalty,
+    /// with the default 10% contribution cap, that pays out immediately. The campaign window is
+    /// relative to the clock at initialize: it opens `starts_in` seconds from
+    /// then and stays open for `duration` seconds
+    #[derive(Clone, Copy)]
+    struct InitParams {
+        amount_to_raise: u64,
+        starts_in: i64,
+        duration: i64,
+        mode: u8,
+        stretch_goal: u64,
+        release: u8,
+        tranches: u8,
+        vesting_duration: i64,
+        cap_percentage: u8,
+        max_contribution: u64,
+        min_contribution: u64,
+        hard_cap: u64,
+        withdraw_penalty_bps: u16,
+        vote_period: i64,
+    }
+
+    impl InitParams {
+        fn new(amount_to_raise: u64, days: i64) -> Self {
+            Self {
+                amount_to_raise,
+                starts_in: 0,
+                duration: days * 86400,
+                mode: 0,
+                stretch_goal: 0,
+                release: 0,
+                tranches: 0,
+                vesting_duration: 0,
+                cap_percentage: 0,
+                max_contribution: 0,
+                min_contribution: 0,
+                hard_cap: 0,
+                withdraw_penalty_bps: 0,
+                vote_period: 0,
+            }
+        }
+
+        /// Initialize payload, after the discriminator
+        fn data(&self, bump: u8, now: i64) -> Vec<u8> {
+            let start_time = now + self.starts_in;
+            let mut data = self.amount_to_raise.to_le_bytes().to_vec();
+            data.extend_from_slice(&start_time.to_le_bytes());
+            data.extend_from_slice(&(start_time + self.duration).to_le_bytes());
+            data.push(bump);
+            data.push(self.mode);
+            data.extend_from_slice(&self.stretch_goal.to_le_bytes());
+            data.push(self.release);
+            data.push(self.tranches);
+            data.extend_from_slice(&self.vesting_duration.to_le_bytes());
+            data.push(self.cap_percentage);
+            data.extend_from_slice(&self.max_contribution.to_le_bytes());
+            data.extend_from_slice(&self.min_contribution.to_le_bytes());
+            data.extend_from_slice(&self.hard_cap.to_le_bytes());
+            data.extend_from_slice(&self.withdraw_penalty_bps.to_le_bytes());
+            data.extend_from_slice(&self.vote_period.to_le_bytes());
+            data
+        }
+    }
+
+    /// Helper: initialize a default fundraiser running for `days` and return
+    /// (mint, fundraiser_pda, fundraiser_bump, vault)
     fn do_initialize(
         svm: &mut LiteSVM,
         maker: &Keypair,
         amount_to_raise: u64,
-        duration: u8,
+        days: i64,
+    ) -> (Pubkey, Pubkey, u8, Pubkey) {
+        do_initialize_with(svm, maker, &InitParams::new(amount_to_raise, days))
+    }
+
+    /// Helper: initialize a fundraiser with custom parameters
+    fn do_initialize_with(
+        svm: &mut LiteSVM,
+        maker: &Keypair,
+        params: &InitParams,
     ) -> (Pubkey, Pubkey, u8, Pubkey) {
+        let (result, mint, fundraiser_pda, bump, vault) = try_initialize(svm, maker, params);
+        println!("Initialize CUs: {}", result.unwrap().compute_units_consumed);
+
+        (mint, fundraiser_pda, bump, vault)
+    }
+
+    /// Helper: like `do_initialize_with`, but returns the result instead of unwrapping it
+    fn try_initialize(
+        svm: &mut LiteSVM,
+        maker: &Keypair,
+        params: &InitParams,
+    ) -> (TransactionResult, Pubkey, Pubkey, u8, Pubkey) {
         let program_id = program_id();
         let system_program = solana_sdk_ids::system_program::ID;
 
@@ -63,11 +153,10 @@ mod tests {
             &mint,
         );
 
-        // Build initialize ix: disc=0, amount:u64, duration:u8, bump:u8
+        // Build initialize ix: disc=0, then the InitParams payload
         let mut ix_data = vec![0u8]; // discriminator
-        ix_data.extend_from_slice(&amount_to_raise.to_le_bytes());
-        ix_data.push(duration);
-        ix_data.push(bump);
+        let clock: Clock = svm.get_sysvar();
+        ix_data.extend_from_slice(&params.data(bump, clock.unix_timestamp));
 
         let ix = Instruction {
             program_id,
@@ -83,10 +172,8 @@ mod tests {
         let message = Message::new(&[ix], Some(&maker.pubkey()));
         let blockhash = svm.latest_blockhash();
         let tx = Transaction::new(&[maker], message, blockhash);
-        let result = svm.send_transaction(tx).unwrap();
-        println!("Initialize CUs: {}", result.compute_units_consumed);
 
-        (mint, fundraiser_pda, bump, vault)
+        (svm.send_transaction(tx), mint, fundraiser_pda, bump, vault)
     }
 
     /// Helper: create the vault ATA for the fundraiser PDA
@@ -142,6 +229,56 @@ mod tests {
         (contributor_state_pda, bump)
     }
 
+    /// Helper: build a contribute instruction
+    fn contribute_ix(
+        contributor: &Pubkey,
+        fundraiser_pda: &Pubkey,
+        vault: &Pubkey,
+        contributor_ata: &Pubkey,
+        contributor_state_pda: &Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        let mut ix_data = vec![2u8]; // disc=2
+        ix_data.extend_from_slice(&amount.to_le_bytes());
+
+        Instruction {
+            program_id: program_id(),
+            accounts: vec![
+                AccountMeta::new(*contributor, true),
+                AccountMeta::new(*fundraiser_pda, false),
+                AccountMeta::new(*vault, false),
+                AccountMeta::new(*contributor_ata, false),
+                AccountMeta::new(*contributor_state_pda, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            ],
+            data: ix_data,
+        }
+    }
+
+    /// Helper: send a contribute instruction without unwrapping the result
+    fn send_contribute(
+        svm: &mut LiteSVM,
+        contributor: &Keypair,
+        fundraiser_pda: &Pubkey,
+        vault: &Pubkey,
+        contributor_ata: &Pubkey,
+        contributor_state_pda: &Pubkey,
+        amount: u64,
+    ) -> TransactionResult {
+        let ix = contribute_ix(
+            &contributor.pubkey(),
+            fundraiser_pda,
+            vault,
+            contributor_ata,
+            contributor_state_pda,
+            amount,
+        );
+        let message = Message::new(&[ix], Some(&contributor.pubkey()));
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new(&[contributor], message, blockhash);
+        svm.send_transaction(tx)
+    }
+
     /// Helper: contribute tokens
     fn do_contribute(
         svm: &mut LiteSVM,
@@ -152,30 +289,229 @@ mod tests {
         contributor_state_pda: &Pubkey,
         amount: u64,
     ) {
-        let program_id = program_id();
-        let token_program = TOKEN_PROGRAM_ID;
+        let result = send_contribute(
+            svm,
+            contributor,
+            fundraiser_pda,
+            vault,
+            contributor_ata,
+            contributor_state_pda,
+            amount,
+        )
+        .unwrap();
+        println!("Contribute CUs: {}", result.compute_units_consumed);
+    }
 
-        let mut ix_data = vec![2u8]; // disc=2
+    /// Helper: fund a new contributor with `balance` tokens and create its
+    /// state PDA. Returns (contributor, contributor_ata, contributor_state_pda)
+    fn setup_contributor(
+        svm: &mut LiteSVM,
+        maker: &Keypair,
+        mint: &Pubkey,
+        fundraiser_pda: &Pubkey,
+        balance: u64,
+    ) -> (Keypair, Pubkey, Pubkey) {
+        let contributor = Keypair::new();
+        svm.airdrop(&contributor.pubkey(), 5 * LAMPORTS_PER_SOL)
+            .unwrap();
+
+        let (contributor_state_pda, _) =
+            do_create_contributor(svm, &contributor, fundraiser_pda);
+
+        let contributor_ata = CreateAssociatedTokenAccount::new(svm, &contributor, mint)
+            .owner(&contributor.pubkey())
+            .send()
+            .unwrap();
+
+        MintTo::new(svm, maker, mint, &contributor_ata, balance)
+            .send()
+            .unwrap();
+
+        (contributor, contributor_ata, contributor_state_pda)
+    }
+
+    /// Helper: build a checker instruction
+    fn checker_ix(
+        maker: &Pubkey,
+        fundraiser_pda: &Pubkey,
+        vault: &Pubkey,
+        maker_ata: &Pubkey,
+    ) -> Instruction {
+        Instruction {
+            program_id: program_id(),
+            accounts: vec![
+                AccountMeta::new(*maker, true),
+                AccountMeta::new(*fundraiser_pda, false),
+                AccountMeta::new(*vault, false),
+                AccountMeta::new(*maker_ata, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            ],
+            data: vec![3u8],
+        }
+    }
+
+    /// Helper: build a refund instruction
+    fn refund_ix(
+        contributor: &Pubkey,
+        maker: &Pubkey,
+        fundraiser_pda: &Pubkey,
+        vault: &Pubkey,
+        contributor_ata: &Pubkey,
+        contributor_state_pda: &Pubkey,
+    ) -> Instruction {
+        Instruction {
+            program_id: program_id(),
+            accounts: vec![
+                AccountMeta::new(*contributor, true),
+                AccountMeta::new_readonly(*maker, false),
+                AccountMeta::new(*fundraiser_pda, false),
+                AccountMeta::new(*vault, false),
+                AccountMeta::new(*contributor_ata, false),
+                AccountMeta::new(*contributor_state_pda, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            ],
+            data: vec![4u8],
+        }
+    }
+
+    /// Helper: `count` new contributors each contribute `amount`. Returns each
+    /// (contributor, contributor_state_pda)
+    fn contribute_from_many(
+        svm: &mut LiteSVM,
+        maker: &Keypair,
+        mint: &Pubkey,
+        fundraiser_pda: &Pubkey,
+        vault: &Pubkey,
+        count: usize,
+        amount: u64,
+    ) -> Vec<(Keypair, Pubkey)> {
+        let mut contributors = Vec::with_capacity(count);
+        for _ in 0..count {
+            let (contributor, contributor_ata, contributor_state_pda) =
+                setup_contributor(svm, maker, mint, fundraiser_pda, amount);
+            do_contribute(
+                svm,
+                &contributor,
+                fundraiser_pda,
+                vault,
+                &contributor_ata,
+                &contributor_state_pda,
+                amount,
+            );
+            contributors.push((contributor, contributor_state_pda));
+        }
+        contributors
+    }
+
+    /// Helper: send a checker instruction signed by the maker
+    fn send_checker(
+        svm: &mut LiteSVM,
+        maker: &Keypair,
+        fundraiser_pda: &Pubkey,
+        vault: &Pubkey,
+        maker_ata: &Pubkey,
+    ) -> TransactionResult {
+        let ix = checker_ix(&maker.pubkey(), fundraiser_pda, vault, maker_ata);
+        let message = Message::new(&[ix], Some(&maker.pubkey()));
+        svm.expire_blockhash();
+        let tx = Transaction::new(&[maker], message, svm.latest_blockhash());
+        svm.send_transaction(tx)
+    }
+
+    /// Helper: send a withdraw instruction signed by the contributor
+    #[allow(clippy::too_many_arguments)]
+    fn send_withdraw(
+        svm: &mut LiteSVM,
+        contributor: &Keypair,
+        maker: &Pubkey,
+        fundraiser_pda: &Pubkey,
+        vault: &Pubkey,
+        contributor_ata: &Pubkey,
+        contributor_state_pda: &Pubkey,
+        maker_ata: &Pubkey,
+        amount: u64,
+    ) -> TransactionResult {
+        let mut ix_data = vec![7u8]; // disc=7
         ix_data.extend_from_slice(&amount.to_le_bytes());
 
         let ix = Instruction {
-            program_id,
+            program_id: program_id(),
             accounts: vec![
                 AccountMeta::new(contributor.pubkey(), true),
+                AccountMeta::new_readonly(*maker, false),
                 AccountMeta::new(*fundraiser_pda, false),
                 AccountMeta::new(*vault, false),
                 AccountMeta::new(*contributor_ata, false),
                 AccountMeta::new(*contributor_state_pda, false),
-                AccountMeta::new_readonly(token_program, false),
+                AccountMeta::new(*maker_ata, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
             ],
             data: ix_data,
         };
+        let message = Message::new(&[ix], Some(&contributor.pubkey()));
+        svm.expire_blockhash();
+        let tx = Transaction::new(&[contributor], message, svm.latest_blockhash());
+        svm.send_transaction(tx)
+    }
+
+    /// Helper: send a release instruction signed by the maker
+    fn send_release(
+        svm: &mut LiteSVM,
+        maker: &Keypair,
+        fundraiser_pda: &Pubkey,
+        vault: &Pubkey,
+        maker_ata: &Pubkey,
+    ) -> TransactionResult {
+        let mut ix = checker_ix(&maker.pubkey(), fundraiser_pda, vault, maker_ata);
+        ix.data = vec![5u8]; // same accounts as checker, disc=5
+        let message = Message::new(&[ix], Some(&maker.pubkey()));
+        svm.expire_blockhash();
+        let tx = Transaction::new(&[maker], message, svm.latest_blockhash());
+        svm.send_transaction(tx)
+    }
 
+    /// Helper: send an approve-tranche vote signed by the contributor
+    fn send_approve_tranche(
+        svm: &mut LiteSVM,
+        contributor: &Keypair,
+        fundraiser_pda: &Pubkey,
+        contributor_state_pda: &Pubkey,
+    ) -> TransactionResult {
+        let ix = Instruction {
+            program_id: program_id(),
+            accounts: vec![
+                AccountMeta::new_readonly(contributor.pubkey(), true),
+                AccountMeta::new(*fundraiser_pda, false),
+                AccountMeta::new(*contributor_state_pda, false),
+            ],
+            data: vec![6u8],
+        };
         let message = Message::new(&[ix], Some(&contributor.pubkey()));
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new(&[contributor], message, blockhash);
-        let result = svm.send_transaction(tx).unwrap();
-        println!("Contribute CUs: {}", result.compute_units_consumed);
+        svm.expire_blockhash();
+        let tx = Transaction::new(&[contributor], message, svm.latest_blockhash());
+        svm.send_transaction(tx)
+    }
+
+    /// Helper: move the clock forward by `seconds`
+    fn warp_by(svm: &mut LiteSVM, seconds: i64) {
+        let mut clock: Clock = svm.get_sysvar();
+        clock.unix_timestamp += seconds;
+        svm.set_sysvar(&clock);
+    }
+
+    /// Helper: read a token account's balance
+    fn token_balance(svm: &LiteSVM, account: &Pubkey) -> u64 {
+        let data = svm.get_account(account).unwrap().data;
+        u64::from_le_bytes(data[64..72].try_into().unwrap())
+    }
+
+    /// Helper: assert a transaction failed with the given fundraiser error
+    fn assert_fundraiser_error(result: TransactionResult, expected: FundraiserError) {
+        let failed = result.expect_err("transaction should fail");
+        assert_eq!(
+            failed.err,
+            TransactionError::InstructionError(0, InstructionError::Custom(expected as u32)),
+        );
     }
 
     #[test]
@@ -190,7 +526,7 @@ mod tests {
         // Verify fundraiser account exists and is owned by our program
         let account = svm.get_account(&fundraiser_pda).unwrap();
         assert_eq!(account.owner, program_id());
-        assert_eq!(account.data.len(), 96); // Fundraiser::LEN
+        assert_eq!(account.data.len(), 184); // Fundraiser::LEN
         println!("Initialize test passed");
     }
 
@@ -313,20 +649,7 @@ mod tests {
             .unwrap();
 
         // Call checker (disc=3)
-        let program_id = program_id();
-        let token_program = TOKEN_PROGRAM_ID;
-
-        let ix = Instruction {
-            program_id,
-            accounts: vec![
-                AccountMeta::new(maker.pubkey(), true),
-                AccountMeta::new(fundraiser_pda, false),
-                AccountMeta::new(vault, false),
-                AccountMeta::new(maker_ata, false),
-                AccountMeta::new_readonly(token_program, false),
-            ],
-            data: vec![3u8],
-        };
+        let ix = checker_ix(&maker.pubkey(), &fundraiser_pda, &vault, &maker_ata);
 
         let message = Message::new(&[ix], Some(&maker.pubkey()));
         let blockhash = svm.latest_blockhash();
@@ -349,7 +672,7 @@ mod tests {
     fn test_refund() {
         let (mut svm, maker) = setup();
         let amount_to_raise = 1_000_000_000u64; // 1000 tokens
-        let duration = 1u8; // 1 day
+        let duration = 1; // 1 day
 
         let (mint, fundraiser_pda, _bump, _) =
             do_initialize(&mut svm, &maker, amount_to_raise, duration);
@@ -396,22 +719,14 @@ mod tests {
         svm.set_sysvar(&clock);
 
         // Call refund (disc=4)
-        let program_id = program_id();
-        let token_program = TOKEN_PROGRAM_ID;
-
-        let ix = Instruction {
-            program_id,
-            accounts: vec![
-                AccountMeta::new(contributor.pubkey(), true),
-                AccountMeta::new_readonly(maker.pubkey(), false),
-                AccountMeta::new(fundraiser_pda, false),
-                AccountMeta::new(vault, false),
-                AccountMeta::new(contributor_ata, false),
-                AccountMeta::new(contributor_state_pda, false),
-                AccountMeta::new_readonly(token_program, false),
-            ],
-            data: vec![4u8],
-        };
+        let ix = refund_ix(
+            &contributor.pubkey(),
+            &maker.pubkey(),
+            &fundraiser_pda,
+            &vault,
+            &contributor_ata,
+            &contributor_state_pda,
+        );
 
         let message = Message::new(&[ix], Some(&contributor.pubkey()));
         let blockhash = svm.latest_blockhash();
@@ -429,4 +744,1249 @@ mod tests {
         assert!(svm.get_account(&contributor_state_pda).is_none());
         println!("Refund test passed, refunded: {}", refunded_balance);
     }
+
+    #[test]
+    fn test_contribute_rejects_invalid_amounts() {
+        let (mut svm, maker) = setup();
+        let amount_to_raise = 10_000_000_000u64; // cap per contributor = 1000 tokens
+        let (mint, fundraiser_pda, _bump, _) =
+            do_initialize(&mut svm, &maker, amount_to_raise, 7);
+        let vault = create_vault(&mut svm, &maker, &fundraiser_pda, &mint);
+        let (contributor, contributor_ata, contributor_state_pda) =
+            setup_contributor(&mut svm, &maker, &mint, &fundraiser_pda, 2_000_000_000);
+
+        let contribute = |svm: &mut LiteSVM, amount: u64| {
+            svm.expire_blockhash();
+            send_contribute(
+                svm,
+                &contributor,
+                &fundraiser_pda,
+                &vault,
+                &contributor_ata,
+                &contributor_state_pda,
+                amount,
+            )
+        };
+
+        assert_fundraiser_error(contribute(&mut svm, 0), FundraiserError::ZeroContribution);
+        assert_fundraiser_error(
+            contribute(&mut svm, 1_000_000_001),
+            FundraiserError::ContributionTooLarge,
+        );
+
+        contribute(&mut svm, 600_000_000).unwrap();
+        assert_fundraiser_error(
+            contribute(&mut svm, 500_000_000),
+            FundraiserError::ContributorCapExceeded,
+        );
+    }
+
+    #[test]
+    fn test_contribute_after_end_fails() {
+        let (mut svm, maker) = setup();
+        let (mint, fundraiser_pda, _bump, _) =
+            do_initialize(&mut svm, &maker, 1_000_000_000, 1);
+        let vault = create_vault(&mut svm, &maker, &fundraiser_pda, &mint);
+        let (contributor, contributor_ata, contributor_state_pda) =
+            setup_contributor(&mut svm, &maker, &mint, &fundraiser_pda, 50_000_000);
+
+        let mut clock: Clock = svm.get_sysvar();
+        clock.unix_timestamp += 2 * 86400;
+        svm.set_sysvar(&clock);
+
+        let result = send_contribute(
+            &mut svm,
+            &contributor,
+            &fundraiser_pda,
+            &vault,
+            &contributor_ata,
+            &contributor_state_pda,
+            50_000_000,
+        );
+        assert_fundraiser_error(result, FundraiserError::FundraiserEnded);
+    }
+
+    #[test]
+    fn test_checker_before_target_fails() {
+        let (mut svm, maker) = setup();
+        let (mint, fundraiser_pda, _bump, _) =
+            do_initialize(&mut svm, &maker, 1_000_000_000, 30);
+        let vault = create_vault(&mut svm, &maker, &fundraiser_pda, &mint);
+        let (contributor, contributor_ata, contributor_state_pda) =
+            setup_contributor(&mut svm, &maker, &mint, &fundraiser_pda, 100_000_000);
+        do_contribute(
+            &mut svm,
+            &contributor,
+            &fundraiser_pda,
+            &vault,
+            &contributor_ata,
+            &contributor_state_pda,
+            100_000_000,
+        );
+
+        let maker_ata = CreateAssociatedTokenAccount::new(&mut svm, &maker, &mint)
+            .owner(&maker.pubkey())
+            .send()
+            .unwrap();
+
+        let ix = checker_ix(&maker.pubkey(), &fundraiser_pda, &vault, &maker_ata);
+        let message = Message::new(&[ix], Some(&maker.pubkey()));
+        let tx = Transaction::new(&[&maker], message, svm.latest_blockhash());
+        assert_fundraiser_error(svm.send_transaction(tx), FundraiserError::TargetNotMet);
+    }
+
+    #[test]
+    fn test_refund_before_end_fails() {
+        let (mut svm, maker) = setup();
+        let (mint, fundraiser_pda, _bump, _) =
+            do_initialize(&mut svm, &maker, 1_000_000_000, 1);
+        let vault = create_vault(&mut svm, &maker, &fundraiser_pda, &mint);
+        let (contributor, contributor_ata, contributor_state_pda) =
+            setup_contributor(&mut svm, &maker, &mint, &fundraiser_pda, 50_000_000);
+        do_contribute(
+            &mut svm,
+            &contributor,
+            &fundraiser_pda,
+            &vault,
+            &contributor_ata,
+            &contributor_state_pda,
+            50_000_000,
+        );
+
+        let ix = refund_ix(
+            &contributor.pubkey(),
+            &maker.pubkey(),
+            &fundraiser_pda,
+            &vault,
+            &contributor_ata,
+            &contributor_state_pda,
+        );
+        let message = Message::new(&[ix], Some(&contributor.pubkey()));
+        let tx = Transaction::new(&[&contributor], message, svm.latest_blockhash());
+        assert_fundraiser_error(svm.send_transaction(tx), FundraiserError::FundraiserNotEnded);
+    }
+
+    #[test]
+    fn test_contribute_rejects_short_data() {
+        let (mut svm, maker) = setup();
+        let (mint, fundraiser_pda, _bump, _) =
+            do_initialize(&mut svm, &maker, 1_000_000_000, 7);
+        let vault = create_vault(&mut svm, &maker, &fundraiser_pda, &mint);
+        let (contributor, contributor_ata, contributor_state_pda) =
+            setup_contributor(&mut svm, &maker, &mint, &fundraiser_pda, 50_000_000);
+
+        let mut ix = contribute_ix(
+            &contributor.pubkey(),
+            &fundraiser_pda,
+            &vault,
+            &contributor_ata,
+            &contributor_state_pda,
+            50_000_000,
+        );
+        ix.data.truncate(5); // discriminator + 4 of the 8 amount bytes
+
+        let message = Message::new(&[ix], Some(&contributor.pubkey()));
+        let tx = Transaction::new(&[&contributor], message, svm.latest_blockhash());
+        let failed = svm.send_transaction(tx).expect_err("short data should fail");
+        assert_eq!(
+            failed.err,
+            TransactionError::InstructionError(0, InstructionError::InvalidInstructionData),
+        );
+    }
+
+    #[test]
+    fn test_keep_what_you_raise() {
+        let (mut svm, maker) = setup();
+        // Flexible 1000-token target, only 50 tokens raised
+        let params = InitParams {
+            mode: 1,
+            ..InitParams::new(1_000_000_000, 1)
+        };
+        let (mint, fundraiser_pda, _bump, _) = do_initialize_with(&mut svm, &maker, &params);
+        let vault = create_vault(&mut svm, &maker, &fundraiser_pda, &mint);
+        let (contributor, contributor_ata, contributor_state_pda) =
+            setup_contributor(&mut svm, &maker, &mint, &fundraiser_pda, 50_000_000);
+        do_contribute(
+            &mut svm,
+            &contributor,
+            &fundraiser_pda,
+            &vault,
+            &contributor_ata,
+            &contributor_state_pda,
+            50_000_000,
+        );
+
+        let maker_ata = CreateAssociatedTokenAccount::new(&mut svm, &maker, &mint)
+            .owner(&maker.pubkey())
+            .send()
+            .unwrap();
+
+        // Below target the maker still has to wait for the end
+        assert_fundraiser_error(
+            send_checker(&mut svm, &maker, &fundraiser_pda, &vault, &maker_ata),
+            FundraiserError::TargetNotMet,
+        );
+
+        // After the end contributors can't refund ...
+        warp_by(&mut svm, 2 * 86400);
+        let ix = refund_ix(
+            &contributor.pubkey(),
+            &maker.pubkey(),
+            &fundraiser_pda,
+            &vault,
+            &contributor_ata,
+            &contributor_state_pda,
+        );
+        let message = Message::new(&[ix], Some(&contributor.pubkey()));
+        let tx = Transaction::new(&[&contributor], message, svm.latest_blockhash());
+        assert_fundraiser_error(svm.send_transaction(tx), FundraiserError::RefundsDisabled);
+
+        // ... and the maker claims what was raised
+        send_checker(&mut svm, &maker, &fundraiser_pda, &vault, &maker_ata).unwrap();
+        assert_eq!(token_balance(&svm, &maker_ata), 50_000_000);
+        assert!(svm.get_account(&fundraiser_pda).is_none());
+    }
+
+    #[test]
+    fn test_all_or_nothing_below_target_after_end_fails() {
+        let (mut svm, maker) = setup();
+        let (mint, fundraiser_pda, _bump, _) =
+            do_initialize(&mut svm, &maker, 1_000_000_000, 1);
+        let vault = create_vault(&mut svm, &maker, &fundraiser_pda, &mint);
+        contribute_from_many(&mut svm, &maker, &mint, &fundraiser_pda, &vault, 1, 50_000_000);
+
+        let maker_ata = CreateAssociatedTokenAccount::new(&mut svm, &maker, &mint)
+            .owner(&maker.pubkey())
+            .send()
+            .unwrap();
+
+        warp_by(&mut svm, 2 * 86400);
+        assert_fundraiser_error(
+            send_checker(&mut svm, &maker, &fundraiser_pda, &vault, &maker_ata),
+            FundraiserError::TargetNotMet,
+        );
+    }
+
+    #[test]
+    fn test_stretch_goal() {
+        let (mut svm, maker) = setup();
+        // Target 1000 units (cap 100 per contributor), stretch goal 1200
+        let params = InitParams {
+            stretch_goal: 1200,
+            ..InitParams::new(1000, 30)
+        };
+        let (mint, fundraiser_pda, _bump, _) = do_initialize_with(&mut svm, &maker, &params);
+        let vault = create_vault(&mut svm, &maker, &fundraiser_pda, &mint);
+        let maker_ata = CreateAssociatedTokenAccount::new(&mut svm, &maker, &mint)
+            .owner(&maker.pubkey())
+            .send()
+            .unwrap();
+
+        // Meeting the target alone keeps the campaign open for the stretch goal
+        contribute_from_many(&mut svm, &maker, &mint, &fundraiser_pda, &vault, 10, 100);
+        assert_fundraiser_error(
+            send_checker(&mut svm, &maker, &fundraiser_pda, &vault, &maker_ata),
+            FundraiserError::TargetNotMet,
+        );
+
+        // Reaching the stretch goal settles early
+        contribute_from_many(&mut svm, &maker, &mint, &fundraiser_pda, &vault, 2, 100);
+        send_checker(&mut svm, &maker, &fundraiser_pda, &vault, &maker_ata).unwrap();
+        assert_eq!(token_balance(&svm, &maker_ata), 1200);
+        assert!(svm.get_account(&fundraiser_pda).is_none());
+    }
+
+    #[test]
+    fn test_stretch_goal_missed_pays_out_at_end() {
+        let (mut svm, maker) = setup();
+        let params = InitParams {
+            stretch_goal: 1200,
+            ..InitParams::new(1000, 1)
+        };
+        let (mint, fundraiser_pda, _bump, _) = do_initialize_with(&mut svm, &maker, &params);
+        let vault = create_vault(&mut svm, &maker, &fundraiser_pda, &mint);
+        let maker_ata = CreateAssociatedTokenAccount::new(&mut svm, &maker, &mint)
+            .owner(&maker.pubkey())
+            .send()
+            .unwrap();
+
+        // Target met, stretch goal missed: the maker claims once it ends
+        contribute_from_many(&mut svm, &maker, &mint, &fundraiser_pda, &vault, 11, 100);
+        warp_by(&mut svm, 2 * 86400);
+        send_checker(&mut svm, &maker, &fundraiser_pda, &vault, &maker_ata).unwrap();
+        assert_eq!(token_balance(&svm, &maker_ata), 1100);
+    }
+
+    #[test]
+    fn test_initialize_rejects_invalid_mode_and_goals() {
+        let (mut svm, maker) = setup();
+
+        let cases = [
+            (2, 0, FundraiserError::InvalidMode),
+            (0, 1000, FundraiserError::InvalidStretchGoal), // the target itself
+            (0, 999, FundraiserError::InvalidStretchGoal), // below the target
+        ];
+        for (mode, stretch_goal, expected) in cases {
+            let params = InitParams {
+                mode,
+                stretch_goal,
+                ..InitParams::new(1000, 7)
+            };
+            let (result, ..) = try_initialize(&mut svm, &maker, &params);
+            assert_fundraiser_error(result, expected);
+        }
+    }
+
+    #[test]
+    fn test_linear_release() {
+        let (mut svm, maker) = setup();
+        // 1000 units vesting over 1000 seconds after settlement
+        let params = InitParams {
+            release: 1,
+            vesting_duration: 1000,
+            ..InitParams::new(1000, 30)
+        };
+        let (mint, fundraiser_pda, _bump, _) = do_initialize_with(&mut svm, &maker, &params);
+        let vault = create_vault(&mut svm, &maker, &fundraiser_pda, &mint);
+        let maker_ata = CreateAssociatedTokenAccount::new(&mut svm, &maker, &mint)
+            .owner(&maker.pubkey())
+            .send()
+            .unwrap();
+        contribute_from_many(&mut svm, &maker, &mint, &fundraiser_pda, &vault, 10, 100);
+
+        // Checker only settles: nothing paid out, fundraiser stays open
+        send_checker(&mut svm, &maker, &fundraiser_pda, &vault, &maker_ata).unwrap();
+        assert_eq!(token_balance(&svm, &maker_ata), 0);
+        assert_eq!(token_balance(&svm, &vault), 1000);
+        assert_fundraiser_error(
+            send_checker(&mut svm, &maker, &fundraiser_pda, &vault, &maker_ata),
+            FundraiserError::AlreadySettled,
+        );
+        assert_fundraiser_error(
+            send_release(&mut svm, &maker, &fundraiser_pda, &vault, &maker_ata),
+            FundraiserError::NothingToRelease,
+        );
+
+        // A quarter of the way through, a quarter is unlocked
+        warp_by(&mut svm, 250);
+        send_release(&mut svm, &maker, &fundraiser_pda, &vault, &maker_ata).unwrap();
+        assert_eq!(token_balance(&svm, &maker_ata), 250);
+        assert_fundraiser_error(
+            send_release(&mut svm, &maker, &fundraiser_pda, &vault, &maker_ata),
+            FundraiserError::NothingToRelease,
+        );
+
+        // After the vesting period the rest is paid and the fundraiser closed
+        warp_by(&mut svm, 2000);
+        send_release(&mut svm, &maker, &fundraiser_pda, &vault, &maker_ata).unwrap();
+        assert_eq!(token_balance(&svm, &maker_ata), 1000);
+        assert_eq!(token_balance(&svm, &vault), 0);
+        assert!(svm.get_account(&fundraiser_pda).is_none());
+    }
+
+    #[test]
+    fn test_milestone_release() {
+        let (mut svm, maker) = setup();
+        let params = InitParams {
+            release: 2,
+            tranches: 2,
+            vote_period: 86400,
+            ..InitParams::new(1000, 30)
+        };
+        let (mint, fundraiser_pda, _bump, _) = do_initialize_with(&mut svm, &maker, &params);
+        let vault = create_vault(&mut svm, &maker, &fundraiser_pda, &mint);
+        let maker_ata = CreateAssociatedTokenAccount::new(&mut svm, &maker, &mint)
+            .owner(&maker.pubkey())
+            .send()
+            .unwrap();
+        let contributors =
+            contribute_from_many(&mut svm, &maker, &mint, &fundraiser_pda, &vault, 10, 100);
+
+        // No voting before settlement
+        let (first, first_state) = &contributors[0];
+        assert_fundraiser_error(
+            send_approve_tranche(&mut svm, first, &fundraiser_pda, first_state),
+            FundraiserError::NotSettled,
+        );
+        send_checker(&mut svm, &maker, &fundraiser_pda, &vault, &maker_ata).unwrap();
+
+        // Half of the weight isn't a majority yet
+        for (contributor, state) in &contributors[..5] {
+            send_approve_tranche(&mut svm, contributor, &fundraiser_pda, state).unwrap();
+        }
+        assert_fundraiser_error(
+            send_approve_tranche(&mut svm, first, &fundraiser_pda, first_state),
+            FundraiserError::AlreadyVoted,
+        );
+        assert_fundraiser_error(
+            send_release(&mut svm, &maker, &fundraiser_pda, &vault, &maker_ata),
+            FundraiserError::NothingToRelease,
+        );
+
+        // The sixth vote approves the first tranche
+        let (sixth, sixth_state) = &contributors[5];
+        send_approve_tranche(&mut svm, sixth, &fundraiser_pda, sixth_state).unwrap();
+        send_release(&mut svm, &maker, &fundraiser_pda, &vault, &maker_ata).unwrap();
+        assert_eq!(token_balance(&svm, &maker_ata), 500);
+
+        // Contributors vote again for the second tranche
+        for (contributor, state) in &contributors[4..] {
+            send_approve_tranche(&mut svm, contributor, &fundraiser_pda, state).unwrap();
+        }
+        send_release(&mut svm, &maker, &fundraiser_pda, &vault, &maker_ata).unwrap();
+        assert_eq!(token_balance(&svm, &maker_ata), 1000);
+        assert!(svm.get_account(&fundraiser_pda).is_none());
+    }
+
+    #[test]
+    fn test_vote_weight_ignores_foreign_contributions() {
+        let (mut svm, maker) = setup();
+        let params = InitParams {
+            release: 2,
+            tranches: 2,
+            vote_period: 86400,
+            ..InitParams::new(1000, 30)
+        };
+        let (mint, fundraiser_pda, _bump, _) = do_initialize_with(&mut svm, &maker, &params);
+        let vault = create_vault(&mut svm, &maker, &fundraiser_pda, &mint);
+        let maker_ata = CreateAssociatedTokenAccount::new(&mut svm, &maker, &mint)
+            .owner(&maker.pubkey())
+            .send()
+            .unwrap();
+        let (voter, voter_ata, voter_state_pda) =
+            setup_contributor(&mut svm, &maker, &mint, &fundraiser_pda, 100);
+        do_contribute(
+            &mut svm,
+            &voter,
+            &fundraiser_pda,
+            &vault,
+            &voter_ata,
+            &voter_state_pda,
+            100,
+        );
+
+        // Contributing to another fundraiser can't add weight to this state
+        let other_maker = Keypair::new();
+        svm.airdrop(&other_maker.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+        let (other_mint, other_pda, _bump, _) =
+            do_initialize(&mut svm, &other_maker, 1_000_000_000, 30);
+        let other_vault = create_vault(&mut svm, &other_maker, &other_pda, &other_mint);
+        let voter_other_ata = CreateAssociatedTokenAccount::new(&mut svm, &voter, &other_mint)
+            .owner(&voter.pubkey())
+            .send()
+            .unwrap();
+        MintTo::new(&mut svm, &other_maker, &other_mint, &voter_other_ata, 100_000_000)
+            .send()
+            .unwrap();
+        let result = send_contribute(
+            &mut svm,
+            &voter,
+            &other_pda,
+            &other_vault,
+            &voter_other_ata,
+            &voter_state_pda,
+            100_000_000,
+        );
+        assert_fundraiser_error(result, FundraiserError::InvalidContributorPda);
+
+        contribute_from_many(&mut svm, &maker, &mint, &fundraiser_pda, &vault, 9, 100);
+        send_checker(&mut svm, &maker, &fundraiser_pda, &vault, &maker_ata).unwrap();
+
+        // The vote weighs only what was contributed here: no majority
+        send_approve_tranche(&mut svm, &voter, &fundraiser_pda, &voter_state_pda).unwrap();
+        let data = svm.get_account(&fundraiser_pda).unwrap().data;
+        assert_eq!(data[100], 0); // tranches_approved
+        assert_eq!(u64::from_le_bytes(data[144..152].try_into().unwrap()), 100); // votes
+    }
+
+    #[test]
+    fn test_refund_unapproved_tranches_after_voting_closes() {
+        let (mut svm, maker) = setup();
+        let params = InitParams {
+            release: 2,
+            tranches: 2,
+            vote_period: 86400,
+            ..InitParams::new(1000, 30)
+        };
+        let (mint, fundraiser_pda, _bump, _) = do_initialize_with(&mut svm, &maker, &params);
+        let vault = create_vault(&mut svm, &maker, &fundraiser_pda, &mint);
+        let maker_ata = CreateAssociatedTokenAccount::new(&mut svm, &maker, &mint)
+            .owner(&maker.pubkey())
+            .send()
+            .unwrap();
+        let contributors =
+            contribute_from_many(&mut svm, &maker, &mint, &fundraiser_pda, &vault, 10, 100);
+        send_checker(&mut svm, &maker, &fundraiser_pda, &vault, &maker_ata).unwrap();
+
+        // Only the first tranche is approved and released
+        for (contributor, state) in &contributors[..6] {
+            send_approve_tranche(&mut svm, contributor, &fundraiser_pda, state).unwrap();
+        }
+        send_release(&mut svm, &maker, &fundraiser_pda, &vault, &maker_ata).unwrap();
+        assert_eq!(token_balance(&svm, &maker_ata), 500);
+
+        let (first, first_state) = &contributors[0];
+        let first_ata =
+            spl_associated_token_account::get_associated_token_address(&first.pubkey(), &mint);
+        let refund = |svm: &mut LiteSVM| {
+            let ix = refund_ix(
+                &first.pubkey(),
+                &maker.pubkey(),
+                &fundraiser_pda,
+                &vault,
+                &first_ata,
+                first_state,
+            );
+            let message = Message::new(&[ix], Some(&first.pubkey()));
+            svm.expire_blockhash();
+            let tx = Transaction::new(&[first], message, svm.latest_blockhash());
+            svm.send_transaction(tx)
+        };
+
+        // The second tranche can still be approved until voting closes
+        assert_fundraiser_error(refund(&mut svm), FundraiserError::VotingOpen);
+
+        warp_by(&mut svm, 86400);
+        assert_fundraiser_error(
+            send_approve_tranche(&mut svm, first, &fundraiser_pda, first_state),
+            FundraiserError::VotingClosed,
+        );
+
+        // The contributor gets back their share of the locked tranche
+        refund(&mut svm).unwrap();
+        assert_eq!(token_balance(&svm, &first_ata), 50);
+        assert_eq!(token_balance(&svm, &vault), 450);
+        assert!(svm.get_account(first_state).is_none());
+        assert_fundraiser_error(
+            send_release(&mut svm, &maker, &fundraiser_pda, &vault, &maker_ata),
+            FundraiserError::NothingToRelease,
+        );
+    }
+
+    #[test]
+    fn test_release_rejections() {
+        let (mut svm, maker) = setup();
+
+        let cases = [
+            (3, 0, 0, 0),       // unknown schedule
+            (0, 2, 0, 0),       // immediate with tranches
+            (0, 0, 0, 100),     // immediate with a vote period
+            (1, 0, 0, 0),       // linear without a vesting duration
+            (1, 0, -5, 0),      // negative vesting duration
+            (1, 0, 100, 100),   // linear with a vote period
+            (2, 0, 0, 100),     // milestones without tranches
+            (2, 11, 0, 100),    // more than MAX_TRANCHES
+            (2, 2, 100, 100),   // milestones with a vesting duration
+            (2, 2, 0, 0),       // milestones without a vote period
+        ];
+        for (release, tranches, vesting_duration, vote_period) in cases {
+            let params = InitParams {
+                release,
+                tranches,
+                vesting_duration,
+                vote_period,
+                ..InitParams::new(1000, 30)
+            };
+            let (result, ..) = try_initialize(&mut svm, &maker, &params);
+            assert_fundraiser_error(result, FundraiserError::InvalidReleaseSchedule);
+        }
+
+        // Linear fundraiser: no release before settlement, no votes ever
+        let params = InitParams {
+            release: 1,
+            vesting_duration: 1000,
+            ..InitParams::new(1000, 30)
+        };
+        let (mint, fundraiser_pda, _bump, _) = do_initialize_with(&mut svm, &maker, &params);
+        let vault = create_vault(&mut svm, &maker, &fundraiser_pda, &mint);
+        let maker_ata = CreateAssociatedTokenAccount::new(&mut svm, &maker, &mint)
+            .owner(&maker.pubkey())
+            .send()
+            .unwrap();
+        let contributors =
+            contribute_from_many(&mut svm, &maker, &mint, &fundraiser_pda, &vault, 10, 100);
+        assert_fundraiser_error(
+            send_release(&mut svm, &maker, &fundraiser_pda, &vault, &maker_ata),
+            FundraiserError::NotSettled,
+        );
+
+        send_checker(&mut svm, &maker, &fundraiser_pda, &vault, &maker_ata).unwrap();
+        let (contributor, state) = &contributors[0];
+        assert_fundraiser_error(
+            send_approve_tranche(&mut svm, contributor, &fundraiser_pda, state),
+            FundraiserError::NoPendingTranche,
+        );
+    }
+
+    #[test]
+    fn test_contribution_limits() {
+        let (mut svm, maker) = setup();
+        // Absolute cap of 300 per contributor, at least 50 per contribution,
+        // and no more than 1000 raised in total
+        let params = InitParams {
+            max_contribution: 300,
+            min_contribution: 50,
+            hard_cap: 1000,
+            ..InitParams::new(1000, 30)
+        };
+        let (mint, fundraiser_pda, _bump, _) = do_initialize_with(&mut svm, &maker, &params);
+        let vault = create_vault(&mut svm, &maker, &fundraiser_pda, &mint);
+
+        let (contributor, contributor_ata, contributor_state_pda) =
+            setup_contributor(&mut svm, &maker, &mint, &fundraiser_pda, 1000);
+        let cases = [
+            (49, FundraiserError::ContributionTooSmall),
+            (301, FundraiserError::ContributionTooLarge),
+        ];
+        for (amount, expected) in cases {
+            let result = send_contribute(
+                &mut svm,
+                &contributor,
+                &fundraiser_pda,
+                &vault,
+                &contributor_ata,
+                &contributor_state_pda,
+                amount,
+            );
+            assert_fundraiser_error(result, expected);
+        }
+
+        // 900 raised, so only 100 more fit under the hard cap
+        contribute_from_many(&mut svm, &maker, &mint, &fundraiser_pda, &vault, 3, 300);
+        let result = send_contribute(
+            &mut svm,
+            &contributor,
+            &fundraiser_pda,
+            &vault,
+            &contributor_ata,
+            &contributor_state_pda,
+            200,
+        );
+        assert_fundraiser_error(result, FundraiserError::HardCapExceeded);
+        do_contribute(
+            &mut svm,
+            &contributor,
+            &fundraiser_pda,
+            &vault,
+            &contributor_ata,
+            &contributor_state_pda,
+            100,
+        );
+        assert_eq!(token_balance(&svm, &vault), 1000);
+    }
+
+    #[test]
+    fn test_contribution_cap_percentage() {
+        let (mut svm, maker) = setup();
+        let params = InitParams {
+            cap_percentage: 50,
+            ..InitParams::new(1000, 30)
+        };
+        let (mint, fundraiser_pda, _bump, _) = do_initialize_with(&mut svm, &maker, &params);
+        let vault = create_vault(&mut svm, &maker, &fundraiser_pda, &mint);
+
+        let (contributor, contributor_ata, contributor_state_pda) =
+            setup_contributor(&mut svm, &maker, &mint, &fundraiser_pda, 1000);
+        let result = send_contribute(
+            &mut svm,
+            &contributor,
+            &fundraiser_pda,
+            &vault,
+            &contributor_ata,
+            &contributor_state_pda,
+            501,
+        );
+        assert_fundraiser_error(result, FundraiserError::ContributionTooLarge);
+
+        // Two contributors at the 50% cap meet the target
+        contribute_from_many(&mut svm, &maker, &mint, &fundraiser_pda, &vault, 2, 500);
+        assert_eq!(token_balance(&svm, &vault), 1000);
+    }
+
+    #[test]
+    fn test_initialize_rejects_invalid_limits() {
+        let (mut svm, maker) = setup();
+
+        let base = InitParams::new(1000, 30);
+        let cases = [
+            // Percentage and absolute cap both set
+            InitParams { cap_percentage: 20, max_contribution: 200, ..base },
+            // More than 100%
+            InitParams { cap_percentage: 101, ..base },
+            // Minimum above the per-contributor cap
+            InitParams { min_contribution: 101, ..base },
+            // Hard cap below the target
+            InitParams { hard_cap: 999, ..base },
+            // Hard cap below the stretch goal
+            InitParams { stretch_goal: 1500, hard_cap: 1200, ..base },
+        ];
+        for params in cases {
+            let (result, ..) = try_initialize(&mut svm, &maker, &params);
+            assert_fundraiser_error(result, FundraiserError::InvalidContributionLimits);
+        }
+    }
+
+    #[test]
+    fn test_scheduled_campaign_window() {
+        let (mut svm, maker) = setup();
+        // Opens in an hour and runs for two hours
+        let params = InitParams {
+            starts_in: 3600,
+            duration: 7200,
+            ..InitParams::new(1_000_000_000, 0)
+        };
+        let (mint, fundraiser_pda, _bump, _) = do_initialize_with(&mut svm, &maker, &params);
+        let vault = create_vault(&mut svm, &maker, &fundraiser_pda, &mint);
+        let (contributor, contributor_ata, contributor_state_pda) =
+            setup_contributor(&mut svm, &maker, &mint, &fundraiser_pda, 100_000_000);
+
+        let contribute = |svm: &mut LiteSVM, amount: u64| {
+            send_contribute(
+                svm,
+                &contributor,
+                &fundraiser_pda,
+                &vault,
+                &contributor_ata,
+                &contributor_state_pda,
+                amount,
+            )
+        };
+
+        // One second before the start is still too early
+        warp_by(&mut svm, 3599);
+        assert_fundraiser_error(
+            contribute(&mut svm, 10_000_000),
+            FundraiserError::FundraiserNotStarted,
+        );
+
+        // Open from the start second up to the last second before the end
+        warp_by(&mut svm, 1);
+        contribute(&mut svm, 20_000_000).unwrap();
+        warp_by(&mut svm, 7199);
+        contribute(&mut svm, 30_000_000).unwrap();
+
+        // Closed from the end second on
+        warp_by(&mut svm, 1);
+        assert_fundraiser_error(
+            contribute(&mut svm, 40_000_000),
+            FundraiserError::FundraiserEnded,
+        );
+        assert_eq!(token_balance(&svm, &vault), 50_000_000);
+
+        // Refunds open at the end second as well
+        let ix = refund_ix(
+            &contributor.pubkey(),
+            &maker.pubkey(),
+            &fundraiser_pda,
+            &vault,
+            &contributor_ata,
+            &contributor_state_pda,
+        );
+        let message = Message::new(&[ix], Some(&contributor.pubkey()));
+        let tx = Transaction::new(&[&contributor], message, svm.latest_blockhash());
+        svm.send_transaction(tx).unwrap();
+        assert_eq!(token_balance(&svm, &contributor_ata), 100_000_000);
+    }
+
+    #[test]
+    fn test_initialize_rejects_invalid_window() {
+        let (mut svm, maker) = setup();
+
+        let cases = [
+            (0, 0),      // ends when it starts
+            (3600, -60), // ends before it starts
+            (-200, 100), // already over
+        ];
+        for (starts_in, duration) in cases {
+            let params = InitParams {
+                starts_in,
+                duration,
+                ..InitParams::new(1000, 0)
+            };
+            let (result, ..) = try_initialize(&mut svm, &maker, &params);
+            assert_fundraiser_error(result, FundraiserError::InvalidCampaignWindow);
+        }
+    }
+
+    #[test]
+    fn test_withdraw() {
+        let (mut svm, maker) = setup();
+        let (mint, fundraiser_pda, _bump, _) = do_initialize(&mut svm, &maker, 1_000_000_000, 7);
+        let vault = create_vault(&mut svm, &maker, &fundraiser_pda, &mint);
+        let (contributor, contributor_ata, contributor_state_pda) =
+            setup_contributor(&mut svm, &maker, &mint, &fundraiser_pda, 100_000_000);
+        do_contribute(
+            &mut svm,
+            &contributor,
+            &fundraiser_pda,
+            &vault,
+            &contributor_ata,
+            &contributor_state_pda,
+            100_000_000,
+        );
+
+        // Partial withdrawal; without a penalty maker_ata is never touched
+        send_withdraw(
+            &mut svm,
+            &contributor,
+            &maker.pubkey(),
+            &fundraiser_pda,
+            &vault,
+            &contributor_ata,
+            &contributor_state_pda,
+            &contributor_ata,
+            40_000_000,
+        )
+        .unwrap();
+        assert_eq!(token_balance(&svm, &contributor_ata), 40_000_000);
+        assert_eq!(token_balance(&svm, &vault), 60_000_000);
+        let state = svm.get_account(&contributor_state_pda).unwrap();
+        assert_eq!(u64::from_le_bytes(state.data[32..40].try_into().unwrap()), 60_000_000);
+        let fundraiser = svm.get_account(&fundraiser_pda).unwrap();
+        assert_eq!(u64::from_le_bytes(fundraiser.data[72..80].try_into().unwrap()), 60_000_000);
+
+        // Withdrawing the rest closes the contributor state
+        send_withdraw(
+            &mut svm,
+            &contributor,
+            &maker.pubkey(),
+            &fundraiser_pda,
+            &vault,
+            &contributor_ata,
+            &contributor_state_pda,
+            &contributor_ata,
+            60_000_000,
+        )
+        .unwrap();
+        assert_eq!(token_balance(&svm, &contributor_ata), 100_000_000);
+        assert_eq!(token_balance(&svm, &vault), 0);
+        assert!(svm.get_account(&contributor_state_pda).is_none());
+        let fundraiser = svm.get_account(&fundraiser_pda).unwrap();
+        assert_eq!(u64::from_le_bytes(fundraiser.data[72..80].try_into().unwrap()), 0);
+    }
+
+    #[test]
+    fn test_withdraw_with_penalty() {
+        let (mut svm, maker) = setup();
+        // 5% of every withdrawal goes to the maker
+        let params = InitParams {
+            withdraw_penalty_bps: 500,
+            ..InitParams::new(1_000_000_000, 7)
+        };
+        let (mint, fundraiser_pda, _bump, _) = do_initialize_with(&mut svm, &maker, &params);
+        let vault = create_vault(&mut svm, &maker, &fundraiser_pda, &mint);
+        let maker_ata = CreateAssociatedTokenAccount::new(&mut svm, &maker, &mint)
+            .owner(&maker.pubkey())
+            .send()
+            .unwrap();
+        let (contributor, contributor_ata, contributor_state_pda) =
+            setup_contributor(&mut svm, &maker, &mint, &fundraiser_pda, 100_000_000);
+        do_contribute(
+            &mut svm,
+            &contributor,
+            &fundraiser_pda,
+            &vault,
+            &contributor_ata,
+            &contributor_state_pda,
+            100_000_000,
+        );
+
+        // The penalty can't be redirected to another token account
+        let result = send_withdraw(
+            &mut svm,
+            &contributor,
+            &maker.pubkey(),
+            &fundraiser_pda,
+            &vault,
+            &contributor_ata,
+            &contributor_state_pda,
+            &contributor_ata,
+            100_000_000,
+        );
+        assert_fundraiser_error(result, FundraiserError::InvalidMakerAta);
+
+        send_withdraw(
+            &mut svm,
+            &contributor,
+            &maker.pubkey(),
+            &fundraiser_pda,
+            &vault,
+            &contributor_ata,
+            &contributor_state_pda,
+            &maker_ata,
+            100_000_000,
+        )
+        .unwrap();
+        assert_eq!(token_balance(&svm, &contributor_ata), 95_000_000);
+        assert_eq!(token_balance(&svm, &maker_ata), 5_000_000);
+        assert_eq!(token_balance(&svm, &vault), 0);
+        assert!(svm.get_account(&contributor_state_pda).is_none());
+    }
+
+    #[test]
+    fn test_withdraw_rejections() {
+        let (mut svm, maker) = setup();
+
+        let params = InitParams {
+            withdraw_penalty_bps: 10_001,
+            ..InitParams::new(1_000_000_000, 7)
+        };
+        let (result, ..) = try_initialize(&mut svm, &maker, &params);
+        assert_fundraiser_error(result, FundraiserError::InvalidWithdrawPenalty);
+
+        let (mint, fundraiser_pda, _bump, _) = do_initialize(&mut svm, &maker, 1_000_000_000, 7);
+        let vault = create_vault(&mut svm, &maker, &fundraiser_pda, &mint);
+        let (contributor, contributor_ata, contributor_state_pda) =
+            setup_contributor(&mut svm, &maker, &mint, &fundraiser_pda, 100_000_000);
+        do_contribute(
+            &mut svm,
+            &contributor,
+            &fundraiser_pda,
+            &vault,
+            &contributor_ata,
+            &contributor_state_pda,
+            50_000_000,
+        );
+
+        // Nothing, or more than was contributed
+        for amount in [0, 50_000_001] {
+            let result = send_withdraw(
+                &mut svm,
+                &contributor,
+                &maker.pubkey(),
+                &fundraiser_pda,
+                &vault,
+                &contributor_ata,
+                &contributor_state_pda,
+                &contributor_ata,
+                amount,
+            );
+            assert_fundraiser_error(result, FundraiserError::InvalidWithdrawAmount);
+        }
+
+        // Another contributor can't withdraw from this contributor's state
+        let (other, other_ata, _) =
+            setup_contributor(&mut svm, &maker, &mint, &fundraiser_pda, 0);
+        let result = send_withdraw(
+            &mut svm,
+            &other,
+            &maker.pubkey(),
+            &fundraiser_pda,
+            &vault,
+            &other_ata,
+            &contributor_state_pda,
+            &other_ata,
+            50_000_000,
+        );
+        assert_fundraiser_error(result, FundraiserError::InvalidContributorPda);
+
+        // No withdrawals once the fundraiser has ended
+        warp_by(&mut svm, 7 * 86400);
+        let result = send_withdraw(
+            &mut svm,
+            &contributor,
+            &maker.pubkey(),
+            &fundraiser_pda,
+            &vault,
+            &contributor_ata,
+            &contributor_state_pda,
+            &contributor_ata,
+            50_000_000,
+        );
+        assert_fundraiser_error(result, FundraiserError::FundraiserEnded);
+    }
+
+    #[test]
+    fn test_contribute_rejects_foreign_contributor_state() {
+        let (mut svm, maker) = setup();
+        let (mint, fundraiser_pda, _bump, _) = do_initialize(&mut svm, &maker, 1_000_000_000, 7);
+        let vault = create_vault(&mut svm, &maker, &fundraiser_pda, &mint);
+        contribute_from_many(&mut svm, &maker, &mint, &fundraiser_pda, &vault, 1, 50_000_000);
+        let (attacker, attacker_ata, attacker_state_pda) =
+            setup_contributor(&mut svm, &maker, &mint, &fundraiser_pda, 10_000_000);
+        do_contribute(
+            &mut svm,
+            &attacker,
+            &fundraiser_pda,
+            &vault,
+            &attacker_ata,
+            &attacker_state_pda,
+            10_000_000,
+        );
+
+        // A second fundraiser over a cheap mint the attacker can fund
+        let other_maker = Keypair::new();
+        svm.airdrop(&other_maker.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+        let (other_mint, other_pda, _bump, _) =
+            do_initialize(&mut svm, &other_maker, 1_000_000_000, 7);
+        let other_vault = create_vault(&mut svm, &other_maker, &other_pda, &other_mint);
+        let attacker_other_ata =
+            CreateAssociatedTokenAccount::new(&mut svm, &attacker, &other_mint)
+                .owner(&attacker.pubkey())
+                .send()
+                .unwrap();
+        MintTo::new(&mut svm, &other_maker, &other_mint, &attacker_other_ata, 50_000_000)
+            .send()
+            .unwrap();
+
+        // Contributing there must not credit the state from the first fundraiser
+        let result = send_contribute(
+            &mut svm,
+            &attacker,
+            &other_pda,
+            &other_vault,
+            &attacker_other_ata,
+            &attacker_state_pda,
+            50_000_000,
+        );
+        assert_fundraiser_error(result, FundraiserError::InvalidContributorPda);
+        let state = svm.get_account(&attacker_state_pda).unwrap();
+        assert_eq!(u64::from_le_bytes(state.data[32..40].try_into().unwrap()), 10_000_000);
+
+        // So the attacker can't withdraw more than they put into the first vault
+        let result = send_withdraw(
+            &mut svm,
+            &attacker,
+            &maker.pubkey(),
+            &fundraiser_pda,
+            &vault,
+            &attacker_ata,
+            &attacker_state_pda,
+            &attacker_ata,
+            60_000_000,
+        );
+        assert_fundraiser_error(result, FundraiserError::InvalidWithdrawAmount);
+        assert_eq!(token_balance(&svm, &vault), 60_000_000);
+    }
+
+    #[test]
+    fn test_donation_cannot_unlock_checker() {
+        let (mut svm, maker) = setup();
+        let (mint, fundraiser_pda, _bump, _) = do_initialize(&mut svm, &maker, 1_000_000_000, 7);
+        let vault = create_vault(&mut svm, &maker, &fundraiser_pda, &mint);
+        contribute_from_many(&mut svm, &maker, &mint, &fundraiser_pda, &vault, 1, 50_000_000);
+        let maker_ata = CreateAssociatedTokenAccount::new(&mut svm, &maker, &mint)
+            .owner(&maker.pubkey())
+            .send()
+            .unwrap();
+
+        // Tokens sent straight to the vault cover the target ...
+        MintTo::new(&mut svm, &maker, &mint, &vault, 1_000_000_000)
+            .send()
+            .unwrap();
+
+        // ... but only contributions count towards it
+        assert_fundraiser_error(
+            send_checker(&mut svm, &maker, &fundraiser_pda, &vault, &maker_ata),
+            FundraiserError::TargetNotMet,
+        );
+        warp_by(&mut svm, 7 * 86400);
+        assert_fundraiser_error(
+            send_checker(&mut svm, &maker, &fundraiser_pda, &vault, &maker_ata),
+            FundraiserError::TargetNotMet,
+        );
+    }
+
+    #[test]
+    fn test_donation_cannot_block_refund() {
+        let (mut svm, maker) = setup();
+        let (mint, fundraiser_pda, _bump, _) = do_initialize(&mut svm, &maker, 1_000_000_000, 7);
+        let vault = create_vault(&mut svm, &maker, &fundraiser_pda, &mint);
+        let (contributor, contributor_ata, contributor_state_pda) =
+            setup_contributor(&mut svm, &maker, &mint, &fundraiser_pda, 50_000_000);
+        do_contribute(
+            &mut svm,
+            &contributor,
+            &fundraiser_pda,
+            &vault,
+            &contributor_ata,
+            &contributor_state_pda,
+            50_000_000,
+        );
+
+        // A griefer tops the vault up past the target after the end
+        warp_by(&mut svm, 7 * 86400);
+        MintTo::new(&mut svm, &maker, &mint, &vault, 1_000_000_000)
+            .send()
+            .unwrap();
+
+        // The target still wasn't met by contributions, so refunds go through
+        let ix = refund_ix(
+            &contributor.pubkey(),
+            &maker.pubkey(),
+            &fundraiser_pda,
+            &vault,
+            &contributor_ata,
+            &contributor_state_pda,
+        );
+        let message = Message::new(&[ix], Some(&contributor.pubkey()));
+        let tx = Transaction::new(&[&contributor], message, svm.latest_blockhash());
+        svm.send_transaction(tx).unwrap();
+        assert_eq!(token_balance(&svm, &contributor_ata), 50_000_000);
+        assert_eq!(token_balance(&svm, &vault), 1_000_000_000);
+    }
+
+    #[test]
+    fn test_donations_swept_after_refunds() {
+        let (mut svm, maker) = setup();
+        let (mint, fundraiser_pda, _bump, _) = do_initialize(&mut svm, &maker, 1_000_000_000, 7);
+        let vault = create_vault(&mut svm, &maker, &fundraiser_pda, &mint);
+        let (contributor, contributor_ata, contributor_state_pda) =
+            setup_contributor(&mut svm, &maker, &mint, &fundraiser_pda, 50_000_000);
+        do_contribute(
+            &mut svm,
+            &contributor,
+            &fundraiser_pda,
+            &vault,
+            &contributor_ata,
+            &contributor_state_pda,
+            50_000_000,
+        );
+        MintTo::new(&mut svm, &maker, &mint, &vault, 5_000_000)
+            .send()
+            .unwrap();
+        let maker_ata = CreateAssociatedTokenAccount::new(&mut svm, &maker, &mint)
+            .owner(&maker.pubkey())
+            .send()
+            .unwrap();
+
+        // The failed raise still owes its contributor
+        warp_by(&mut svm, 7 * 86400);
+        assert_fundraiser_error(
+            send_checker(&mut svm, &maker, &fundraiser_pda, &vault, &maker_ata),
+            FundraiserError::TargetNotMet,
+        );
+
+        let ix = refund_ix(
+            &contributor.pubkey(),
+            &maker.pubkey(),
+            &fundraiser_pda,
+            &vault,
+            &contributor_ata,
+            &contributor_state_pda,
+        );
+        let message = Message::new(&[ix], Some(&contributor.pubkey()));
+        let tx = Transaction::new(&[&contributor], message, svm.latest_blockhash());
+        svm.send_transaction(tx).unwrap();
+
+        // Once everything is refunded, the maker sweeps the donation
+        send_checker(&mut svm, &maker, &fundraiser_pda, &vault, &maker_ata).unwrap();
+        assert_eq!(token_balance(&svm, &maker_ata), 5_000_000);
+        assert_eq!(token_balance(&svm, &vault), 0);
+        assert!(svm.get_account(&fundraiser_pda).is_none());
+    }
+
+    #[test]
+    fn test_rejects_foreign_vault() {
+        let (mut svm, maker) = setup();
+        let (mint, fundraiser_pda, _bump, _) = do_initialize(&mut svm, &maker, 1000, 7);
+        let vault = create_vault(&mut svm, &maker, &fundraiser_pda, &mint);
+        let maker_ata = CreateAssociatedTokenAccount::new(&mut svm, &maker, &mint)
+            .owner(&maker.pubkey())
+            .send()
+            .unwrap();
+
+        // The fundraiser's token account for another mint
+        let other_mint = CreateMint::new(&mut svm, &maker)
+            .decimals(6)
+            .authority(&maker.pubkey())
+            .send()
+            .unwrap();
+        let other_mint_vault = create_vault(&mut svm, &maker, &fundraiser_pda, &other_mint);
+
+        // A token account for the right mint and owner that isn't the ATA
+        let side_account = CreateAccount::new(&mut svm, &maker, &mint)
+            .owner(&fundraiser_pda)
+            .send()
+            .unwrap();
+
+        // Contributions can't be credited without reaching the vault
+        let (contributor, contributor_ata, contributor_state_pda) =
+            setup_contributor(&mut svm, &maker, &mint, &fundraiser_pda, 100);
+        for wrong_vault in [maker_ata, other_mint_vault, side_account] {
+            let result = send_contribute(
+                &mut svm,
+                &contributor,
+                &fundraiser_pda,
+                &wrong_vault,
+                &contributor_ata,
+                &contributor_state_pda,
+                100,
+            );
+            assert_fundraiser_error(result, FundraiserError::InvalidVault);
+        }
+
+        // Checker and refund only accept the fundraiser's vault too
+        contribute_from_many(&mut svm, &maker, &mint, &fundraiser_pda, &vault, 10, 100);
+        assert_fundraiser_error(
+            send_checker(&mut svm, &maker, &fundraiser_pda, &maker_ata, &maker_ata),
+            FundraiserError::InvalidVault,
+        );
+        assert_fundraiser_error(
+            send_checker(&mut svm, &maker, &fundraiser_pda, &side_account, &maker_ata),
+            FundraiserError::InvalidVault,
+        );
+        send_checker(&mut svm, &maker, &fundraiser_pda, &vault, &maker_ata).unwrap();
+        assert_eq!(token_balance(&svm, &maker_ata), 1000);
+    }
+
+    #[test]
+    fn test_refund_rejects_other_contributor_state() {
+        let (mut svm, maker) = setup();
+        let (mint, fundraiser_pda, _bump, _) = do_initialize(&mut svm, &maker, 1_000_000_000, 7);
+        let vault = create_vault(&mut svm, &maker, &fundraiser_pda, &mint);
+        let (contributor, contributor_ata, contributor_state_pda) =
+            setup_contributor(&mut svm, &maker, &mint, &fundraiser_pda, 50_000_000);
+        do_contribute(
+            &mut svm,
+            &contributor,
+            &fundraiser_pda,
+            &vault,
+            &contributor_ata,
+            &contributor_state_pda,
+            50_000_000,
+        );
+        let (thief, thief_ata, _) = setup_contributor(&mut svm, &maker, &mint, &fundraiser_pda, 0);
+
+        warp_by(&mut svm, 7 * 86400);
+        let ix = refund_ix(
+            &thief.pubkey(),
+            &maker.pubkey(),
+            &fundraiser_pda,
+            &vault,
+            &thief_ata,
+            &contributor_state_pda,
+        );
+        let message = Message::new(&[ix], Some(&thief.pubkey()));
+        let tx = Transaction::new(&[&thief], message, svm.latest_blockhash());
+        assert_fundraiser_error(
+            svm.send_transaction(tx),
+            FundraiserError::InvalidContributorPda,
+        );
+        assert_eq!(token_balance(&svm, &vault), 50_000_000);
+    }
+
+    #[test]
+    fn test_contribute_rejects_other_contributor_state() {
+        let (mut svm, maker) = setup();
+        let (mint, fundraiser_pda, _bump, _) = do_initialize(&mut svm, &maker, 1_000_000_000, 7);
+        let vault = create_vault(&mut svm, &maker, &fundraiser_pda, &mint);
+        let (_, _, contributor_state_pda) =
+            setup_contributor(&mut svm, &maker, &mint, &fundraiser_pda, 0);
+        let (other, other_ata, _) =
+            setup_contributor(&mut svm, &maker, &mint, &fundraiser_pda, 50_000_000);
+
+        // Crediting someone else's state would let them withdraw or refund it
+        let result = send_contribute(
+            &mut svm,
+            &other,
+            &fundraiser_pda,
+            &vault,
+            &other_ata,
+            &contributor_state_pda,
+            50_000_000,
+        );
+        assert_fundraiser_error(result, FundraiserError::InvalidContributorPda);
+        assert_eq!(token_balance(&svm, &vault), 0);
+    }
 }
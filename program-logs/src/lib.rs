@@ -0,0 +1,77 @@
+//! Reads the `Program data:` payloads a program logged during a transaction.
+//!
+//! Shared by the escrow clients so the log-frame walk lives in one place.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+/// Decodes the `Program data:` payloads logged by `program_id`, in order.
+///
+/// Only lines logged while `program_id` is the innermost executing program
+/// are read, so another program in the transaction cannot forge the first
+/// program's events by logging the same bytes. Lines that aren't valid base64
+/// are skipped.
+pub fn program_data<S: AsRef<str>>(logs: &[S], program_id: &str) -> Vec<Vec<u8>> {
+    let mut stack: Vec<&str> = Vec::new();
+    let mut payloads = Vec::new();
+
+    for line in logs.iter().map(AsRef::as_ref) {
+        if let Some(data) = line.strip_prefix("Program data: ") {
+            if stack.last() != Some(&program_id) {
+                continue;
+            }
+            let Some(field) = data.split(' ').next() else { continue };
+            if let Ok(payload) = STANDARD.decode(field) {
+                payloads.push(payload);
+            }
+        } else if let Some(rest) = line.strip_prefix("Program ") {
+            // `Program log:`/`Program return:` lines are program output, not frames
+            let mut words = rest.split(' ');
+            match (words.next(), words.next()) {
+                (Some(id), _) if id.ends_with(':') => {}
+                (Some(id), Some("invoke")) => stack.push(id),
+                (Some(_), Some("success" | "failed:")) => {
+                    stack.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+    payloads
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROGRAM: &str = "Escrow1111111111111111111111111111111111111";
+    const OTHER: &str = "Other11111111111111111111111111111111111111";
+
+    #[test]
+    fn reads_only_the_programs_own_frames() {
+        let logs = [
+            format!("Program {PROGRAM} invoke [1]"),
+            "Program log: Instruction: Take".to_string(),
+            "Program data: AQI=".to_string(),
+            format!("Program {OTHER} invoke [2]"),
+            "Program data: AwQ=".to_string(),
+            format!("Program {OTHER} success"),
+            "Program data: BQY= Bwg=".to_string(),
+            "Program data: not base64!".to_string(),
+            format!("Program {PROGRAM} success"),
+            "Program data: CQo=".to_string(),
+        ];
+        assert_eq!(program_data(&logs, PROGRAM), vec![vec![1, 2], vec![5, 6]]);
+        assert_eq!(program_data(&logs, OTHER), vec![vec![3, 4]]);
+    }
+
+    #[test]
+    fn failed_frames_are_popped() {
+        let logs = [
+            format!("Program {PROGRAM} invoke [1]"),
+            format!("Program {OTHER} invoke [2]"),
+            format!("Program {OTHER} failed: custom program error: 0x1"),
+            "Program data: AQI=".to_string(),
+        ];
+        assert_eq!(program_data(&logs, PROGRAM), vec![vec![1, 2]]);
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::queue::Queue;
+use crate::todo::Todo;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum DepError {
+    UnknownTodo(u64),
+    SelfBlocked(u64),
+    Cycle(Vec<u64>),
+}
+
+impl fmt::Display for DepError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DepError::UnknownTodo(id) => write!(f, "no todo with id {id}"),
+            DepError::SelfBlocked(id) => write!(f, "todo {id} cannot be blocked by itself"),
+            DepError::Cycle(path) => {
+                let path: Vec<String> = path.iter().map(u64::to_string).collect();
+                write!(f, "dependency cycle: {}", path.join(" -> "))
+            }
+        }
+    }
+}
+
+fn find(queue: &Queue<Todo>, id: u64) -> Option<&Todo> {
+    queue.iter().find(|t| t.id == id)
+}
+
+/// Checks that `id` may be blocked by `blocked_by`: every blocker must be
+/// queued, and no blocker may (transitively) depend on `id`.
+pub fn validate(queue: &Queue<Todo>, id: u64, blocked_by: &[u64]) -> Result<(), DepError> {
+    for &blocker in blocked_by {
+        if blocker == id {
+            return Err(DepError::SelfBlocked(id));
+        }
+        if find(queue, blocker).is_none() {
+            return Err(DepError::UnknownTodo(blocker));
+        }
+    }
+    for &blocker in blocked_by {
+        let mut path = vec![id, blocker];
+        let mut visited = HashSet::new();
+        if reaches(queue, blocker, id, &mut visited, &mut path) {
+            return Err(DepError::Cycle(path));
+        }
+    }
+    Ok(())
+}
+
+// Depth-first search along `blocked_by` edges; on success `path` holds the
+// route from the start node to `target`.
+fn reaches(
+    queue: &Queue<Todo>,
+    from: u64,
+    target: u64,
+    visited: &mut HashSet<u64>,
+    path: &mut Vec<u64>,
+) -> bool {
+    if !visited.insert(from) {
+        return false;
+    }
+    let Some(todo) = find(queue, from) else {
+        return false;
+    };
+    for &next in &todo.blocked_by {
+        path.push(next);
+        if next == target || reaches(queue, next, target, visited, path) {
+            return true;
+        }
+        path.pop();
+    }
+    false
+}
+
+/// A todo is blocked while any of its blockers is still queued.
+pub fn is_blocked(queue: &Queue<Todo>, todo: &Todo) -> bool {
+    todo.blocked_by.iter().any(|&id| find(queue, id).is_some())
+}
+
+/// Removes the oldest todo that has no queued blockers, and drops its id
+/// from the `blocked_by` lists of the todos it was blocking.
+pub fn dequeue_unblocked(queue: &mut Queue<Todo>) -> Option<Todo> {
+    let index = queue.iter().position(|t| !is_blocked(queue, t))?;
+    let todo = queue.remove(index)?;
+    for other in queue.iter_mut() {
+        other.blocked_by.retain(|&id| id != todo.id);
+    }
+    Some(todo)
+}
+
+/// Renders `todos` with the queued todos each one is waiting on.
+pub fn render_graph(queue: &Queue<Todo>, todos: &[&Todo]) -> String {
+    let mut out = String::new();
+    for todo in todos {
+        let status = if is_blocked(queue, todo) {
+            "blocked"
+        } else {
+            "ready"
+        };
+        out.push_str(&format!("[{}] {} ({status})\n", todo.id, todo.description));
+        for blocker in todo.blocked_by.iter().filter_map(|&id| find(queue, id)) {
+            out.push_str(&format!("  <- [{}] {}\n", blocker.id, blocker.description));
+        }
+    }
+    out
+}
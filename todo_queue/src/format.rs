@@ -0,0 +1,79 @@
+use clap::ValueEnum;
+
+use crate::todo::Todo;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+/// Renders the gap between `then` and `now` as a short human string,
+/// e.g. `just now`, `5m ago`, `3h ago`, `2d ago` or `in 10m`.
+pub fn relative_time(then: u64, now: u64) -> String {
+    let (secs, future) = if then > now {
+        (then - now, true)
+    } else {
+        (now - then, false)
+    };
+    if secs < 60 {
+        return "just now".to_string();
+    }
+    let (amount, unit) = match secs {
+        s if s < 3_600 => (s / 60, "m"),
+        s if s < 86_400 => (s / 3_600, "h"),
+        s if s < 7 * 86_400 => (s / 86_400, "d"),
+        s if s < 365 * 86_400 => (s / (7 * 86_400), "w"),
+        s => (s / (365 * 86_400), "y"),
+    };
+    if future {
+        format!("in {amount}{unit}")
+    } else {
+        format!("{amount}{unit} ago")
+    }
+}
+
+pub fn render(todos: &[&Todo], format: OutputFormat, now: u64) -> String {
+    match format {
+        OutputFormat::Text => todos
+            .iter()
+            .map(|t| {
+                format!(
+                    "[{}] {} ({})\n",
+                    t.id,
+                    t.description,
+                    relative_time(t.created_at, now)
+                )
+            })
+            .collect(),
+        OutputFormat::Json => {
+            let mut out = serde_json::to_string_pretty(todos).expect("failed to serialize todos");
+            out.push('\n');
+            out
+        }
+        OutputFormat::Csv => {
+            let mut out = String::from("id,description,created_at,blocked_by\n");
+            for t in todos {
+                let blocked_by: Vec<String> = t.blocked_by.iter().map(u64::to_string).collect();
+                out.push_str(&format!(
+                    "{},{},{},{}\n",
+                    t.id,
+                    csv_field(&t.description),
+                    t.created_at,
+                    blocked_by.join(";")
+                ));
+            }
+            out
+        }
+    }
+}
+
+// Quotes a field per RFC 4180 when it contains a delimiter, quote or newline.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -1,3 +1,6 @@
+pub mod deps;
+pub mod format;
+pub mod query;
 pub mod queue;
 pub mod store;
 pub mod todo;
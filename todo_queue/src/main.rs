@@ -1,8 +1,12 @@
 use std::path::Path;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use clap::{Parser, Subcommand};
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, Subcommand};
 
+use todo_queue::deps;
+use todo_queue::format::{self, OutputFormat};
+use todo_queue::query::{self, Matcher, Query, SortKey};
 use todo_queue::store;
 use todo_queue::todo::Todo;
 
@@ -18,49 +22,125 @@ struct Cli {
 #[derive(Subcommand)]
 enum Command {
     /// Add a new todo
-    Add { description: String },
-    /// List all todos in FIFO order
-    List,
-    /// Complete (dequeue) the oldest todo
+    Add {
+        description: String,
+        /// Ids of todos that must be completed first (comma-separated)
+        #[arg(short, long, value_delimiter = ',')]
+        blocked_by: Vec<u64>,
+    },
+    /// List todos in FIFO order, optionally filtered and sorted
+    List {
+        /// Only show todos whose description contains this text (case-insensitive)
+        #[arg(short, long, conflicts_with = "regex")]
+        search: Option<String>,
+        /// Only show todos whose description matches this regex
+        #[arg(short, long)]
+        regex: Option<String>,
+        /// Only show todos created at or after this time (timestamp, YYYY-MM-DD, or e.g. 3d)
+        #[arg(long)]
+        after: Option<String>,
+        /// Only show todos created before this time (timestamp, YYYY-MM-DD, or e.g. 3d)
+        #[arg(long)]
+        before: Option<String>,
+        /// Sort by this field instead of queue order
+        #[arg(long, value_enum)]
+        sort: Option<SortKey>,
+        /// Reverse the output order
+        #[arg(long)]
+        reverse: bool,
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+        /// Show the dependency graph: each todo with the todos blocking it
+        #[arg(long, conflicts_with = "format")]
+        blocked: bool,
+    },
+    /// Complete (dequeue) the oldest todo that isn't blocked
     Done,
 }
 
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+fn fail(message: String) -> ! {
+    Cli::command().error(ErrorKind::ValueValidation, message).exit()
+}
+
 fn main() {
     let cli = Cli::parse();
     let path = Path::new(DATA_FILE);
-    let mut queue = store::load(path);
+    let mut queue = store::load(path).unwrap_or_else(|e| {
+        eprintln!("error: failed to load {}: {e}", path.display());
+        std::process::exit(1)
+    });
 
     match cli.command {
-        Command::Add { description } => {
+        Command::Add {
+            description,
+            mut blocked_by,
+        } => {
             let max_id = queue.iter().map(|t| t.id).max().unwrap_or(0);
-            let now = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs();
+            let id = max_id + 1;
+            blocked_by.sort_unstable();
+            blocked_by.dedup();
+            deps::validate(&queue, id, &blocked_by).unwrap_or_else(|e| fail(e.to_string()));
             let todo = Todo {
-                id: max_id + 1,
+                id,
                 description,
-                created_at: now,
+                created_at: now(),
+                blocked_by,
             };
             println!("Added: {todo}");
             queue.enqueue(todo);
             store::save(path, &queue).expect("failed to save");
         }
-        Command::List => {
-            if queue.is_empty() {
+        Command::List {
+            search,
+            regex,
+            after,
+            before,
+            sort,
+            reverse,
+            format,
+            blocked,
+        } => {
+            let now = now();
+            let matcher = match (search, regex) {
+                (Some(needle), _) => Some(Matcher::substring(&needle)),
+                (None, Some(pattern)) => {
+                    Some(Matcher::regex(&pattern).unwrap_or_else(|e| fail(e.to_string())))
+                }
+                (None, None) => None,
+            };
+            let parse = |s: String| query::parse_time(&s, now).unwrap_or_else(|e| fail(e));
+            let query = Query {
+                matcher,
+                after: after.map(parse),
+                before: before.map(parse),
+                sort,
+                reverse,
+            };
+
+            let todos = query.apply(queue.iter());
+            if todos.is_empty() && format == OutputFormat::Text {
                 println!("No todos.");
+            } else if blocked {
+                print!("{}", deps::render_graph(&queue, &todos));
             } else {
-                for todo in queue.iter() {
-                    println!("{todo}");
-                }
+                print!("{}", format::render(&todos, format, now));
             }
         }
-        Command::Done => match queue.dequeue() {
+        Command::Done => match deps::dequeue_unblocked(&mut queue) {
             Some(todo) => {
                 println!("Done: {todo}");
                 store::save(path, &queue).expect("failed to save");
             }
-            None => println!("No todos to complete."),
+            None if queue.is_empty() => println!("No todos to complete."),
+            None => println!("Every remaining todo is blocked."),
         },
     }
 }
@@ -0,0 +1,157 @@
+use clap::ValueEnum;
+use regex::Regex;
+
+use crate::todo::Todo;
+
+const SECS_PER_DAY: u64 = 86_400;
+
+/// How a search term is matched against a todo's description.
+#[derive(Debug, Clone)]
+pub enum Matcher {
+    /// Case-insensitive substring match (needle stored lowercased).
+    Substring(String),
+    Regex(Regex),
+}
+
+impl Matcher {
+    pub fn substring(needle: &str) -> Self {
+        Matcher::Substring(needle.to_lowercase())
+    }
+
+    pub fn regex(pattern: &str) -> Result<Self, regex::Error> {
+        Ok(Matcher::Regex(Regex::new(pattern)?))
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        match self {
+            Matcher::Substring(needle) => text.to_lowercase().contains(needle),
+            Matcher::Regex(re) => re.is_match(text),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SortKey {
+    Id,
+    Created,
+    Description,
+}
+
+/// Filters and ordering applied by `list`. The default query keeps every
+/// todo in FIFO order.
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    pub matcher: Option<Matcher>,
+    /// Inclusive lower bound on `created_at`.
+    pub after: Option<u64>,
+    /// Exclusive upper bound on `created_at`.
+    pub before: Option<u64>,
+    pub sort: Option<SortKey>,
+    pub reverse: bool,
+}
+
+impl Query {
+    pub fn matches(&self, todo: &Todo) -> bool {
+        if let Some(matcher) = &self.matcher {
+            if !matcher.is_match(&todo.description) {
+                return false;
+            }
+        }
+        if self.after.is_some_and(|after| todo.created_at < after) {
+            return false;
+        }
+        if self.before.is_some_and(|before| todo.created_at >= before) {
+            return false;
+        }
+        true
+    }
+
+    pub fn apply<'a>(&self, todos: impl IntoIterator<Item = &'a Todo>) -> Vec<&'a Todo> {
+        let mut out: Vec<&Todo> = todos.into_iter().filter(|t| self.matches(t)).collect();
+        // Stable sorts keep FIFO order between equal keys.
+        match self.sort {
+            Some(SortKey::Id) => out.sort_by_key(|t| t.id),
+            Some(SortKey::Created) => out.sort_by_key(|t| t.created_at),
+            Some(SortKey::Description) => {
+                out.sort_by_cached_key(|t| t.description.to_lowercase())
+            }
+            None => {}
+        }
+        if self.reverse {
+            out.reverse();
+        }
+        out
+    }
+}
+
+/// Parses a point in time given on the command line.
+///
+/// Accepts a unix timestamp (`1700000000`), a `YYYY-MM-DD` date (UTC
+/// midnight), or a duration relative to `now` such as `90s`, `30m`, `3h`,
+/// `2d` or `1w`.
+pub fn parse_time(s: &str, now: u64) -> Result<u64, String> {
+    let s = s.trim();
+    if !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()) {
+        return s.parse().map_err(|_| format!("timestamp out of range: {s}"));
+    }
+    if let Some(secs) = parse_date(s) {
+        return Ok(secs);
+    }
+    if let Some(ago) = parse_duration(s) {
+        return Ok(now.saturating_sub(ago));
+    }
+    Err(format!(
+        "invalid time '{s}': expected a unix timestamp, YYYY-MM-DD, or a duration like 3h"
+    ))
+}
+
+fn parse_duration(s: &str) -> Option<u64> {
+    let split = s.find(|c: char| !c.is_ascii_digit())?;
+    let (amount, unit) = s.split_at(split);
+    let amount: u64 = amount.parse().ok()?;
+    let unit = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3_600,
+        "d" => SECS_PER_DAY,
+        "w" => 7 * SECS_PER_DAY,
+        _ => return None,
+    };
+    amount.checked_mul(unit)
+}
+
+fn parse_date(s: &str) -> Option<u64> {
+    let mut parts = s.split('-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: u32 = parts.next()?.parse().ok()?;
+    let day: u32 = parts.next()?.parse().ok()?;
+    if parts.next().is_some() || !(1..=12).contains(&month) {
+        return None;
+    }
+    if !(1..=days_in_month(year, month)).contains(&day) {
+        return None;
+    }
+    let days = days_from_civil(year, month, day);
+    u64::try_from(days).ok().map(|d| d * SECS_PER_DAY)
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Days since 1970-01-01 for a proleptic Gregorian date (Howard Hinnant's
+// `days_from_civil`).
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let m = i64::from(month);
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
@@ -7,6 +7,12 @@ pub struct Queue<T> {
     items: VecDeque<T>,
 }
 
+impl<T> Default for Queue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T> Queue<T> {
     pub fn new() -> Self {
         Self {
@@ -22,6 +28,10 @@ impl<T> Queue<T> {
         self.items.pop_front()
     }
 
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        self.items.remove(index)
+    }
+
     pub fn peek(&self) -> Option<&T> {
         self.items.front()
     }
@@ -37,4 +47,8 @@ impl<T> Queue<T> {
     pub fn iter(&self) -> impl Iterator<Item = &T> {
         self.items.iter()
     }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.items.iter_mut()
+    }
 }
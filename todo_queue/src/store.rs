@@ -1,18 +1,59 @@
+use std::io;
 use std::path::Path;
 
-use borsh::{from_slice, to_vec};
+use borsh::{from_slice, BorshDeserialize, BorshSerialize};
 
 use crate::queue::Queue;
 use crate::todo::Todo;
 
-pub fn save(path: &Path, queue: &Queue<Todo>) -> std::io::Result<()> {
-    let bytes = to_vec(queue).expect("failed to serialize queue");
+/// Prefix of versioned save files. Files without it predate versioning and
+/// hold a bare Borsh `Queue<Todo>`, with or without `blocked_by`.
+const MAGIC: &[u8; 4] = b"TDQ\0";
+const VERSION: u8 = 1;
+
+/// `Todo` as saved before `blocked_by` existed.
+#[derive(BorshDeserialize)]
+struct LegacyTodo {
+    id: u64,
+    description: String,
+    created_at: u64,
+}
+
+pub fn save(path: &Path, queue: &Queue<Todo>) -> io::Result<()> {
+    let mut bytes = MAGIC.to_vec();
+    bytes.push(VERSION);
+    queue.serialize(&mut bytes)?;
     std::fs::write(path, bytes)
 }
 
-pub fn load(path: &Path) -> Queue<Todo> {
-    match std::fs::read(path) {
-        Ok(bytes) => from_slice(&bytes).expect("failed to deserialize queue"),
-        Err(_) => Queue::new(),
+/// Loads the queue, or an empty one if the file doesn't exist. Unversioned
+/// files are migrated; unreadable ones are an `InvalidData` error.
+pub fn load(path: &Path) -> io::Result<Queue<Todo>> {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Queue::new()),
+        Err(e) => return Err(e),
+    };
+    match bytes.strip_prefix(MAGIC) {
+        Some([VERSION, rest @ ..]) => from_slice(rest),
+        Some(_) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "unsupported todo file version",
+        )),
+        None => from_slice(&bytes).or_else(|_| load_legacy(&bytes)),
+    }
+}
+
+fn load_legacy(bytes: &[u8]) -> io::Result<Queue<Todo>> {
+    let mut legacy: Queue<LegacyTodo> = from_slice(bytes)?;
+    let mut queue = Queue::new();
+    while let Some(todo) = legacy.dequeue() {
+        queue.enqueue(Todo {
+            id: todo.id,
+            description: todo.description,
+            created_at: todo.created_at,
+            blocked_by: Vec::new(),
+        });
     }
+    Ok(queue)
 }
@@ -1,12 +1,15 @@
 use std::fmt;
 
 use borsh::{BorshDeserialize, BorshSerialize};
+use serde::Serialize;
 
-#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize, Serialize)]
 pub struct Todo {
     pub id: u64,
     pub description: String,
     pub created_at: u64,
+    /// Ids of queued todos that must be completed before this one.
+    pub blocked_by: Vec<u64>,
 }
 
 impl fmt::Display for Todo {
@@ -1,5 +1,8 @@
 use std::path::Path;
 
+use todo_queue::deps::{self, DepError};
+use todo_queue::format::{self, OutputFormat};
+use todo_queue::query::{self, Matcher, Query, SortKey};
 use todo_queue::queue::Queue;
 use todo_queue::store;
 use todo_queue::todo::Todo;
@@ -9,6 +12,7 @@ fn make_todo(id: u64, desc: &str) -> Todo {
         id,
         description: desc.to_string(),
         created_at: 1000 + id,
+        blocked_by: vec![],
     }
 }
 
@@ -53,7 +57,7 @@ fn persistence_roundtrip() {
     q.enqueue(make_todo(2, "Second"));
     store::save(path, &q).unwrap();
 
-    let loaded = store::load(path);
+    let loaded = store::load(path).unwrap();
     assert_eq!(loaded.len(), 2);
     assert_eq!(loaded.peek().unwrap().id, 1);
 
@@ -64,10 +68,52 @@ fn persistence_roundtrip() {
 fn load_missing_file_returns_empty() {
     let path = Path::new("/tmp/todo_queue_test_missing.bin");
     let _ = std::fs::remove_file(path);
-    let q = store::load(path);
+    let q = store::load(path).unwrap();
     assert!(q.is_empty());
 }
 
+#[test]
+fn load_migrates_files_without_blocked_by() {
+    #[derive(borsh::BorshSerialize)]
+    struct OldTodo {
+        id: u64,
+        description: String,
+        created_at: u64,
+    }
+
+    let path = Path::new("/tmp/todo_queue_test_legacy.bin");
+    let mut old = Queue::new();
+    old.enqueue(OldTodo { id: 1, description: "Old".to_string(), created_at: 1001 });
+    std::fs::write(path, borsh::to_vec(&old).unwrap()).unwrap();
+
+    let mut loaded = store::load(path).unwrap();
+    let todo = loaded.dequeue().unwrap();
+    assert_eq!((todo.id, todo.description.as_str(), todo.created_at), (1, "Old", 1001));
+    assert!(todo.blocked_by.is_empty());
+    assert!(loaded.is_empty());
+
+    // Unversioned files that already carry blocked_by still load
+    let mut q = Queue::new();
+    q.enqueue(make_blocked(2, "New", &[1]));
+    std::fs::write(path, borsh::to_vec(&q).unwrap()).unwrap();
+    assert_eq!(store::load(path).unwrap().peek().unwrap().blocked_by, vec![1]);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn load_corrupt_file_is_an_error() {
+    let path = Path::new("/tmp/todo_queue_test_corrupt.bin");
+    std::fs::write(path, b"not a todo file").unwrap();
+    assert!(store::load(path).is_err());
+
+    // Versioned header with an unknown version
+    std::fs::write(path, b"TDQ\0\x09").unwrap();
+    assert!(store::load(path).is_err());
+
+    std::fs::remove_file(path).unwrap();
+}
+
 #[test]
 fn done_removes_oldest() {
     let path = Path::new("/tmp/todo_queue_test_done.bin");
@@ -78,7 +124,7 @@ fn done_removes_oldest() {
     q.enqueue(make_todo(2, "Newer"));
     store::save(path, &q).unwrap();
 
-    let mut loaded = store::load(path);
+    let mut loaded = store::load(path).unwrap();
     let removed = loaded.dequeue().unwrap();
     assert_eq!(removed.id, 1);
     assert_eq!(removed.description, "Oldest");
@@ -87,3 +133,183 @@ fn done_removes_oldest() {
 
     std::fs::remove_file(path).unwrap();
 }
+
+#[test]
+fn query_substring_search_is_case_insensitive() {
+    let todos = [make_todo(1, "Buy groceries"), make_todo(2, "Walk dog")];
+    let query = Query {
+        matcher: Some(Matcher::substring("GROC")),
+        ..Query::default()
+    };
+    let ids: Vec<u64> = query.apply(&todos).iter().map(|t| t.id).collect();
+    assert_eq!(ids, vec![1]);
+}
+
+#[test]
+fn query_regex_search() {
+    let todos = [
+        make_todo(1, "release v1.2"),
+        make_todo(2, "release notes"),
+        make_todo(3, "v2 planning"),
+    ];
+    let query = Query {
+        matcher: Some(Matcher::regex(r"v\d+").unwrap()),
+        ..Query::default()
+    };
+    let ids: Vec<u64> = query.apply(&todos).iter().map(|t| t.id).collect();
+    assert_eq!(ids, vec![1, 3]);
+    assert!(Matcher::regex("(").is_err());
+}
+
+#[test]
+fn query_date_range_and_sort() {
+    // created_at = 1000 + id
+    let todos = [
+        make_todo(3, "c"),
+        make_todo(1, "b"),
+        make_todo(2, "a"),
+        make_todo(4, "d"),
+    ];
+    let query = Query {
+        after: Some(1002),
+        before: Some(1004),
+        ..Query::default()
+    };
+    let ids: Vec<u64> = query.apply(&todos).iter().map(|t| t.id).collect();
+    assert_eq!(ids, vec![3, 2]); // FIFO order, `before` is exclusive
+
+    let query = Query {
+        sort: Some(SortKey::Description),
+        reverse: true,
+        ..Query::default()
+    };
+    let ids: Vec<u64> = query.apply(&todos).iter().map(|t| t.id).collect();
+    assert_eq!(ids, vec![4, 3, 1, 2]);
+}
+
+#[test]
+fn parse_time_formats() {
+    let now = 1_700_000_000;
+    assert_eq!(query::parse_time("1234", now), Ok(1234));
+    assert_eq!(query::parse_time("3h", now), Ok(now - 3 * 3600));
+    assert_eq!(query::parse_time("2d", now), Ok(now - 2 * 86_400));
+    assert_eq!(query::parse_time("1970-01-02", now), Ok(86_400));
+    assert_eq!(query::parse_time("2024-03-01", now), Ok(1_709_251_200));
+    assert!(query::parse_time("yesterday", now).is_err());
+    assert!(query::parse_time("3x", now).is_err());
+}
+
+#[test]
+fn parse_time_rejects_impossible_dates() {
+    let now = 1_700_000_000;
+    assert!(query::parse_time("2024-02-31", now).is_err());
+    assert!(query::parse_time("2023-02-29", now).is_err());
+    assert!(query::parse_time("1900-02-29", now).is_err());
+    assert!(query::parse_time("2024-04-31", now).is_err());
+    assert_eq!(query::parse_time("2024-02-29", now), Ok(1_709_164_800));
+    assert_eq!(query::parse_time("2000-02-29", now), Ok(951_782_400));
+}
+
+#[test]
+fn relative_time_units() {
+    let now = 10_000_000;
+    assert_eq!(format::relative_time(now - 5, now), "just now");
+    assert_eq!(format::relative_time(now - 120, now), "2m ago");
+    assert_eq!(format::relative_time(now - 3 * 3600, now), "3h ago");
+    assert_eq!(format::relative_time(now - 2 * 86_400, now), "2d ago");
+    assert_eq!(format::relative_time(now - 21 * 86_400, now), "3w ago");
+    assert_eq!(format::relative_time(now + 600, now), "in 10m");
+}
+
+#[test]
+fn render_text_json_csv() {
+    let a = make_todo(1, "plain");
+    let b = make_todo(2, "has, comma \"quoted\"");
+    let todos = vec![&a, &b];
+
+    let text = format::render(&todos, OutputFormat::Text, 1001 + 3600);
+    assert_eq!(text, "[1] plain (1h ago)\n[2] has, comma \"quoted\" (59m ago)\n");
+
+    let json: serde_json::Value =
+        serde_json::from_str(&format::render(&todos, OutputFormat::Json, 0)).unwrap();
+    assert_eq!(json[1]["id"], 2);
+    assert_eq!(json[1]["description"], "has, comma \"quoted\"");
+    assert_eq!(json[0]["created_at"], 1001);
+
+    let csv = format::render(&todos, OutputFormat::Csv, 0);
+    assert_eq!(
+        csv,
+        "id,description,created_at,blocked_by\n1,plain,1001,\n2,\"has, comma \"\"quoted\"\"\",1002,\n"
+    );
+}
+
+fn make_blocked(id: u64, desc: &str, blocked_by: &[u64]) -> Todo {
+    Todo {
+        blocked_by: blocked_by.to_vec(),
+        ..make_todo(id, desc)
+    }
+}
+
+#[test]
+fn done_skips_blocked_todos() {
+    let mut q = Queue::new();
+    q.enqueue(make_blocked(1, "Publish", &[3]));
+    q.enqueue(make_blocked(2, "Tag", &[3]));
+    q.enqueue(make_todo(3, "Changelog"));
+    q.enqueue(make_todo(4, "Announce"));
+
+    assert_eq!(deps::dequeue_unblocked(&mut q).unwrap().id, 3);
+    // Completing 3 unblocks 1 and 2, which are older than 4.
+    assert!(q.iter().all(|t| t.blocked_by.is_empty()));
+    assert_eq!(deps::dequeue_unblocked(&mut q).unwrap().id, 1);
+    assert_eq!(deps::dequeue_unblocked(&mut q).unwrap().id, 2);
+    assert_eq!(deps::dequeue_unblocked(&mut q).unwrap().id, 4);
+    assert!(deps::dequeue_unblocked(&mut q).is_none());
+}
+
+#[test]
+fn validate_rejects_unknown_self_and_cycles() {
+    let mut q = Queue::new();
+    q.enqueue(make_todo(1, "a"));
+    q.enqueue(make_blocked(2, "b", &[1]));
+    q.enqueue(make_blocked(3, "c", &[2]));
+
+    assert_eq!(deps::validate(&q, 4, &[3, 1]), Ok(()));
+    assert_eq!(deps::validate(&q, 4, &[9]), Err(DepError::UnknownTodo(9)));
+    assert_eq!(deps::validate(&q, 4, &[4]), Err(DepError::SelfBlocked(4)));
+    // Re-blocking 1 on 3 would close 1 -> 3 -> 2 -> 1.
+    assert_eq!(
+        deps::validate(&q, 1, &[3]),
+        Err(DepError::Cycle(vec![1, 3, 2, 1]))
+    );
+}
+
+#[test]
+fn blocked_by_survives_persistence() {
+    let path = Path::new("/tmp/todo_queue_test_blocked.bin");
+    let _ = std::fs::remove_file(path);
+
+    let mut q = Queue::new();
+    q.enqueue(make_todo(1, "First"));
+    q.enqueue(make_blocked(2, "Second", &[1]));
+    store::save(path, &q).unwrap();
+
+    let loaded = store::load(path).unwrap();
+    let second = loaded.iter().find(|t| t.id == 2).unwrap();
+    assert_eq!(second.blocked_by, vec![1]);
+    assert!(deps::is_blocked(&loaded, second));
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn render_dependency_graph() {
+    let mut q = Queue::new();
+    q.enqueue(make_todo(1, "Changelog"));
+    q.enqueue(make_blocked(2, "Publish", &[1]));
+    let todos: Vec<&Todo> = q.iter().collect();
+    assert_eq!(
+        deps::render_graph(&q, &todos),
+        "[1] Changelog (ready)\n[2] Publish (blocked)\n  <- [1] Changelog\n"
+    );
+}